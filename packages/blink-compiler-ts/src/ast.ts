@@ -55,9 +55,15 @@ export interface EventDef {
   type: 'event';
   name: string;
   fields: FieldDef[];
+  /** Same-time phase from `event Name phase <phase> { ... }`; defaults to `actions`. */
+  phase?: EventPhase;
   span: Span;
 }
 
+/** Phases within a timestamp, in firing order. */
+export const EVENT_PHASES = ['start_of_tick', 'actions', 'effects', 'cleanup'] as const;
+export type EventPhase = (typeof EVENT_PHASES)[number];
+
 // ===== Rule Definition =====
 
 export interface RuleDef {
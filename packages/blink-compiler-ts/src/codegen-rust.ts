@@ -368,17 +368,31 @@ export class RustCodeGenerator {
     }
     // Call expression: infer return type from function name
     if (expr.type === 'call') {
+      // min(list) / max(list) return the list's element type
+      if ((expr.name === 'min' || expr.name === 'max') && expr.args.length === 1) return 'unknown';
       switch (expr.name) {
         case 'min': case 'max': case 'floor': case 'ceil':
         case 'round': case 'abs': case 'random': case 'random_range':
+        case 'clamp': case 'pow': case 'sqrt': case 'log': case 'exp': case 'lerp':
+        case 'inverse_lerp': case 'sign': case 'round_to': case 'curve_exp': case 'curve_logistic':
           return 'decimal'; // brl_* builtins always return f64
-        case 'len':
+        case 'div_floor': case 'mod_floor':
+          return 'integer';
+        case 'random_normal': case 'random_exponential':
+          return 'decimal';
+        case 'len': case 'random_int': case 'random_poisson': case 'random_binomial':
+        case 'random_weighted_index': case 'roll':
           return 'integer';
         case 'concat': case 'to_string': case 'str_replace': case 'str_pick_variant':
+        case 'format': case 'format_named': case 'tr': case 'tr_named':
+        case 'str_join': case 'str_upper': case 'str_lower': case 'str_capitalize': case 'str_trim':
+        case 'str_substring': case 'str_pad_start': case 'str_pad_end':
           return 'string';
-        case 'str_contains':
+        case 'str_contains': case 'contains': case 'str_starts_with': case 'str_ends_with':
           return 'boolean';
-        case 'str_count_variants':
+        case 'index_of':
+          return 'integer';
+        case 'str_count_variants': case 'str_length':
           return 'integer';
       }
       return 'unknown'; // user-defined functions
@@ -432,17 +446,49 @@ export class RustCodeGenerator {
   private generateComponentsFile(): string {
     let code = '// Generated by blink-compiler-ts - do not edit\n';
     code += '#![allow(dead_code, unused_imports)]\n\n';
-    code += 'use blink_runtime::interning::InternedString;\n';
+    code += 'use blink_runtime::interning::{InternedString, LiveStrings, MarkStrings};\n';
     code += 'use blink_runtime::EntityId;\n\n';
 
     for (const comp of this.componentDefs) {
       code += this.generateComponentStruct(comp);
       code += '\n';
+      if (this.componentHoldsStrings(comp)) {
+        code += this.generateMarkStringsImpl(comp);
+        code += '\n';
+      }
     }
 
     return code;
   }
 
+  /** Whether a field type holds interned strings (directly or in a list). */
+  private typeHoldsStrings(type: AST.TypeExpr): boolean {
+    switch (type.type) {
+      case 'string': return true;
+      case 'list': return this.typeHoldsStrings(type.element);
+      case 'optional': return this.typeHoldsStrings(type.inner);
+      default: return false;
+    }
+  }
+
+  private componentHoldsStrings(comp: AST.ComponentDef): boolean {
+    return comp.fields.some(f => this.typeHoldsStrings(f.fieldType));
+  }
+
+  /** Lets string collection see the component's string fields. */
+  private generateMarkStringsImpl(comp: AST.ComponentDef): string {
+    let code = `impl MarkStrings for ${comp.name} {\n`;
+    code += `    fn mark_strings(&self, live: &mut LiveStrings) {\n`;
+    for (const field of comp.fields) {
+      if (this.typeHoldsStrings(field.fieldType)) {
+        code += `        self.${this.toSnakeCase(field.name)}.mark_strings(live);\n`;
+      }
+    }
+    code += `    }\n`;
+    code += `}\n`;
+    return code;
+  }
+
   private generateComponentStruct(comp: AST.ComponentDef): string {
     let code = `#[derive(Clone, Debug, Default)]\n`;
     code += `pub struct ${comp.name} {\n`;
@@ -770,6 +816,7 @@ export class RustCodeGenerator {
     code += 'use crate::components::*;\n\n';
 
     code += '/// Create a component from a JSON string and attach it to an entity.\n';
+    code += '/// Names without a generated type fall back to schema-defined (dynamic) components.\n';
     code += '/// Returns true on success, false if the component name is unknown or the JSON is invalid.\n';
     code += 'pub fn add_component_from_json(\n';
     code += '    engine: &mut Engine,\n';
@@ -797,7 +844,7 @@ export class RustCodeGenerator {
       code += `        }\n`;
     }
 
-    code += '        _ => false,\n';
+    code += '        _ => engine.world.add_dynamic_component_from_json(entity_id, component_name, &v, &mut engine.interner),\n';
     code += '    }\n';
     code += '}\n\n';
 
@@ -826,7 +873,10 @@ export class RustCodeGenerator {
       code += `        }\n`;
     }
 
-    code += '        _ => "{}".to_string(),\n';
+    code += '        _ => engine\n';
+    code += '            .world\n';
+    code += '            .dynamic_component_json(entity_id, component_name, &engine.interner)\n';
+    code += '            .unwrap_or_else(|| "{}".to_string()),\n';
     code += '    }\n';
     code += '}\n\n';
 
@@ -842,13 +892,36 @@ export class RustCodeGenerator {
       code += `        "${comp.name}" => engine.world.query_component::<${comp.name}>(),\n`;
     }
 
-    code += '        _ => vec![],\n';
+    code += '        _ => engine.world.query_dynamic(component_name),\n';
     code += '    }\n';
     code += '}\n';
 
     return code;
   }
 
+  /** Runtime `ValueType` name used in event schemas for a BRL type. */
+  private schemaTypeName(type: AST.TypeExpr): string {
+    switch (type.type) {
+      case 'integer':
+        return 'integer';
+      case 'decimal':
+      case 'number':
+        return 'number';
+      case 'string':
+        return 'string';
+      case 'boolean':
+        return 'boolean';
+      case 'id':
+        return 'entity';
+      case 'list':
+        return 'list';
+      case 'optional':
+        return this.schemaTypeName(type.inner);
+      default:
+        return 'any';
+    }
+  }
+
   /**
    * Generate Rust expression that deserialises one JSON field into the target Rust type.
    * `jsonKey` is the key in the JSON object (original BRL camelCase name).
@@ -951,8 +1024,53 @@ export class RustCodeGenerator {
     code += '/// Register all component types with the engine.\n';
     code += 'pub fn register_components(engine: &mut Engine) {\n';
     for (const comp of this.componentDefs) {
-      code += `    engine.world.register_component::<${comp.name}>();\n`;
+      // Components with string fields register their strings for collection.
+      const register = this.componentHoldsStrings(comp) ? 'register_component_strings' : 'register_component';
+      code += `    engine.world.${register}::<${comp.name}>();\n`;
+    }
+    code += '}\n\n';
+
+    // Generate event schemas from `event` declarations. `source` and `target`
+    // are built-in event properties, not fields, so they are left out.
+    const eventSchemas = this.eventDefs.map(evt => ({
+      name: evt.name,
+      fields: evt.fields
+        .filter(f => f.name !== 'source' && f.name !== 'target')
+        .map(f => ({
+          name: f.name,
+          type: this.schemaTypeName(f.fieldType),
+          required: !f.optional && f.fieldType.type !== 'optional',
+        })),
+    }));
+    code += '/// Event schemas from `event` declarations, in the format\n';
+    code += '/// `EventSchemaRegistry::register_json` reads and the host type-checks against.\n';
+    code += `pub const EVENT_SCHEMAS_JSON: &str = r#"${JSON.stringify(eventSchemas)}"#;\n\n`;
+    code += '/// Register event schemas so scheduled events are validated.\n';
+    code += 'pub fn register_event_schemas(engine: &mut Engine) {\n';
+    code += '    engine\n';
+    code += '        .timeline\n';
+    code += '        .schemas\n';
+    code += '        .register_json(EVENT_SCHEMAS_JSON, &mut engine.interner)\n';
+    code += '        .expect("generated event schemas are valid");\n';
+    code += '}\n\n';
+
+    // Generate the same-time phase of every event declared with `phase`.
+    const eventPhases: Record<string, string> = {};
+    for (const evt of this.eventDefs) {
+      if (evt.phase) {
+        eventPhases[evt.name] = evt.phase;
+      }
     }
+    code += '/// Same-time phases from `event Name phase <phase>` declarations, in the\n';
+    code += '/// format `PhaseTable::register_json` reads.\n';
+    code += `pub const EVENT_PHASES_JSON: &str = r#"${JSON.stringify(eventPhases)}"#;\n\n`;
+    code += '/// Map event types to their phases so same-time events fire in phase order.\n';
+    code += 'pub fn register_event_phases(engine: &mut Engine) {\n';
+    code += '    engine\n';
+    code += '        .timeline\n';
+    code += '        .phases\n';
+    code += '        .register_json(EVENT_PHASES_JSON, &mut engine.interner)\n';
+    code += '        .expect("generated event phases are valid");\n';
     code += '}\n\n';
 
     // Generate the init_string_table function
@@ -967,7 +1085,8 @@ export class RustCodeGenerator {
     code += '}\n\n';
 
     // Generate the main init function
-    code += '/// Initialize the game: register components, intern strings, create entities.\n';
+    code += '/// Initialize the game: register components, intern strings, register event\n';
+    code += '/// schemas and phases, create entities.\n';
     const hasNamedEntities = this.entityDefs.some(e => e.variable);
     if (hasNamedEntities) {
       code += 'pub fn init_game(engine: &mut Engine) -> entities::NamedEntities {\n';
@@ -976,6 +1095,10 @@ export class RustCodeGenerator {
     }
     code += '    register_components(engine);\n';
     code += '    init_string_table(engine);\n';
+    code += '    register_event_schemas(engine);\n';
+    code += '    register_event_phases(engine);\n';
+    code += '    // Everything interned so far is static; later strings can be collected.\n';
+    code += '    engine.interner.mark_static();\n';
     if (hasNamedEntities) {
       code += '    entities::create_initial_entities(engine)\n';
     } else {
@@ -985,9 +1108,10 @@ export class RustCodeGenerator {
 
     // Generate step function
     code += '/// Process one event from the timeline.\n';
-    code += '/// Returns true if an event was processed, false if timeline is empty.\n';
+    code += '/// Returns true if an event was processed, false if timeline is empty\n';
+    code += '/// or the engine is suspended on a pending choice.\n';
     code += 'pub fn step(engine: &mut Engine) -> bool {\n';
-    code += '    if let Some(event) = engine.timeline.pop() {\n';
+    code += '    if let Some(event) = engine.next_event() {\n';
     code += '        dispatch::dispatch_event(&event, engine);\n';
     code += '        true\n';
     code += '    } else {\n';
@@ -1070,7 +1194,7 @@ export class RustCodeGenerator {
         return this.generateCreateStatement(stmt, indent);
 
       case 'delete':
-        return `${pad}engine.world.despawn(${this.exprToRust(stmt.entity)});\n`;
+        return `${pad}engine.despawn(${this.exprToRust(stmt.entity)});\n`;
 
       case 'return':
         if (stmt.value) {
@@ -1525,16 +1649,67 @@ export class RustCodeGenerator {
 
     // Built-in functions
     switch (expr.name) {
-      case 'min': return `brl_min((${args[0]}) as f64, (${args[1]}) as f64)`;
-      case 'max': return `brl_max((${args[0]}) as f64, (${args[1]}) as f64)`;
+      case 'min':
+        if (args.length === 1) return `brl_list_min(&(${args[0]}), &engine.interner)`;
+        return `brl_min((${args[0]}) as f64, (${args[1]}) as f64)`;
+      case 'max':
+        if (args.length === 1) return `brl_list_max(&(${args[0]}), &engine.interner)`;
+        return `brl_max((${args[0]}) as f64, (${args[1]}) as f64)`;
       case 'floor': return `brl_floor((${args[0]}) as f64)`;
       case 'ceil': return `brl_ceil((${args[0]}) as f64)`;
       case 'round': return `brl_round((${args[0]}) as f64)`;
       case 'abs': return `brl_abs((${args[0]}) as f64)`;
+      case 'clamp': return `brl_clamp((${args[0]}) as f64, (${args[1]}) as f64, (${args[2]}) as f64)`;
+      case 'pow': return `brl_pow((${args[0]}) as f64, (${args[1]}) as f64)`;
+      case 'sqrt': return `brl_sqrt((${args[0]}) as f64)`;
+      case 'log': return `brl_log((${args[0]}) as f64)`;
+      case 'exp': return `brl_exp((${args[0]}) as f64)`;
+      case 'lerp': return `brl_lerp((${args[0]}) as f64, (${args[1]}) as f64, (${args[2]}) as f64)`;
+      case 'inverse_lerp': return `brl_inverse_lerp((${args[0]}) as f64, (${args[1]}) as f64, (${args[2]}) as f64)`;
+      case 'sign': return `brl_sign((${args[0]}) as f64)`;
+      case 'div_floor': return `brl_div_floor((${args[0]}) as i64, (${args[1]}) as i64)`;
+      case 'mod_floor': return `brl_mod_floor((${args[0]}) as i64, (${args[1]}) as i64)`;
+      case 'round_to': return `brl_round_to((${args[0]}) as f64, (${args[1]}) as i64)`;
+      case 'curve_exp': return `brl_curve_exp((${args[0]}) as f64, (${args[1]}) as f64, (${args[2]}) as f64)`;
+      case 'curve_logistic':
+        return `brl_curve_logistic(${args.slice(0, 5).map(a => `(${a}) as f64`).join(', ')})`;
       case 'random': return `engine.rng.random()`;
       case 'random_range': return `engine.rng.random_range(${args[0]} as f64, ${args[1]} as f64)`;
+      case 'random_int': return `brl_random_int((${args[0]}) as i64, (${args[1]}) as i64, &mut engine.rng)`;
+      case 'random_normal': return `brl_random_normal((${args[0]}) as f64, (${args[1]}) as f64, &mut engine.rng)`;
+      case 'random_exponential': return `brl_random_exponential((${args[0]}) as f64, &mut engine.rng)`;
+      case 'random_poisson': return `brl_random_poisson((${args[0]}) as f64, &mut engine.rng)`;
+      case 'random_binomial': return `brl_random_binomial((${args[0]}) as i64, (${args[1]}) as f64, &mut engine.rng)`;
+      case 'random_weighted_index':
+        return `brl_random_weighted_index(&(${args[0]}).iter().map(|w| *w as f64).collect::<Vec<f64>>(), &mut engine.rng)`;
+      case 'roll': return `brl_roll(${args[0]}, &mut engine.rng, &engine.interner)`;
+      case 'roll_breakdown': return `brl_roll_breakdown(${args[0]}, &mut engine.rng, &mut engine.interner)`;
+      case 'roll_loot': return `brl_roll_loot(${args[0]}, engine)`;
+      case 'shuffle': return `brl_shuffle(&(${args[0]}), &mut engine.rng)`;
+      case 'sample': return `brl_sample(&(${args[0]}), (${args[1]}) as i64, &mut engine.rng)`;
       case 'len': return `(${args[0]}).len() as i64`;
       case 'entities_having': return `engine.world.query_component::<${this.extractComponentName(expr.args[0])}>()`;
+      // ── List built-ins (each returns a new list) ──
+      case 'push': return `brl_list_push(&(${args[0]}), ${args[1]})`;
+      case 'pop': return `brl_list_pop(&(${args[0]}))`;
+      case 'insert': return `brl_list_insert(&(${args[0]}), (${args[1]}) as i64, ${args[2]})`;
+      case 'remove': return `brl_list_remove(&(${args[0]}), (${args[1]}) as i64)`;
+      case 'contains': return `brl_list_contains(&(${args[0]}), &(${args[1]}))`;
+      case 'index_of': return `brl_list_index_of(&(${args[0]}), &(${args[1]}))`;
+      case 'slice': return `brl_list_slice(&(${args[0]}), (${args[1]}) as i64, (${args[2]}) as i64)`;
+      case 'list_concat': return `brl_list_concat(&(${args[0]}), &(${args[1]}))`;
+      case 'sort': return `brl_list_sort(&(${args[0]}), &engine.interner)`;
+      case 'sort_by': {
+        // sort_by(entities, "Component", "field")
+        const component = this.extractComponentName(expr.args[1]);
+        const field = this.toSnakeCase(this.extractComponentName(expr.args[2]));
+        return `brl_list_sort_by(&(${args[0]}), |e| engine.world.get::<${component}>(*e).${field}.clone(), &engine.interner)`;
+      }
+      case 'reverse': return `brl_list_reverse(&(${args[0]}))`;
+      case 'unique': return `brl_list_unique(&(${args[0]}))`;
+      case 'sum': return `brl_list_sum(&(${args[0]}))`;
+      case 'filter_having':
+        return `brl_list_filter(&(${args[0]}), |e| engine.world.has::<${this.extractComponentName(expr.args[1])}>(*e))`;
       // ── String built-ins ──
       case 'concat': return `brl_concat(${args[0]}, ${args[1]}, &mut engine.interner)`;
       case 'to_string': {
@@ -1547,6 +1722,40 @@ export class RustCodeGenerator {
       case 'str_contains': return `brl_str_contains(${args[0]}, ${args[1]}, &engine.interner)`;
       case 'str_pick_variant': return `brl_str_pick_variant(${args[0]}, ${args[1]} as i64, &mut engine.interner)`;
       case 'str_count_variants': return `brl_str_count_variants(${args[0]}, &engine.interner)`;
+      case 'str_split': return `brl_str_split(${args[0]}, ${args[1]}, &mut engine.interner)`;
+      case 'str_join': return `brl_str_join(&(${args[0]}), ${args[1]}, &mut engine.interner)`;
+      case 'str_upper': return `brl_str_upper(${args[0]}, &mut engine.interner)`;
+      case 'str_lower': return `brl_str_lower(${args[0]}, &mut engine.interner)`;
+      case 'str_capitalize': return `brl_str_capitalize(${args[0]}, &mut engine.interner)`;
+      case 'str_trim': return `brl_str_trim(${args[0]}, &mut engine.interner)`;
+      case 'str_starts_with': return `brl_str_starts_with(${args[0]}, ${args[1]}, &engine.interner)`;
+      case 'str_ends_with': return `brl_str_ends_with(${args[0]}, ${args[1]}, &engine.interner)`;
+      case 'str_substring':
+        return `brl_str_substring(${args[0]}, (${args[1]}) as i64, (${args[2]}) as i64, &mut engine.interner)`;
+      case 'str_length': return `brl_str_length(${args[0]}, &engine.interner)`;
+      case 'str_pad_start': return `brl_str_pad_start(${args[0]}, (${args[1]}) as i64, ${args[2]}, &mut engine.interner)`;
+      case 'str_pad_end': return `brl_str_pad_end(${args[0]}, (${args[1]}) as i64, ${args[2]}, &mut engine.interner)`;
+      case 'format':
+      case 'format_named':
+      case 'tr':
+      case 'tr_named': {
+        // format("{} hits {}", a, b) fills positional placeholders;
+        // format_named("{hero} hits {enemy}", "hero", a, "enemy", b) fills named ones.
+        // tr / tr_named do the same with a template looked up in the active locale.
+        const rest = expr.args.slice(1);
+        let argsValue: string;
+        if (!expr.name.endsWith('_named')) {
+          argsValue = `Value::List(vec![${rest.map(a => this.exprToValueRust(a)).join(', ')}])`;
+        } else {
+          const pairs: string[] = [];
+          for (let i = 0; i + 1 < rest.length; i += 2) {
+            pairs.push(`(${this.exprToRust(rest[i])}, ${this.exprToValueRust(rest[i + 1])})`);
+          }
+          argsValue = `Value::Map([${pairs.join(', ')}].into_iter().collect())`;
+        }
+        const builtin = expr.name.startsWith('tr') ? 'brl_tr' : 'brl_format';
+        return `${builtin}(${args[0]}, &${argsValue}, ${this.entityNameResolver()}, engine)`;
+      }
       default:
         // User-defined function — append engine as last argument
         return `${this.toSnakeCase(expr.name)}(${args.length > 0 ? args.join(', ') + ', ' : ''}engine)`;
@@ -1587,6 +1796,19 @@ export class RustCodeGenerator {
 
   // ── Utility methods ──
 
+  /**
+   * Closure resolving an entity's display name for `brl_format`: the `name`
+   * field of the first component that declares a string `name`.
+   */
+  private entityNameResolver(): string {
+    for (const [component, fields] of this.componentFieldTypes) {
+      if (fields.get('name') === 'string') {
+        return `|w, e| w.try_get::<${component}>(e).map(|c| c.name).filter(|s| *s != InternedString::NONE)`;
+      }
+    }
+    return '|_, _| None';
+  }
+
   private extractComponentName(expr: AST.Expr): string {
     if (expr.type === 'literal' && expr.value.type === 'string') {
       return expr.value.value;
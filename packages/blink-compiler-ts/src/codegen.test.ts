@@ -143,6 +143,35 @@ describe('CodeGenerator', () => {
       expect(action.event).toBe('DoAttack');
       expect(action.delay).toBeDefined();
     });
+
+    it('should generate IR for return action', () => {
+      const ir = compileToIR(`
+        rule test on Test t {
+          if t.source.Health.current <= 0 {
+            return
+          }
+          t.source.Health.current -= 1
+        }
+      `);
+      const action = ir.rules[0].actions[0] as IR.IRConditionalAction;
+      expect(action.then_actions).toEqual([{ type: 'return' }]);
+      expect(ir.rules[0].actions).toHaveLength(2);
+    });
+
+    it('should generate a query for rules over entities with a component', () => {
+      const ir = compileToIR(`
+        rule regen on Tick t {
+          if entity has Health {
+            entity.Health.current += 1
+          }
+        }
+        rule test on Test t {
+          let x = 10
+        }
+      `);
+      expect(ir.rules[0].query).toEqual({ component: 'Health', variable: 'entity' });
+      expect(ir.rules[1].query).toBeUndefined();
+    });
   });
 
   describe('Function generation', () => {
@@ -238,6 +267,20 @@ describe('CodeGenerator', () => {
       expect(action.value.type).toBe('field');
     });
 
+    it('should generate IR for field access on a computed entity', () => {
+      const ir = compileToIR(`
+        rule test on Test t {
+          let hp = t.source.Health.current
+        }
+      `);
+      const action = ir.rules[0].actions[0] as IR.IRLetAction;
+      const value = action.value as IR.IRFieldExpr;
+      expect(value.type).toBe('field');
+      expect(value.component).toBe('Health');
+      expect(value.field).toBe('current');
+      expect(value.entity).toEqual({ type: 'param', name: 'source' });
+    });
+
     it('should generate IR for function calls', () => {
       const ir = compileToIR(`
         rule test on Test t {
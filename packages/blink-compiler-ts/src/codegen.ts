@@ -173,11 +173,28 @@ export class CodeGenerator {
         type: 'event',
         event: rule.triggerEvent,
       },
+      query: this.generateRuleQuery(rule.body),
       condition: rule.condition ? this.generateExpression(rule.condition) : undefined,
       actions,
     };
   }
 
+  /**
+   * A rule body starting with `if entity has X` runs once per entity having X,
+   * like the query loop the Rust backend generates for it.
+   */
+  private generateRuleQuery(block: AST.Block): IR.IRRuleQuery | undefined {
+    if (block.statements.length === 0) {
+      return undefined;
+    }
+    const first = block.statements[0];
+    if (first.type === 'if' && first.condition.type === 'has_component' &&
+        first.condition.entity.type === 'identifier' && first.condition.entity.name === 'entity') {
+      return { component: first.condition.component, variable: 'entity' };
+    }
+    return undefined;
+  }
+
   private generateBlockActions(block: AST.Block): IR.IRAction[] {
     const actions: IR.IRAction[] = [];
     
@@ -237,6 +254,8 @@ export class CodeGenerator {
         };
       
       case 'return':
+        return { type: 'return' };
+
       case 'cancel':
       case 'expr':
         // These don't directly translate to IR actions
@@ -584,6 +603,13 @@ export class CodeGenerator {
           field: expr.field,
         };
       }
+      // Computed entity, e.g. `event.source.Team.side`
+      return {
+        type: 'field',
+        entity: this.generateExpression(baseAccess.base),
+        component: baseAccess.field,
+        field: expr.field,
+      };
     }
     
     // Handle event alias or legacy 'event' keyword - both generate event field access IR
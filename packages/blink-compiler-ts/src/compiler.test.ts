@@ -408,4 +408,27 @@ describe('End-to-End Compiler', () => {
       expect(functionsFile).not.toMatch(/mut flag/);
     });
   });
+
+  describe('Rust codegen: event schemas', () => {
+    it('should emit schemas for declared events and register them in init_game', () => {
+      const result = compileToRust([
+        {
+          path: 'test.brl',
+          language: 'brl',
+          content: `
+            component Health { current: integer }
+            event UseItem { item: string, count: integer, target: id }
+          `,
+        },
+      ]);
+
+      expect(result.errors).toHaveLength(0);
+      const libFile = result.files.get('lib.rs');
+      expect(libFile).toBeDefined();
+      expect(libFile).toContain(
+        '{"name":"UseItem","fields":[{"name":"item","type":"string","required":true},{"name":"count","type":"integer","required":true}]}'
+      );
+      expect(libFile).toContain('    register_event_schemas(engine);');
+    });
+  });
 });
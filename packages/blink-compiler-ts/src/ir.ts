@@ -74,6 +74,7 @@ export interface IRRule {
   name?: string;
   trigger: IRTrigger;
   filter?: IRFilter;
+  query?: IRRuleQuery;
   condition?: IRExpression;
   actions: IRAction[];
 }
@@ -88,6 +89,15 @@ export interface IRFilter {
   components?: string[];
 }
 
+/**
+ * Run the rule's actions once per entity having `component`, with the
+ * entity bound to `variable` (the `if entity has X` rule pattern).
+ */
+export interface IRRuleQuery {
+  component: string;
+  variable: string;
+}
+
 // ===== Actions =====
 
 export type IRAction =
@@ -99,7 +109,8 @@ export type IRAction =
   | IRConditionalAction
   | IRLoopAction
   | IRLetAction
-  | IRWhileAction;
+  | IRWhileAction
+  | IRReturnAction;
 
 export interface IRModifyAction {
   type: 'modify';
@@ -161,6 +172,10 @@ export interface IRWhileAction {
   body: IRAction[];
 }
 
+export interface IRReturnAction {
+  type: 'return';
+}
+
 // ===== Expressions =====
 
 export type IRExpression =
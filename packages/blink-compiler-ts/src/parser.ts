@@ -123,6 +123,20 @@ export class Parser {
     const start = this.consume(TokenKind.Event, 'event').span.start;
     const name = this.consume(TokenKind.Identifier, 'event name').text;
 
+    // Optional same-time phase: event DotTick phase start_of_tick { ... }
+    let phase: AST.EventPhase | undefined;
+    if (this.check(TokenKind.Identifier) && this.peek().text === 'phase') {
+      this.advance();
+      const token = this.consume(TokenKind.Identifier, 'phase name');
+      if (!(AST.EVENT_PHASES as readonly string[]).includes(token.text)) {
+        throw new ParseError(
+          token.span.start,
+          `Unknown event phase '${token.text}', expected one of: ${AST.EVENT_PHASES.join(', ')}`
+        );
+      }
+      phase = token.text as AST.EventPhase;
+    }
+
     const fields: AST.FieldDef[] = [];
     if (this.check(TokenKind.LBrace)) {
       this.advance();
@@ -142,6 +156,7 @@ export class Parser {
       type: 'event',
       name,
       fields,
+      ...(phase ? { phase } : {}),
       span: { start, end },
     };
   }
@@ -38,9 +38,17 @@ export class SemanticAnalyzer {
   // Built-in functions
   private builtinFunctions = new Set([
     'min', 'max', 'floor', 'ceil', 'round', 'abs',
-    'random', 'random_range', 'len', 'list', 'get',
+    'clamp', 'pow', 'sqrt', 'log', 'exp', 'lerp', 'inverse_lerp', 'sign',
+    'div_floor', 'mod_floor', 'round_to', 'curve_exp', 'curve_logistic',
+    'random', 'random_range', 'random_int', 'random_normal', 'random_exponential',
+    'random_poisson', 'random_binomial', 'random_weighted_index', 'shuffle', 'sample', 'roll', 'roll_breakdown', 'roll_loot',
+    'len', 'list', 'get',
+    'push', 'pop', 'insert', 'remove', 'contains', 'index_of', 'slice', 'list_concat',
+    'sort', 'sort_by', 'reverse', 'unique', 'sum', 'filter_having',
     'entities_having', 'concat', 'to_string', 'str_replace', 'str_contains',
-    'str_pick_variant', 'str_count_variants',
+    'str_pick_variant', 'str_count_variants', 'format', 'format_named', 'tr', 'tr_named',
+    'str_split', 'str_join', 'str_upper', 'str_lower', 'str_capitalize', 'str_trim',
+    'str_starts_with', 'str_ends_with', 'str_substring', 'str_length', 'str_pad_start', 'str_pad_end',
   ]);
   
   // Built-in variables available in rules
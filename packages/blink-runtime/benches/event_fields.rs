@@ -0,0 +1,165 @@
+//! Event field storage: inline `EventFields` against the previous
+//! `HashMap<InternedString, Value>` layout.
+//!
+//! Run with `cargo bench --bench event_fields`. Each benchmark builds, clones
+//! and reads events the way the timeline and generated rules do; the
+//! `timeline` group measures schedule/pop throughput, both through `Timeline`
+//! and through a plain heap holding either layout.
+
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+use std::hint::black_box;
+
+use blink_runtime::interning::InternedString;
+use blink_runtime::{Event, EventFields, Timeline, Value};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+
+/// Events per iteration.
+const EVENTS: usize = 10_000;
+
+/// The event layout before `EventFields`.
+#[derive(Clone)]
+struct HashMapEvent {
+    event_type: InternedString,
+    fields: HashMap<InternedString, Value>,
+}
+
+/// A queued event in the baseline heap: earliest time first, then
+/// scheduling order, as in `Timeline`.
+struct Queued<E> {
+    time: f64,
+    sequence: u64,
+    event: E,
+}
+
+impl<E> Ord for Queued<E> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // `BinaryHeap` is a max-heap; reverse so the earliest event pops first.
+        other.time.total_cmp(&self.time).then(other.sequence.cmp(&self.sequence))
+    }
+}
+
+impl<E> PartialOrd for Queued<E> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<E> PartialEq for Queued<E> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<E> Eq for Queued<E> {}
+
+/// Schedule `EVENTS` events built by `make` on a plain heap, then pop them all.
+fn heap_schedule_pop<E>(mut make: impl FnMut() -> E) -> usize {
+    let mut heap = BinaryHeap::new();
+    for i in 0..EVENTS {
+        let event = make();
+        heap.push(Queued { time: (i % 97) as f64, sequence: i as u64, event });
+    }
+    let mut popped = 0;
+    while let Some(queued) = heap.pop() {
+        black_box(&queued.event);
+        popped += 1;
+    }
+    popped
+}
+
+fn field_values(count: usize) -> Vec<(InternedString, Value)> {
+    (0..count)
+        .map(|i| (InternedString(i as u32 + 1), Value::Integer(i as i64)))
+        .collect()
+}
+
+fn bench_build_clone_read(c: &mut Criterion) {
+    let mut group = c.benchmark_group("event_fields");
+    group.throughput(Throughput::Elements(EVENTS as u64));
+    for count in [0, 2, 4] {
+        let values = field_values(count);
+        let lookup = InternedString(count as u32);
+
+        group.bench_with_input(BenchmarkId::new("hashmap", count), &values, |b, values| {
+            b.iter(|| {
+                let mut sum = 0i64;
+                for _ in 0..EVENTS {
+                    let mut fields = HashMap::new();
+                    for (k, v) in values {
+                        fields.insert(*k, v.clone());
+                    }
+                    let event = HashMapEvent { event_type: InternedString(1), fields };
+                    let queued = black_box(event.clone());
+                    sum += queued.fields.get(&lookup).map(|v| v.as_integer()).unwrap_or(0);
+                    black_box(queued.event_type);
+                }
+                sum
+            })
+        });
+
+        group.bench_with_input(BenchmarkId::new("inline", count), &values, |b, values| {
+            b.iter(|| {
+                let mut sum = 0i64;
+                for _ in 0..EVENTS {
+                    let mut event = Event::new(InternedString(1));
+                    for (k, v) in values {
+                        event.fields.insert(*k, v.clone());
+                    }
+                    let queued = black_box(event.clone());
+                    sum += queued.get_field(lookup).as_integer();
+                }
+                sum
+            })
+        });
+    }
+    group.finish();
+}
+
+fn bench_timeline(c: &mut Criterion) {
+    let mut group = c.benchmark_group("timeline");
+    group.throughput(Throughput::Elements(EVENTS as u64));
+    for count in [0, 2, 4] {
+        let values = field_values(count);
+        group.bench_with_input(BenchmarkId::new("schedule_pop", count), &values, |b, values| {
+            b.iter(|| {
+                let mut timeline = Timeline::new();
+                for i in 0..EVENTS {
+                    let fields: EventFields = values.iter().cloned().collect();
+                    let mut event = Event::new(InternedString(1));
+                    event.fields = fields;
+                    timeline.schedule_delay((i % 97) as f64, event);
+                }
+                let mut popped = 0;
+                while let Some(event) = timeline.pop() {
+                    black_box(&event);
+                    popped += 1;
+                }
+                popped
+            })
+        });
+
+        group.bench_with_input(BenchmarkId::new("heap_hashmap", count), &values, |b, values| {
+            b.iter(|| {
+                heap_schedule_pop(|| HashMapEvent {
+                    event_type: InternedString(1),
+                    fields: values.iter().cloned().collect(),
+                })
+            })
+        });
+
+        group.bench_with_input(BenchmarkId::new("heap_inline", count), &values, |b, values| {
+            b.iter(|| {
+                heap_schedule_pop(|| {
+                    let mut event = Event::new(InternedString(1));
+                    event.fields = values.iter().cloned().collect();
+                    event
+                })
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_build_clone_read, bench_timeline);
+criterion_main!(benches);
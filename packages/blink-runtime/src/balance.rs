@@ -0,0 +1,601 @@
+//! Statistical balance assertions over multi-seed simulation runs.
+//!
+//! Balance checks are declared in JSON (same shape as `game/data/test-checks.json`:
+//! `id`, `name`, `type`, `params`) and evaluated against the results of many
+//! seeded runs. Each check is decided with a confidence interval rather than a
+//! point estimate, and evaluation is sequential: results are added in batches,
+//! every call to `BalanceEvaluator::look` re-evaluates the checks that are still
+//! open, and a check is locked in as soon as its interval lies entirely inside
+//! (pass) or entirely outside (fail) the accepted bounds. The host only needs to
+//! keep running seeds for `pending_scenarios()`.
+//!
+//! The per-look significance level is the overall level split evenly across the
+//! planned number of looks (Bonferroni), so peeking after every batch does not
+//! inflate the false-decision rate. Looks past the plan still report estimates
+//! but decide nothing.
+//!
+//! Check files may hold types this evaluator has no statistic for (the JS
+//! harness's `determinism`, `mean_score_greater`, ...). Those are kept and
+//! reported as `Verdict::Unsupported` instead of rejecting the whole file.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::{Deserialize, Serialize};
+
+/// Outcome of a single simulation run, as reported by the batch simulator.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunResult {
+    pub scenario: String,
+    #[serde(default)]
+    pub seed: u64,
+    pub victory: bool,
+    /// Turns (or simulation time units) taken to clear the run.
+    /// Only meaningful for victories.
+    #[serde(default)]
+    pub turns: f64,
+    #[serde(default)]
+    pub heroes_alive: u32,
+    #[serde(default)]
+    pub heroes_total: u32,
+}
+
+/// The statistic a check asserts on, with its accepted bounds.
+/// Rates are percentages (0–100) to match the JS harness checks.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "params", rename_all = "snake_case")]
+pub enum BalanceCheckKind {
+    /// Fraction of runs that end in victory. The harness's `victory_rate_min`
+    /// is the same check without an upper bound.
+    #[serde(rename_all = "camelCase", alias = "victory_rate_min")]
+    WinRateBetween {
+        scenario: String,
+        #[serde(default)]
+        min_rate: f64,
+        #[serde(default = "default_max_rate")]
+        max_rate: f64,
+    },
+    /// Mean `turns` over victorious runs.
+    #[serde(rename_all = "camelCase")]
+    MeanTurnsToClear {
+        scenario: String,
+        #[serde(default)]
+        min_turns: f64,
+        #[serde(default = "default_max_turns")]
+        max_turns: f64,
+    },
+    /// Fraction of heroes alive at the end of a run, pooled over all runs.
+    #[serde(rename_all = "camelCase")]
+    HeroSurvivalRate {
+        scenario: String,
+        #[serde(default)]
+        min_rate: f64,
+        #[serde(default = "default_max_rate")]
+        max_rate: f64,
+    },
+}
+
+/// `type` values that map to a `BalanceCheckKind`.
+const SUPPORTED_TYPES: [&str; 4] = [
+    "win_rate_between",
+    "victory_rate_min",
+    "mean_turns_to_clear",
+    "hero_survival_rate",
+];
+
+fn default_max_rate() -> f64 {
+    100.0
+}
+
+fn default_max_turns() -> f64 {
+    f64::INFINITY
+}
+
+impl BalanceCheckKind {
+    /// The scenario whose runs this check consumes.
+    pub fn scenario(&self) -> &str {
+        match self {
+            BalanceCheckKind::WinRateBetween { scenario, .. }
+            | BalanceCheckKind::MeanTurnsToClear { scenario, .. }
+            | BalanceCheckKind::HeroSurvivalRate { scenario, .. } => scenario,
+        }
+    }
+
+    /// Accepted `[min, max]` bounds, in the units of the statistic.
+    fn bounds(&self) -> (f64, f64) {
+        match self {
+            BalanceCheckKind::WinRateBetween { min_rate, max_rate, .. }
+            | BalanceCheckKind::HeroSurvivalRate { min_rate, max_rate, .. } => {
+                (*min_rate, *max_rate)
+            }
+            BalanceCheckKind::MeanTurnsToClear { min_turns, max_turns, .. } => {
+                (*min_turns, *max_turns)
+            }
+        }
+    }
+}
+
+/// A declarative balance check.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BalanceCheck {
+    pub id: String,
+    #[serde(default)]
+    pub name: String,
+    #[serde(flatten)]
+    pub kind: BalanceCheckKind,
+}
+
+/// Sequential testing parameters.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SequentialPlan {
+    /// Overall confidence level, e.g. 0.95.
+    pub confidence: f64,
+    /// Planned number of looks; the error budget is split across them.
+    pub max_looks: u32,
+    /// Minimum number of samples before a check may be decided.
+    pub min_samples: usize,
+}
+
+impl Default for SequentialPlan {
+    fn default() -> Self {
+        SequentialPlan {
+            confidence: 0.95,
+            max_looks: 5,
+            min_samples: 10,
+        }
+    }
+}
+
+impl SequentialPlan {
+    /// Two-sided critical z value for a single look.
+    fn z(&self) -> f64 {
+        let alpha = (1.0 - self.confidence) / self.max_looks.max(1) as f64;
+        normal_quantile(1.0 - alpha / 2.0)
+    }
+}
+
+/// Decision state of a check.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Verdict {
+    Pass,
+    Fail,
+    /// Not enough evidence yet (or ever, once the plan is exhausted).
+    Undecided,
+    /// The check's type has no statistic in this evaluator.
+    Unsupported,
+}
+
+/// Result of evaluating one check at one look.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CheckOutcome {
+    pub id: String,
+    pub verdict: Verdict,
+    pub estimate: f64,
+    pub lower: f64,
+    pub upper: f64,
+    pub samples: usize,
+    /// The look at which the verdict was reached (or the latest look).
+    pub look: u32,
+}
+
+/// Accumulates run results and evaluates balance checks sequentially.
+pub struct BalanceEvaluator {
+    checks: Vec<BalanceCheck>,
+    /// `(id, type)` of checks with an unsupported type.
+    unsupported: Vec<(String, String)>,
+    plan: SequentialPlan,
+    results: BTreeMap<String, Vec<RunResult>>,
+    decided: BTreeMap<String, CheckOutcome>,
+    looks: u32,
+}
+
+impl BalanceEvaluator {
+    pub fn new(checks: Vec<BalanceCheck>, plan: SequentialPlan) -> Self {
+        BalanceEvaluator {
+            checks,
+            unsupported: Vec::new(),
+            plan,
+            results: BTreeMap::new(),
+            decided: BTreeMap::new(),
+            looks: 0,
+        }
+    }
+
+    /// Parse a JSON array of checks. Checks of an unsupported type are kept
+    /// and reported as `Verdict::Unsupported`; malformed checks of a supported
+    /// type are an error.
+    pub fn from_json(checks_json: &str, plan: SequentialPlan) -> Result<Self, String> {
+        let raw: Vec<serde_json::Value> =
+            serde_json::from_str(checks_json).map_err(|e| e.to_string())?;
+        let mut checks = Vec::new();
+        let mut unsupported = Vec::new();
+        for check in raw {
+            let check_type = check.get("type").and_then(|t| t.as_str()).unwrap_or("");
+            if SUPPORTED_TYPES.contains(&check_type) {
+                checks.push(serde_json::from_value(check).map_err(|e| e.to_string())?);
+            } else {
+                let id = check.get("id").and_then(|id| id.as_str()).unwrap_or("");
+                unsupported.push((id.to_string(), check_type.to_string()));
+            }
+        }
+        let mut evaluator = Self::new(checks, plan);
+        evaluator.unsupported = unsupported;
+        Ok(evaluator)
+    }
+
+    /// `(id, type)` of the checks skipped as unsupported.
+    pub fn unsupported(&self) -> &[(String, String)] {
+        &self.unsupported
+    }
+
+    /// Record the result of one run.
+    pub fn add_result(&mut self, result: RunResult) {
+        self.results
+            .entry(result.scenario.clone())
+            .or_default()
+            .push(result);
+    }
+
+    /// Record a batch of run results.
+    pub fn add_results<I: IntoIterator<Item = RunResult>>(&mut self, results: I) {
+        for result in results {
+            self.add_result(result);
+        }
+    }
+
+    /// Evaluate every open check against the results collected so far.
+    /// Decided checks are locked in and not re-evaluated on later looks.
+    /// Once the planned looks are spent, open checks get fresh estimates but
+    /// stay undecided, since deciding them would exceed the error budget.
+    /// Returns the outcome of every check, decided or not, followed by the
+    /// unsupported ones.
+    pub fn look(&mut self) -> Vec<CheckOutcome> {
+        let exhausted = self.looks >= self.plan.max_looks;
+        if !exhausted {
+            self.looks += 1;
+        }
+        let z = self.plan.z();
+        let mut outcomes = Vec::with_capacity(self.checks.len() + self.unsupported.len());
+        for check in &self.checks {
+            if let Some(done) = self.decided.get(&check.id) {
+                outcomes.push(done.clone());
+                continue;
+            }
+            let runs = self
+                .results
+                .get(check.kind.scenario())
+                .map(|r| r.as_slice())
+                .unwrap_or(&[]);
+            let mut outcome = evaluate(check, runs, z, self.plan.min_samples, self.looks);
+            if exhausted {
+                outcome.verdict = Verdict::Undecided;
+            } else if outcome.verdict != Verdict::Undecided {
+                self.decided.insert(check.id.clone(), outcome.clone());
+            }
+            outcomes.push(outcome);
+        }
+        outcomes.extend(self.unsupported.iter().map(|(id, _)| CheckOutcome {
+            id: id.clone(),
+            verdict: Verdict::Unsupported,
+            estimate: f64::NAN,
+            lower: f64::NAN,
+            upper: f64::NAN,
+            samples: 0,
+            look: self.looks,
+        }));
+        outcomes
+    }
+
+    /// Scenarios that still feed at least one undecided check.
+    /// Empty once every check is decided or the look budget is spent.
+    pub fn pending_scenarios(&self) -> Vec<String> {
+        if self.is_complete() {
+            return Vec::new();
+        }
+        let pending: BTreeSet<&str> = self
+            .checks
+            .iter()
+            .filter(|c| !self.decided.contains_key(&c.id))
+            .map(|c| c.kind.scenario())
+            .collect();
+        pending.into_iter().map(|s| s.to_string()).collect()
+    }
+
+    /// True when every check is decided or the planned looks are exhausted.
+    pub fn is_complete(&self) -> bool {
+        self.decided.len() == self.checks.len() || self.looks >= self.plan.max_looks
+    }
+
+    /// Number of looks taken so far.
+    pub fn looks(&self) -> u32 {
+        self.looks
+    }
+}
+
+fn evaluate(check: &BalanceCheck, runs: &[RunResult], z: f64, min_samples: usize, look: u32) -> CheckOutcome {
+    let (estimate, lower, upper, samples) = match &check.kind {
+        BalanceCheckKind::WinRateBetween { .. } => {
+            let wins = runs.iter().filter(|r| r.victory).count();
+            let (p, lo, hi) = wilson_interval(wins as f64, runs.len() as f64, z);
+            (p * 100.0, lo * 100.0, hi * 100.0, runs.len())
+        }
+        BalanceCheckKind::HeroSurvivalRate { .. } => {
+            let alive: u64 = runs.iter().map(|r| r.heroes_alive as u64).sum();
+            let total: u64 = runs.iter().map(|r| r.heroes_total as u64).sum();
+            let (p, lo, hi) = wilson_interval(alive as f64, total as f64, z);
+            (p * 100.0, lo * 100.0, hi * 100.0, runs.len())
+        }
+        BalanceCheckKind::MeanTurnsToClear { .. } => {
+            let turns: Vec<f64> = runs.iter().filter(|r| r.victory).map(|r| r.turns).collect();
+            let (mean, lo, hi) = mean_interval(&turns, z);
+            (mean, lo, hi, turns.len())
+        }
+    };
+
+    let (min, max) = check.kind.bounds();
+    let verdict = if samples < min_samples.max(1) {
+        Verdict::Undecided
+    } else if lower >= min && upper <= max {
+        Verdict::Pass
+    } else if upper < min || lower > max {
+        Verdict::Fail
+    } else {
+        Verdict::Undecided
+    };
+
+    CheckOutcome {
+        id: check.id.clone(),
+        verdict,
+        estimate,
+        lower,
+        upper,
+        samples,
+        look,
+    }
+}
+
+/// Wilson score interval for a binomial proportion.
+/// Returns (point estimate, lower, upper), all in [0, 1].
+fn wilson_interval(successes: f64, trials: f64, z: f64) -> (f64, f64, f64) {
+    if trials <= 0.0 {
+        return (0.0, 0.0, 1.0);
+    }
+    let p = successes / trials;
+    let z2 = z * z;
+    let denom = 1.0 + z2 / trials;
+    let center = (p + z2 / (2.0 * trials)) / denom;
+    let half = z * (p * (1.0 - p) / trials + z2 / (4.0 * trials * trials)).sqrt() / denom;
+    (p, (center - half).max(0.0), (center + half).min(1.0))
+}
+
+/// Normal-approximation interval for a sample mean.
+/// Returns (mean, lower, upper); the interval is unbounded below two samples.
+fn mean_interval(samples: &[f64], z: f64) -> (f64, f64, f64) {
+    let n = samples.len() as f64;
+    if samples.is_empty() {
+        return (0.0, f64::NEG_INFINITY, f64::INFINITY);
+    }
+    let mean = samples.iter().sum::<f64>() / n;
+    if samples.len() < 2 {
+        return (mean, f64::NEG_INFINITY, f64::INFINITY);
+    }
+    let variance = samples.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (n - 1.0);
+    let half = z * (variance / n).sqrt();
+    (mean, mean - half, mean + half)
+}
+
+/// Inverse of the standard normal CDF (Acklam's rational approximation,
+/// relative error below 1.2e-9).
+fn normal_quantile(p: f64) -> f64 {
+    const A: [f64; 6] = [
+        -3.969683028665376e1,
+        2.209460984245205e2,
+        -2.759285104469687e2,
+        1.383_577_518_672_69e2,
+        -3.066479806614716e1,
+        2.506628277459239,
+    ];
+    const B: [f64; 5] = [
+        -5.447609879822406e1,
+        1.615858368580409e2,
+        -1.556989798598866e2,
+        6.680131188771972e1,
+        -1.328068155288572e1,
+    ];
+    const C: [f64; 6] = [
+        -7.784894002430293e-3,
+        -3.223964580411365e-1,
+        -2.400758277161838,
+        -2.549732539343734,
+        4.374664141464968,
+        2.938163982698783,
+    ];
+    const D: [f64; 4] = [
+        7.784695709041462e-3,
+        3.224671290700398e-1,
+        2.445134137142996,
+        3.754408661907416,
+    ];
+    const P_LOW: f64 = 0.02425;
+
+    if p <= 0.0 {
+        return f64::NEG_INFINITY;
+    }
+    if p >= 1.0 {
+        return f64::INFINITY;
+    }
+    if p < P_LOW {
+        let q = (-2.0 * p.ln()).sqrt();
+        (((((C[0] * q + C[1]) * q + C[2]) * q + C[3]) * q + C[4]) * q + C[5])
+            / ((((D[0] * q + D[1]) * q + D[2]) * q + D[3]) * q + 1.0)
+    } else if p <= 1.0 - P_LOW {
+        let q = p - 0.5;
+        let r = q * q;
+        (((((A[0] * r + A[1]) * r + A[2]) * r + A[3]) * r + A[4]) * r + A[5]) * q
+            / (((((B[0] * r + B[1]) * r + B[2]) * r + B[3]) * r + B[4]) * r + 1.0)
+    } else {
+        -normal_quantile(1.0 - p)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(scenario: &str, victory: bool, turns: f64, alive: u32) -> RunResult {
+        RunResult {
+            scenario: scenario.to_string(),
+            seed: 0,
+            victory,
+            turns,
+            heroes_alive: alive,
+            heroes_total: 4,
+        }
+    }
+
+    #[test]
+    fn test_normal_quantile() {
+        assert!((normal_quantile(0.975) - 1.959964).abs() < 1e-5);
+        assert!((normal_quantile(0.5)).abs() < 1e-9);
+        assert!((normal_quantile(0.01) + 2.326348).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_parse_checks_json() {
+        let json = r#"[
+            { "id": "solo_wins", "name": "Solo wins sometimes", "type": "win_rate_between",
+              "params": { "scenario": "solo_warrior", "minRate": 20, "maxRate": 80 } },
+            { "id": "fast_clear", "type": "mean_turns_to_clear",
+              "params": { "scenario": "full_balanced", "maxTurns": 500 } }
+        ]"#;
+        let eval = BalanceEvaluator::from_json(json, SequentialPlan::default()).unwrap();
+        assert_eq!(eval.checks.len(), 2);
+        assert_eq!(
+            eval.checks[1].kind,
+            BalanceCheckKind::MeanTurnsToClear {
+                scenario: "full_balanced".to_string(),
+                min_turns: 0.0,
+                max_turns: 500.0,
+            }
+        );
+    }
+
+    #[test]
+    fn test_clear_pass_decides_early() {
+        let checks = vec![BalanceCheck {
+            id: "wins".to_string(),
+            name: String::new(),
+            kind: BalanceCheckKind::WinRateBetween {
+                scenario: "s".to_string(),
+                min_rate: 10.0,
+                max_rate: 90.0,
+            },
+        }];
+        let mut eval = BalanceEvaluator::new(checks, SequentialPlan::default());
+        eval.add_results((0..100).map(|i| run("s", i % 2 == 0, 0.0, 4)));
+        let outcomes = eval.look();
+        assert_eq!(outcomes[0].verdict, Verdict::Pass);
+        assert!(eval.is_complete());
+        assert!(eval.pending_scenarios().is_empty());
+    }
+
+    #[test]
+    fn test_clear_fail() {
+        let checks = vec![BalanceCheck {
+            id: "survival".to_string(),
+            name: String::new(),
+            kind: BalanceCheckKind::HeroSurvivalRate {
+                scenario: "s".to_string(),
+                min_rate: 75.0,
+                max_rate: 100.0,
+            },
+        }];
+        let mut eval = BalanceEvaluator::new(checks, SequentialPlan::default());
+        eval.add_results((0..50).map(|_| run("s", true, 10.0, 1)));
+        assert_eq!(eval.look()[0].verdict, Verdict::Fail);
+    }
+
+    #[test]
+    fn test_borderline_stays_open_until_more_data() {
+        let checks = vec![BalanceCheck {
+            id: "turns".to_string(),
+            name: String::new(),
+            kind: BalanceCheckKind::MeanTurnsToClear {
+                scenario: "s".to_string(),
+                min_turns: 0.0,
+                max_turns: 105.0,
+            },
+        }];
+        let mut eval = BalanceEvaluator::new(checks, SequentialPlan::default());
+        // Mean 100, wide spread: too few samples to rule out > 105.
+        eval.add_results((0..10).map(|i| run("s", true, if i % 2 == 0 { 80.0 } else { 120.0 }, 4)));
+        assert_eq!(eval.look()[0].verdict, Verdict::Undecided);
+        assert_eq!(eval.pending_scenarios(), vec!["s".to_string()]);
+
+        eval.add_results((0..400).map(|i| run("s", true, if i % 2 == 0 { 80.0 } else { 120.0 }, 4)));
+        let outcome = &eval.look()[0];
+        assert_eq!(outcome.verdict, Verdict::Pass);
+        assert_eq!(outcome.look, 2);
+    }
+
+    #[test]
+    fn test_load_harness_checks_file() {
+        let json = include_str!("../../../game/data/test-checks.json");
+        let mut eval = BalanceEvaluator::from_json(json, SequentialPlan::default()).unwrap();
+        // victory_rate_min is the only type with a statistic here.
+        assert_eq!(eval.checks.len(), 1);
+        assert_eq!(eval.checks[0].kind.bounds(), (20.0, 100.0));
+        assert!(eval.unsupported().iter().any(|(_, t)| t == "determinism"));
+        let outcomes = eval.look();
+        assert_eq!(outcomes.len(), eval.checks.len() + eval.unsupported().len());
+        assert!(outcomes[1..].iter().all(|o| o.verdict == Verdict::Unsupported));
+        assert_eq!(eval.pending_scenarios(), vec!["full_balanced".to_string()]);
+
+        let bad = r#"[{ "id": "x", "type": "win_rate_between", "params": {} }]"#;
+        assert!(BalanceEvaluator::from_json(bad, SequentialPlan::default()).is_err());
+    }
+
+    #[test]
+    fn test_no_decisions_past_planned_looks() {
+        let checks = vec![BalanceCheck {
+            id: "wins".to_string(),
+            name: String::new(),
+            kind: BalanceCheckKind::WinRateBetween {
+                scenario: "s".to_string(),
+                min_rate: 10.0,
+                max_rate: 90.0,
+            },
+        }];
+        let plan = SequentialPlan { max_looks: 1, ..SequentialPlan::default() };
+        let mut eval = BalanceEvaluator::new(checks, plan);
+        eval.add_results((0..3).map(|i| run("s", i % 2 == 0, 0.0, 4)));
+        assert_eq!(eval.look()[0].verdict, Verdict::Undecided);
+        assert!(eval.is_complete());
+
+        // Enough data to pass, but the single planned look is spent.
+        eval.add_results((0..200).map(|i| run("s", i % 2 == 0, 0.0, 4)));
+        let outcome = &eval.look()[0];
+        assert_eq!(outcome.verdict, Verdict::Undecided);
+        assert_eq!(outcome.samples, 203);
+        assert_eq!(outcome.look, 1);
+        assert_eq!(eval.looks(), 1);
+    }
+
+    #[test]
+    fn test_min_samples_respected() {
+        let checks = vec![BalanceCheck {
+            id: "wins".to_string(),
+            name: String::new(),
+            kind: BalanceCheckKind::WinRateBetween {
+                scenario: "s".to_string(),
+                min_rate: 0.0,
+                max_rate: 100.0,
+            },
+        }];
+        let mut eval = BalanceEvaluator::new(checks, SequentialPlan::default());
+        eval.add_results((0..3).map(|_| run("s", true, 0.0, 4)));
+        assert_eq!(eval.look()[0].verdict, Verdict::Undecided);
+    }
+}
@@ -1,5 +1,11 @@
-/// Built-in functions available to all BRL programs.
-/// These are called directly from generated Rust code.
+//! Built-in functions available to all BRL programs.
+//! These are called directly from generated Rust code.
+//!
+//! Functions that feed the simulation must give identical results natively
+//! and in WASM, so transcendental math goes through `detmath` rather than
+//! the platform `f64` methods.
+
+use crate::detmath;
 
 /// Minimum of two values.
 #[inline]
@@ -37,68 +43,221 @@ pub fn brl_abs(a: f64) -> f64 {
     a.abs()
 }
 
-/// Deterministic pseudo-random number generator.
-/// Uses xoshiro256** for reproducibility across JS and WASM engines.
-pub struct Rng {
-    state: [u64; 4],
-}
-
-impl Rng {
-    /// Create a new RNG with the given seed.
-    pub fn new(seed: u64) -> Self {
-        // SplitMix64 to initialize the state from a single seed
-        let mut s = seed;
-        let mut state = [0u64; 4];
-        for item in &mut state {
-            s = s.wrapping_add(0x9e3779b97f4a7c15);
-            let mut z = s;
-            z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
-            z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
-            *item = z ^ (z >> 31);
-        }
-        Rng { state }
-    }
+/// Clamp a value to [lo, hi].
+#[inline]
+pub fn brl_clamp(value: f64, lo: f64, hi: f64) -> f64 {
+    value.max(lo).min(hi)
+}
+
+/// `base` raised to `exponent`.
+#[inline]
+pub fn brl_pow(base: f64, exponent: f64) -> f64 {
+    detmath::pow(base, exponent)
+}
+
+/// Square root (exact under IEEE-754, so deterministic).
+#[inline]
+pub fn brl_sqrt(a: f64) -> f64 {
+    a.sqrt()
+}
+
+/// Natural logarithm.
+#[inline]
+pub fn brl_log(a: f64) -> f64 {
+    detmath::ln(a)
+}
+
+/// e raised to `a`.
+#[inline]
+pub fn brl_exp(a: f64) -> f64 {
+    detmath::exp(a)
+}
 
-    /// Generate a random f64 in [0, 1).
-    pub fn random(&mut self) -> f64 {
-        let result = self.next_u64();
-        // Convert to f64 in [0, 1)
-        (result >> 11) as f64 / (1u64 << 53) as f64
+/// Linear interpolation: `a` at t = 0, `b` at t = 1. Not clamped.
+#[inline]
+pub fn brl_lerp(a: f64, b: f64, t: f64) -> f64 {
+    a + (b - a) * t
+}
+
+/// Where `value` lies between `a` and `b`, as the `t` `brl_lerp` would need.
+/// 0 when `a == b`.
+#[inline]
+pub fn brl_inverse_lerp(a: f64, b: f64, value: f64) -> f64 {
+    if a == b { 0.0 } else { (value - a) / (b - a) }
+}
+
+/// -1, 0 or 1.
+#[inline]
+pub fn brl_sign(a: f64) -> f64 {
+    if a > 0.0 {
+        1.0
+    } else if a < 0.0 {
+        -1.0
+    } else {
+        0.0
     }
+}
 
-    /// Generate a random f64 in [min, max).
-    pub fn random_range(&mut self, min: f64, max: f64) -> f64 {
-        min + self.random() * (max - min)
+/// Integer division rounding towards negative infinity: `-7 / 2 == -4`.
+/// Division by zero yields 0. The one overflowing quotient,
+/// `i64::MIN / -1`, saturates to `i64::MAX`.
+pub fn brl_div_floor(a: i64, b: i64) -> i64 {
+    if b == 0 {
+        return 0;
     }
+    let Some(q) = a.checked_div(b) else {
+        return i64::MAX;
+    };
+    // A remainder means |b| > 1 or |a| < |b|, so `q - 1` cannot overflow.
+    if a % b != 0 && ((a < 0) != (b < 0)) { q - 1 } else { q }
+}
 
-    /// Generate a random integer in [min, max] (inclusive).
-    pub fn random_int_range(&mut self, min: i64, max: i64) -> i64 {
-        if min >= max {
-            return min;
-        }
-        let range = (max - min + 1) as u64;
-        let result = self.next_u64() % range;
-        min + result as i64
+/// Remainder matching `brl_div_floor`: takes the sign of the divisor, so
+/// `-7 mod 3 == 2`. Modulo zero yields 0.
+pub fn brl_mod_floor(a: i64, b: i64) -> i64 {
+    if b == 0 {
+        return 0;
     }
+    let r = a.wrapping_rem(b);
+    if r != 0 && ((r < 0) != (b < 0)) { r + b } else { r }
+}
+
+/// Round to `decimals` digits after the point (negative rounds to tens,
+/// hundreds, ...). Halves round away from zero.
+pub fn brl_round_to(a: f64, decimals: i64) -> f64 {
+    let scale = detmath::pow(10.0, decimals.clamp(-15, 15) as f64);
+    (a * scale).round() / scale
+}
+
+/// Exponential level curve: `base` at level 1, multiplied by `growth` per
+/// level (`growth` 1.1 = +10% per level).
+pub fn brl_curve_exp(base: f64, growth: f64, level: f64) -> f64 {
+    base * detmath::pow(growth, level - 1.0)
+}
 
-    /// xoshiro256** next
-    fn next_u64(&mut self) -> u64 {
-        let result = self.state[1].wrapping_mul(5).rotate_left(7).wrapping_mul(9);
-        let t = self.state[1] << 17;
-        self.state[2] ^= self.state[0];
-        self.state[3] ^= self.state[1];
-        self.state[1] ^= self.state[2];
-        self.state[0] ^= self.state[3];
-        self.state[2] ^= t;
-        self.state[3] = self.state[3].rotate_left(45);
-        result
+/// Logistic (S-shaped) level curve from `min` to `max`, halfway at
+/// `midpoint`; `steepness` sets how quickly it rises around the midpoint.
+pub fn brl_curve_logistic(min: f64, max: f64, midpoint: f64, steepness: f64, level: f64) -> f64 {
+    min + (max - min) / (1.0 + detmath::exp(-steepness * (level - midpoint)))
+}
+
+pub use crate::rng::Rng;
+
+// ── Random draws ───────────────────────────────────────────────────────────
+// BRL `random_*` functions draw from the engine's master stream.
+
+/// Random integer in [min, max] (inclusive).
+pub fn brl_random_int(min: i64, max: i64, rng: &mut Rng) -> i64 {
+    rng.random_int_range(min, max)
+}
+
+/// Normally distributed number.
+pub fn brl_random_normal(mean: f64, std_dev: f64, rng: &mut Rng) -> f64 {
+    rng.normal(mean, std_dev)
+}
+
+/// Exponentially distributed number with mean 1 / rate.
+pub fn brl_random_exponential(rate: f64, rng: &mut Rng) -> f64 {
+    rng.exponential(rate)
+}
+
+/// Largest trial count `random_binomial` accepts: counts above 2^53 are not
+/// exact as JS numbers.
+pub const MAX_BINOMIAL_TRIALS: i64 = 1 << 53;
+
+/// Poisson-distributed count with mean `lambda`; 0 if `lambda` is not a
+/// positive finite number. Counts saturate at `i64::MAX`.
+pub fn brl_random_poisson(lambda: f64, rng: &mut Rng) -> i64 {
+    rng.poisson(lambda).min(i64::MAX as u64) as i64
+}
+
+/// Successes in `n` trials of probability `p`, with `n` clamped to
+/// `0..=MAX_BINOMIAL_TRIALS`.
+pub fn brl_random_binomial(n: i64, p: f64, rng: &mut Rng) -> i64 {
+    rng.binomial(n.clamp(0, MAX_BINOMIAL_TRIALS) as u64, p) as i64
+}
+
+/// Index picked in proportion to its weight, or -1 if no weight is positive.
+pub fn brl_random_weighted_index(weights: &[f64], rng: &mut Rng) -> i64 {
+    rng.weighted_index(weights).map_or(-1, |i| i as i64)
+}
+
+/// Shuffled copy of a list.
+pub fn brl_shuffle<T: Clone>(list: &[T], rng: &mut Rng) -> Vec<T> {
+    let mut shuffled = list.to_vec();
+    rng.shuffle(&mut shuffled);
+    shuffled
+}
+
+/// `count` distinct elements of a list, in random order.
+pub fn brl_sample<T: Clone>(list: &[T], count: i64, rng: &mut Rng) -> Vec<T> {
+    rng.sample(list, count.max(0) as usize)
+}
+
+/// Roll dice notation such as "2d6+3" or "4d6kh3" (see `dice`).
+/// Invalid notation rolls 0.
+pub fn brl_roll(
+    expr: crate::interning::InternedString,
+    rng: &mut Rng,
+    interner: &crate::interning::StringInterner,
+) -> i64 {
+    match crate::dice::DiceExpr::parse(interner.resolve(expr)) {
+        Ok(expr) => expr.roll(rng).total,
+        Err(_) => 0,
     }
 }
 
-impl Default for Rng {
-    fn default() -> Self {
-        Self::new(42)
+/// Roll dice notation and return the breakdown for a combat log: one
+/// `Value::Map` per term, `{term, rolls, kept, total}`, where
+/// `rolls` holds every die, kept or dropped, and `kept` flags each one. The
+/// term totals add up to the roll's total. Invalid notation yields an empty
+/// list.
+pub fn brl_roll_breakdown(
+    expr: crate::interning::InternedString,
+    rng: &mut Rng,
+    interner: &mut crate::interning::StringInterner,
+) -> Vec<crate::value::Value> {
+    use crate::value::Value;
+    let Ok(expr) = crate::dice::DiceExpr::parse(interner.resolve(expr)) else {
+        return Vec::new();
+    };
+    expr.roll(rng)
+        .terms
+        .iter()
+        .map(|term| {
+            let mut map = std::collections::BTreeMap::new();
+            let notation = interner.intern(&term.term.notation());
+            map.insert(interner.intern("term"), Value::String(notation));
+            map.insert(interner.intern("rolls"), Value::List(term.rolls.iter().map(|r| Value::Integer(*r)).collect()));
+            map.insert(interner.intern("kept"), Value::List(term.kept.iter().map(|k| Value::Boolean(*k)).collect()));
+            map.insert(interner.intern("total"), Value::Integer(term.total));
+            Value::Map(map)
+        })
+        .collect()
+}
+
+/// Most item names `brl_roll_loot` returns; drops past it are cut off.
+pub const MAX_LOOT_ITEMS: usize = 10_000;
+
+/// Roll a loot table and return one item name per unit dropped, e.g.
+/// `["potion", "potion", "dagger"]`, up to `MAX_LOOT_ITEMS`. Unknown tables
+/// drop nothing.
+pub fn brl_roll_loot(
+    table: crate::interning::InternedString,
+    engine: &mut crate::Engine,
+) -> Vec<crate::interning::InternedString> {
+    let name = engine.interner.resolve(table).to_string();
+    let drops = engine.roll_loot(&name);
+    let mut items = Vec::new();
+    for drop in drops {
+        let room = MAX_LOOT_ITEMS - items.len();
+        if room == 0 {
+            break;
+        }
+        let item = engine.interner.intern(&drop.item);
+        items.extend(std::iter::repeat_n(item, (drop.count as usize).min(room)));
     }
+    items
 }
 
 /// Length of a list (as Value::List).
@@ -107,6 +266,181 @@ pub fn brl_len(list: &[crate::value::Value]) -> i64 {
     list.len() as i64
 }
 
+// ── List operations ────────────────────────────────────────────────────────
+// Lists are values: every operation returns a new list and leaves its input
+// unchanged. Indices out of range are clamped or ignored rather than panicking.
+
+/// Ordering used by `sort`, `min` and `max`. Strings compare by text (not by
+/// interned ID) so the order is the same whichever order strings were interned
+/// in; numbers use IEEE total ordering.
+pub trait BrlOrd {
+    fn brl_cmp(&self, other: &Self, interner: &crate::interning::StringInterner) -> std::cmp::Ordering;
+}
+
+impl BrlOrd for i64 {
+    fn brl_cmp(&self, other: &Self, _: &crate::interning::StringInterner) -> std::cmp::Ordering {
+        self.cmp(other)
+    }
+}
+
+impl BrlOrd for u32 {
+    fn brl_cmp(&self, other: &Self, _: &crate::interning::StringInterner) -> std::cmp::Ordering {
+        self.cmp(other)
+    }
+}
+
+impl BrlOrd for f64 {
+    fn brl_cmp(&self, other: &Self, _: &crate::interning::StringInterner) -> std::cmp::Ordering {
+        self.total_cmp(other)
+    }
+}
+
+impl BrlOrd for bool {
+    fn brl_cmp(&self, other: &Self, _: &crate::interning::StringInterner) -> std::cmp::Ordering {
+        self.cmp(other)
+    }
+}
+
+impl BrlOrd for crate::interning::InternedString {
+    fn brl_cmp(&self, other: &Self, interner: &crate::interning::StringInterner) -> std::cmp::Ordering {
+        interner.resolve(*self).cmp(interner.resolve(*other))
+    }
+}
+
+impl BrlOrd for crate::value::Value {
+    /// Values without a natural order (mixed kinds, maps, NaN) fall back to
+    /// `Value::total_cmp`.
+    fn brl_cmp(&self, other: &Self, interner: &crate::interning::StringInterner) -> std::cmp::Ordering {
+        self.compare(other, interner).unwrap_or_else(|_| self.total_cmp(other))
+    }
+}
+
+/// List with `item` appended.
+pub fn brl_list_push<T: Clone>(list: &[T], item: T) -> Vec<T> {
+    let mut result = list.to_vec();
+    result.push(item);
+    result
+}
+
+/// List without its last element.
+pub fn brl_list_pop<T: Clone>(list: &[T]) -> Vec<T> {
+    list[..list.len().saturating_sub(1)].to_vec()
+}
+
+/// List with `item` inserted before `index` (clamped to the list bounds).
+pub fn brl_list_insert<T: Clone>(list: &[T], index: i64, item: T) -> Vec<T> {
+    let mut result = list.to_vec();
+    result.insert(index.clamp(0, list.len() as i64) as usize, item);
+    result
+}
+
+/// List without the element at `index`. Out-of-range indices change nothing.
+pub fn brl_list_remove<T: Clone>(list: &[T], index: i64) -> Vec<T> {
+    let mut result = list.to_vec();
+    if (0..list.len() as i64).contains(&index) {
+        result.remove(index as usize);
+    }
+    result
+}
+
+/// Whether the list contains `item`.
+pub fn brl_list_contains<T: PartialEq>(list: &[T], item: &T) -> bool {
+    list.contains(item)
+}
+
+/// Index of the first occurrence of `item`, or -1.
+pub fn brl_list_index_of<T: PartialEq>(list: &[T], item: &T) -> i64 {
+    list.iter().position(|x| x == item).map_or(-1, |i| i as i64)
+}
+
+/// Elements from `start` up to (not including) `end`. Negative indices count
+/// from the end of the list; both are clamped to its bounds.
+pub fn brl_list_slice<T: Clone>(list: &[T], start: i64, end: i64) -> Vec<T> {
+    let len = list.len() as i64;
+    let bound = |i: i64| (if i < 0 { i + len } else { i }).clamp(0, len) as usize;
+    let (start, end) = (bound(start), bound(end));
+    if start >= end { Vec::new() } else { list[start..end].to_vec() }
+}
+
+/// The elements of `a` followed by those of `b`.
+pub fn brl_list_concat<T: Clone>(a: &[T], b: &[T]) -> Vec<T> {
+    [a, b].concat()
+}
+
+/// Sorted copy of a list, ascending. The sort is stable.
+pub fn brl_list_sort<T: Clone + BrlOrd>(list: &[T], interner: &crate::interning::StringInterner) -> Vec<T> {
+    let mut sorted = list.to_vec();
+    sorted.sort_by(|a, b| a.brl_cmp(b, interner));
+    sorted
+}
+
+/// Copy of a list sorted ascending by a key, e.g. entities by a component
+/// field. The sort is stable, so entities with equal keys keep their order.
+pub fn brl_list_sort_by<T: Clone, K: BrlOrd>(
+    list: &[T],
+    mut key: impl FnMut(&T) -> K,
+    interner: &crate::interning::StringInterner,
+) -> Vec<T> {
+    let mut keyed: Vec<(K, T)> = list.iter().map(|x| (key(x), x.clone())).collect();
+    keyed.sort_by(|a, b| a.0.brl_cmp(&b.0, interner));
+    keyed.into_iter().map(|(_, x)| x).collect()
+}
+
+/// Reversed copy of a list.
+pub fn brl_list_reverse<T: Clone>(list: &[T]) -> Vec<T> {
+    list.iter().rev().cloned().collect()
+}
+
+/// List with duplicates removed, keeping the first occurrence of each.
+pub fn brl_list_unique<T: Clone + PartialEq>(list: &[T]) -> Vec<T> {
+    let mut result: Vec<T> = Vec::with_capacity(list.len());
+    for x in list {
+        if !result.contains(x) {
+            result.push(x.clone());
+        }
+    }
+    result
+}
+
+/// Addition used by `sum`. Integers saturate at `i64::MIN` / `i64::MAX`
+/// instead of overflowing; numbers follow IEEE.
+pub trait BrlSum: Copy + Default {
+    fn brl_add(self, other: Self) -> Self;
+}
+
+impl BrlSum for i64 {
+    fn brl_add(self, other: Self) -> Self {
+        self.saturating_add(other)
+    }
+}
+
+impl BrlSum for f64 {
+    fn brl_add(self, other: Self) -> Self {
+        self + other
+    }
+}
+
+/// Sum of a list of numbers (0 for an empty list). Integer sums saturate.
+pub fn brl_list_sum<T: BrlSum>(list: &[T]) -> T {
+    list.iter().fold(T::default(), |total, x| total.brl_add(*x))
+}
+
+/// Smallest element, or the default value for an empty list.
+pub fn brl_list_min<T: Clone + Default + BrlOrd>(list: &[T], interner: &crate::interning::StringInterner) -> T {
+    list.iter().min_by(|a, b| a.brl_cmp(b, interner)).cloned().unwrap_or_default()
+}
+
+/// Largest element, or the default value for an empty list.
+pub fn brl_list_max<T: Clone + Default + BrlOrd>(list: &[T], interner: &crate::interning::StringInterner) -> T {
+    list.iter().max_by(|a, b| a.brl_cmp(b, interner)).cloned().unwrap_or_default()
+}
+
+/// Elements for which `keep` returns true, in order. Used to filter entity
+/// lists by component presence.
+pub fn brl_list_filter<T: Clone>(list: &[T], mut keep: impl FnMut(&T) -> bool) -> Vec<T> {
+    list.iter().filter(|x| keep(x)).cloned().collect()
+}
+
 // ── String operations ──────────────────────────────────────────────────────
 
 /// Concatenate two interned strings and intern the result.
@@ -144,6 +478,59 @@ pub fn brl_to_string_float(
     interner.intern(&s)
 }
 
+/// Fill in a template such as "{hero} hits {enemy} for {dmg:,}" from a map
+/// (named placeholders) or list (positional ones); see `format`. Only the
+/// final text is interned. Entities format as `name_of(world, entity)`,
+/// falling back to their BRL variable name and then to `#id`.
+pub fn brl_format(
+    template: crate::interning::InternedString,
+    args: &crate::value::Value,
+    name_of: impl Fn(&crate::world::World, crate::value::EntityId) -> Option<crate::interning::InternedString>,
+    engine: &mut crate::Engine,
+) -> crate::interning::InternedString {
+    let text = format_with_names(engine.interner.resolve(template), args, &name_of, engine);
+    engine.interner.intern(&text)
+}
+
+/// Translate a key through the active locale (see `locale`) and fill in its
+/// placeholders like `brl_format`. The plural form is picked by the first
+/// positional argument, or the `count` entry of a map. Keys missing from
+/// every locale format the key itself.
+pub fn brl_tr(
+    key: crate::interning::InternedString,
+    args: &crate::value::Value,
+    name_of: impl Fn(&crate::world::World, crate::value::EntityId) -> Option<crate::interning::InternedString>,
+    engine: &mut crate::Engine,
+) -> crate::interning::InternedString {
+    use crate::value::Value;
+    let key = engine.interner.resolve(key);
+    let count = match args {
+        Value::List(items) => items.first(),
+        _ => engine.interner.lookup("count").and_then(|id| args.get(id)),
+    };
+    let count = count.filter(|v| matches!(v, Value::Integer(_) | Value::Number(_))).map(Value::as_number);
+    let template = engine.locales.translate(key, count).unwrap_or(key);
+    let text = format_with_names(template, args, &name_of, engine);
+    engine.interner.intern(&text)
+}
+
+/// `format_template` with entities named by `name_of`, then by their BRL
+/// variable name.
+fn format_with_names(
+    template: &str,
+    args: &crate::value::Value,
+    name_of: &impl Fn(&crate::world::World, crate::value::EntityId) -> Option<crate::interning::InternedString>,
+    engine: &crate::Engine,
+) -> String {
+    let (world, interner) = (&engine.world, &engine.interner);
+    let entity_name = |e| {
+        name_of(world, e)
+            .map(|name| interner.resolve(name).to_string())
+            .or_else(|| world.get_variable_name(e).map(str::to_string))
+    };
+    crate::format::format_template(template, args, interner, entity_name)
+}
+
 /// Replace all occurrences of a pattern in a string.
 pub fn brl_str_replace(
     text: crate::interning::InternedString,
@@ -198,6 +585,158 @@ pub fn brl_str_count_variants(
     s.split('|').count() as i64
 }
 
+/// Split a string on a separator. An empty separator splits into characters.
+pub fn brl_str_split(
+    text: crate::interning::InternedString,
+    separator: crate::interning::InternedString,
+    interner: &mut crate::interning::StringInterner,
+) -> Vec<crate::interning::InternedString> {
+    let s = interner.resolve(text).to_string();
+    let sep = interner.resolve(separator).to_string();
+    if sep.is_empty() {
+        let mut buf = [0u8; 4];
+        return s.chars().map(|c| interner.intern(c.encode_utf8(&mut buf))).collect();
+    }
+    s.split(sep.as_str()).map(|part| interner.intern(part)).collect()
+}
+
+/// Join strings with a separator.
+pub fn brl_str_join(
+    parts: &[crate::interning::InternedString],
+    separator: crate::interning::InternedString,
+    interner: &mut crate::interning::StringInterner,
+) -> crate::interning::InternedString {
+    let texts: Vec<&str> = parts.iter().map(|p| interner.resolve(*p)).collect();
+    let result = texts.join(interner.resolve(separator));
+    interner.intern(&result)
+}
+
+/// Uppercase a string (Unicode-aware).
+pub fn brl_str_upper(
+    text: crate::interning::InternedString,
+    interner: &mut crate::interning::StringInterner,
+) -> crate::interning::InternedString {
+    let result = interner.resolve(text).to_uppercase();
+    interner.intern(&result)
+}
+
+/// Lowercase a string (Unicode-aware).
+pub fn brl_str_lower(
+    text: crate::interning::InternedString,
+    interner: &mut crate::interning::StringInterner,
+) -> crate::interning::InternedString {
+    let result = interner.resolve(text).to_lowercase();
+    interner.intern(&result)
+}
+
+/// Uppercase the first character, leaving the rest unchanged.
+/// Given "goblin king", returns "Goblin king".
+pub fn brl_str_capitalize(
+    text: crate::interning::InternedString,
+    interner: &mut crate::interning::StringInterner,
+) -> crate::interning::InternedString {
+    let s = interner.resolve(text);
+    let mut chars = s.chars();
+    let Some(first) = chars.next() else {
+        return text;
+    };
+    let result: String = first.to_uppercase().chain(chars).collect();
+    interner.intern(&result)
+}
+
+/// Remove leading and trailing whitespace.
+pub fn brl_str_trim(
+    text: crate::interning::InternedString,
+    interner: &mut crate::interning::StringInterner,
+) -> crate::interning::InternedString {
+    let result = interner.resolve(text).trim().to_string();
+    interner.intern(&result)
+}
+
+/// Check if a string starts with a prefix.
+pub fn brl_str_starts_with(
+    text: crate::interning::InternedString,
+    prefix: crate::interning::InternedString,
+    interner: &crate::interning::StringInterner,
+) -> bool {
+    interner.resolve(text).starts_with(interner.resolve(prefix))
+}
+
+/// Check if a string ends with a suffix.
+pub fn brl_str_ends_with(
+    text: crate::interning::InternedString,
+    suffix: crate::interning::InternedString,
+    interner: &crate::interning::StringInterner,
+) -> bool {
+    interner.resolve(text).ends_with(interner.resolve(suffix))
+}
+
+/// Characters from `start` up to (not including) `end`, counted in
+/// characters rather than bytes so multi-byte text is never cut mid-character.
+/// Negative indices count from the end; both are clamped like `brl_list_slice`.
+pub fn brl_str_substring(
+    text: crate::interning::InternedString,
+    start: i64,
+    end: i64,
+    interner: &mut crate::interning::StringInterner,
+) -> crate::interning::InternedString {
+    let chars: Vec<char> = interner.resolve(text).chars().collect();
+    let result: String = brl_list_slice(&chars, start, end).into_iter().collect();
+    interner.intern(&result)
+}
+
+/// Length of a string in characters (not bytes).
+pub fn brl_str_length(
+    text: crate::interning::InternedString,
+    interner: &crate::interning::StringInterner,
+) -> i64 {
+    interner.resolve(text).chars().count() as i64
+}
+
+/// Widest string `str_pad_start` and `str_pad_end` pad to; larger widths are
+/// clamped to it.
+pub const MAX_PAD: i64 = 10_000;
+
+/// Pad a string on the left with `fill` (its first character, or a space if
+/// empty) to at least `width` characters. Given "7", 3 and "0", returns "007".
+pub fn brl_str_pad_start(
+    text: crate::interning::InternedString,
+    width: i64,
+    fill: crate::interning::InternedString,
+    interner: &mut crate::interning::StringInterner,
+) -> crate::interning::InternedString {
+    let (padding, s) = padding(text, width, fill, interner);
+    let result = padding + &s;
+    interner.intern(&result)
+}
+
+/// Pad a string on the right to at least `width` characters, like
+/// `brl_str_pad_start`.
+pub fn brl_str_pad_end(
+    text: crate::interning::InternedString,
+    width: i64,
+    fill: crate::interning::InternedString,
+    interner: &mut crate::interning::StringInterner,
+) -> crate::interning::InternedString {
+    let (padding, s) = padding(text, width, fill, interner);
+    let result = s + &padding;
+    interner.intern(&result)
+}
+
+/// Padding needed to bring `text` to `width` (at most `MAX_PAD`) characters,
+/// and the text itself.
+fn padding(
+    text: crate::interning::InternedString,
+    width: i64,
+    fill: crate::interning::InternedString,
+    interner: &crate::interning::StringInterner,
+) -> (String, String) {
+    let s = interner.resolve(text).to_string();
+    let fill = interner.resolve(fill).chars().next().unwrap_or(' ');
+    let missing = (width.clamp(0, MAX_PAD) as usize).saturating_sub(s.chars().count());
+    (std::iter::repeat_n(fill, missing).collect(), s)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -217,43 +756,171 @@ mod tests {
     }
 
     #[test]
-    fn test_abs() {
-        assert_eq!(brl_abs(-5.0), 5.0);
-        assert_eq!(brl_abs(5.0), 5.0);
+    fn test_random_builtins() {
+        let mut rng = Rng::new(5);
+        let die = brl_random_int(1, 6, &mut rng);
+        assert!((1..=6).contains(&die));
+        assert!(brl_random_poisson(2.0, &mut rng) >= 0);
+        assert_eq!(brl_random_binomial(-3, 0.5, &mut rng), 0);
+        assert_eq!(brl_random_binomial(i64::MAX, 1.0, &mut rng), MAX_BINOMIAL_TRIALS);
+        assert!(brl_random_binomial(i64::MAX, 0.5, &mut rng) <= MAX_BINOMIAL_TRIALS);
+        assert_eq!(brl_random_poisson(f64::INFINITY, &mut rng), 0);
+        assert_eq!(brl_random_poisson(f64::NAN, &mut rng), 0);
+        assert!(brl_random_poisson(f64::MAX, &mut rng) > 0);
+        assert_eq!(brl_random_weighted_index(&[0.0, 0.0], &mut rng), -1);
+        assert_eq!(brl_random_weighted_index(&[0.0, 2.0], &mut rng), 1);
+        let mut shuffled = brl_shuffle(&[3, 1, 2], &mut rng);
+        shuffled.sort();
+        assert_eq!(shuffled, vec![1, 2, 3]);
+        assert_eq!(brl_sample(&[1, 2, 3], 2, &mut rng).len(), 2);
+        assert!(brl_sample(&[1, 2, 3], -1, &mut rng).is_empty());
     }
 
     #[test]
-    fn test_rng_determinism() {
-        let mut rng1 = Rng::new(12345);
-        let mut rng2 = Rng::new(12345);
+    fn test_roll() {
+        let mut interner = crate::interning::StringInterner::new();
+        let mut rng = Rng::new(8);
+        let expr = interner.intern("3d6+2");
+        let total = brl_roll(expr, &mut rng, &interner);
+        assert!((5..=20).contains(&total));
+        assert_eq!(brl_roll(interner.intern("2d"), &mut rng, &interner), 0);
+        assert_eq!(brl_roll(interner.intern("1d6+99999999999"), &mut rng, &interner), 0);
 
-        for _ in 0..100 {
-            assert_eq!(rng1.random().to_bits(), rng2.random().to_bits());
-        }
+        let terms = brl_roll_breakdown(interner.intern("4d6kh3+2"), &mut rng, &mut interner);
+        assert_eq!(terms.len(), 2);
+        let field = |term: &crate::value::Value, name: &str| term.get(interner.lookup(name).unwrap()).unwrap().clone();
+        let rolls = field(&terms[0], "rolls");
+        let kept = field(&terms[0], "kept");
+        assert_eq!(rolls.as_list().unwrap().len(), 4);
+        assert_eq!(kept.as_list().unwrap().iter().filter(|k| k.as_boolean()).count(), 3);
+        let kept_sum: i64 = rolls
+            .as_list()
+            .unwrap()
+            .iter()
+            .zip(kept.as_list().unwrap())
+            .filter(|(_, k)| k.as_boolean())
+            .map(|(r, _)| r.as_integer())
+            .sum();
+        assert_eq!(field(&terms[0], "total").as_integer(), kept_sum);
+        assert_eq!(interner.resolve(field(&terms[1], "term").as_string_id()), "2");
+        assert_eq!(field(&terms[1], "total").as_integer(), 2);
+        assert!(brl_roll_breakdown(interner.intern("2d"), &mut rng, &mut interner).is_empty());
     }
 
     #[test]
-    fn test_rng_range() {
-        let mut rng = Rng::new(42);
-        for _ in 0..100 {
-            let v = rng.random();
-            assert!((0.0..1.0).contains(&v));
-        }
-        for _ in 0..100 {
-            let v = rng.random_range(10.0, 20.0);
-            assert!((10.0..20.0).contains(&v));
-        }
+    fn test_roll_loot() {
+        let mut engine = crate::Engine::new();
+        engine
+            .loot
+            .load_json(r#"[{"name": "pouch", "entries": [{"item": "coin", "count": 3}]}]"#)
+            .unwrap();
+        let pouch = engine.interner.intern("pouch");
+        let coin = engine.interner.intern("coin");
+        assert_eq!(brl_roll_loot(pouch, &mut engine), vec![coin; 3]);
+        let unknown = engine.interner.intern("unknown");
+        assert!(brl_roll_loot(unknown, &mut engine).is_empty());
+
+        engine
+            .loot
+            .load_json(r#"[{"name": "vault", "rolls": 2, "entries": [{"item": "coin", "count": 4294967295}]}]"#)
+            .unwrap();
+        let vault = engine.interner.intern("vault");
+        assert_eq!(brl_roll_loot(vault, &mut engine), vec![coin; MAX_LOOT_ITEMS]);
+    }
+
+    #[test]
+    fn test_list_operations() {
+        let list = [1i64, 2, 3];
+        assert_eq!(brl_list_push(&list, 4), vec![1, 2, 3, 4]);
+        assert_eq!(brl_list_pop(&list), vec![1, 2]);
+        assert!(brl_list_pop::<i64>(&[]).is_empty());
+        assert_eq!(brl_list_insert(&list, 1, 9), vec![1, 9, 2, 3]);
+        assert_eq!(brl_list_insert(&list, 99, 9), vec![1, 2, 3, 9]);
+        assert_eq!(brl_list_remove(&list, 0), vec![2, 3]);
+        assert_eq!(brl_list_remove(&list, 5), vec![1, 2, 3]);
+        assert!(brl_list_contains(&list, &2));
+        assert_eq!(brl_list_index_of(&list, &3), 2);
+        assert_eq!(brl_list_index_of(&list, &7), -1);
+        assert_eq!(brl_list_slice(&list, 1, 10), vec![2, 3]);
+        assert_eq!(brl_list_slice(&list, -2, -1), vec![2]);
+        assert!(brl_list_slice(&list, 2, 1).is_empty());
+        assert_eq!(brl_list_concat(&list, &[4]), vec![1, 2, 3, 4]);
+        assert_eq!(brl_list_reverse(&list), vec![3, 2, 1]);
+        assert_eq!(brl_list_unique(&[2, 1, 2, 3, 1]), vec![2, 1, 3]);
+        assert_eq!(brl_list_sum(&list), 6);
+        assert_eq!(brl_list_sum::<f64>(&[]), 0.0);
+        assert_eq!(brl_list_sum(&[i64::MAX, 1, -1]), i64::MAX - 1);
+        assert_eq!(brl_list_sum(&[i64::MIN, -5]), i64::MIN);
+        assert_eq!(brl_list_filter(&list, |x| x % 2 == 1), vec![1, 3]);
+    }
+
+    #[test]
+    fn test_list_sort_min_max() {
+        let mut interner = crate::interning::StringInterner::new();
+        // Interned in reverse: sorting must follow the text, not the IDs.
+        let names: Vec<_> = ["cleric", "bard", "archer"].iter().map(|s| interner.intern(s)).collect();
+        let sorted: Vec<&str> = brl_list_sort(&names, &interner).iter().map(|s| interner.resolve(*s)).collect();
+        assert_eq!(sorted, vec!["archer", "bard", "cleric"]);
+        assert_eq!(interner.resolve(brl_list_min(&names, &interner)), "archer");
+        assert_eq!(brl_list_max(&[2.5, -1.0, 7.0], &interner), 7.0);
+        assert_eq!(brl_list_min::<i64>(&[], &interner), 0);
+
+        // Entities by a component field; equal keys keep their order.
+        let hp = [(10u32, 30i64), (11, 5), (12, 30), (13, 12)];
+        let entities: Vec<u32> = hp.iter().map(|(e, _)| *e).collect();
+        let by_hp = brl_list_sort_by(&entities, |e| hp.iter().find(|(id, _)| id == e).unwrap().1, &interner);
+        assert_eq!(by_hp, vec![11, 13, 10, 12]);
+
+        use crate::value::Value;
+        let mixed = [Value::Integer(3), Value::Number(1.5), Value::Integer(2)];
+        assert_eq!(brl_list_sort(&mixed, &interner), vec![Value::Number(1.5), Value::Integer(2), Value::Integer(3)]);
+    }
+
+    #[test]
+    fn test_extended_math() {
+        assert_eq!(brl_clamp(12.0, 0.0, 10.0), 10.0);
+        assert_eq!(brl_clamp(-1.0, 0.0, 10.0), 0.0);
+        assert_eq!(brl_pow(2.0, 10.0), 1024.0);
+        assert_eq!(brl_sqrt(81.0), 9.0);
+        assert!((brl_log(brl_exp(2.5)) - 2.5).abs() < 1e-15);
+        assert_eq!(brl_lerp(10.0, 20.0, 0.25), 12.5);
+        assert_eq!(brl_inverse_lerp(10.0, 20.0, 12.5), 0.25);
+        assert_eq!(brl_inverse_lerp(5.0, 5.0, 7.0), 0.0);
+        assert_eq!((brl_sign(-3.0), brl_sign(0.0), brl_sign(2.0)), (-1.0, 0.0, 1.0));
+        assert_eq!(brl_round_to(12.3456, 2), 12.35);
+        assert_eq!(brl_round_to(1234.5, -2), 1200.0);
     }
 
     #[test]
-    fn test_rng_int_range() {
-        let mut rng = Rng::new(42);
-        for _ in 0..100 {
-            let v = rng.random_int_range(1, 6);
-            assert!((1..=6).contains(&v));
+    fn test_floor_division() {
+        assert_eq!((brl_div_floor(7, 2), brl_mod_floor(7, 2)), (3, 1));
+        assert_eq!((brl_div_floor(-7, 2), brl_mod_floor(-7, 2)), (-4, 1));
+        assert_eq!((brl_div_floor(7, -2), brl_mod_floor(7, -2)), (-4, -1));
+        assert_eq!((brl_div_floor(-6, 3), brl_mod_floor(-6, 3)), (-2, 0));
+        assert_eq!((brl_div_floor(5, 0), brl_mod_floor(5, 0)), (0, 0));
+        assert_eq!((brl_div_floor(i64::MIN, -1), brl_mod_floor(i64::MIN, -1)), (i64::MAX, 0));
+        assert_eq!((brl_div_floor(i64::MIN, 2), brl_mod_floor(i64::MIN, 2)), (i64::MIN / 2, 0));
+        assert_eq!((brl_div_floor(i64::MIN + 1, -2), brl_mod_floor(i64::MIN + 1, -2)), (i64::MAX / 2, -1));
+        for (a, b) in [(-7, 3), (7, -3), (-7, -3), (-100, 7)] {
+            assert_eq!(brl_div_floor(a, b) * b + brl_mod_floor(a, b), a);
         }
     }
 
+    #[test]
+    fn test_level_curves() {
+        assert_eq!(brl_curve_exp(100.0, 1.5, 1.0), 100.0);
+        assert_eq!(brl_curve_exp(100.0, 1.5, 3.0), 225.0);
+        assert_eq!(brl_curve_logistic(10.0, 50.0, 20.0, 0.3, 20.0), 30.0);
+        assert!(brl_curve_logistic(10.0, 50.0, 20.0, 0.3, 0.0) < 11.0);
+        assert!(brl_curve_logistic(10.0, 50.0, 20.0, 0.3, 60.0) > 49.9);
+    }
+
+    #[test]
+    fn test_abs() {
+        assert_eq!(brl_abs(-5.0), 5.0);
+        assert_eq!(brl_abs(5.0), 5.0);
+    }
+
     #[test]
     fn test_concat() {
         let mut interner = crate::interning::StringInterner::new();
@@ -273,6 +940,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::approx_constant)]
     fn test_to_string_float() {
         let mut interner = crate::interning::StringInterner::new();
         let result = brl_to_string_float(3.14, &mut interner);
@@ -292,6 +960,54 @@ mod tests {
         assert_eq!(interner.resolve(result), "hello Alice, welcome!");
     }
 
+    #[test]
+    fn test_format() {
+        use crate::value::Value;
+        let mut engine = crate::Engine::new();
+        let hero = engine.world.spawn_named("hero");
+        let goblin = engine.world.spawn();
+        let template = engine.interner.intern("{} hits {} for {:,} ({:.0%})");
+        let args = Value::List(vec![Value::Entity(hero), Value::Entity(goblin), Value::Integer(12000), Value::Number(0.25)]);
+        let text = brl_format(template, &args, |_, _| None, &mut engine);
+        assert_eq!(engine.interner.resolve(text), format!("hero hits #{} for 12,000 (25%)", goblin));
+
+        let grunt = engine.interner.intern("Grunt");
+        let text = brl_format(template, &args, |_, e| (e == goblin).then_some(grunt), &mut engine);
+        assert_eq!(engine.interner.resolve(text), "hero hits Grunt for 12,000 (25%)");
+    }
+
+    #[test]
+    fn test_tr() {
+        use crate::value::Value;
+        let run = |locale: &str| {
+            let mut engine = crate::Engine::with_seed(3);
+            engine.locales.load_json("en", r#"{"loot": {"one": "Found {0} coin", "other": "Found {0} coins"}}"#).unwrap();
+            engine.locales.load_tsv("de", "loot[one]\t{0} Münze gefunden\nloot[other]\t{0} Münzen gefunden\n").unwrap();
+            engine.locales.set_locale(locale);
+            let key = engine.interner.intern("loot");
+            let mut lines = Vec::new();
+            for _ in 0..3 {
+                let coins = brl_random_int(1, 2, &mut engine.rng);
+                let text = brl_tr(key, &Value::List(vec![Value::Integer(coins)]), |_, _| None, &mut engine);
+                lines.push((coins, engine.interner.resolve(text).to_string()));
+            }
+            let missing = engine.interner.intern("no such key {0}");
+            let text = brl_tr(missing, &Value::List(vec![Value::Integer(1)]), |_, _| None, &mut engine);
+            assert_eq!(engine.interner.resolve(text), "no such key 1");
+            (lines, engine.rng_state())
+        };
+        let (english, en_rng) = run("en");
+        let (german, de_rng) = run("de");
+        // Same rolls and random state in either locale; only the text differs.
+        assert_eq!(en_rng, de_rng);
+        for ((coins, en), (de_coins, de)) in english.iter().zip(&german) {
+            assert_eq!(coins, de_coins);
+            let (en_word, de_word) = if *coins == 1 { ("coin", "Münze") } else { ("coins", "Münzen") };
+            assert_eq!(*en, format!("Found {} {}", coins, en_word));
+            assert_eq!(*de, format!("{} {} gefunden", coins, de_word));
+        }
+    }
+
     #[test]
     fn test_str_contains() {
         let mut interner = crate::interning::StringInterner::new();
@@ -306,14 +1022,19 @@ mod tests {
     fn test_str_pick_variant() {
         let mut interner = crate::interning::StringInterner::new();
         let text = interner.intern("alpha | beta | gamma");
-        assert_eq!(interner.resolve(brl_str_pick_variant(text, 0, &mut interner)), "alpha");
-        assert_eq!(interner.resolve(brl_str_pick_variant(text, 1, &mut interner)), "beta");
-        assert_eq!(interner.resolve(brl_str_pick_variant(text, 2, &mut interner)), "gamma");
+        let v0 = brl_str_pick_variant(text, 0, &mut interner);
+        let v1 = brl_str_pick_variant(text, 1, &mut interner);
+        let v2 = brl_str_pick_variant(text, 2, &mut interner);
+        assert_eq!(interner.resolve(v0), "alpha");
+        assert_eq!(interner.resolve(v1), "beta");
+        assert_eq!(interner.resolve(v2), "gamma");
         // Wraps around
-        assert_eq!(interner.resolve(brl_str_pick_variant(text, 3, &mut interner)), "alpha");
+        let v3 = brl_str_pick_variant(text, 3, &mut interner);
+        assert_eq!(interner.resolve(v3), "alpha");
         // Single variant
         let single = interner.intern("just one");
-        assert_eq!(interner.resolve(brl_str_pick_variant(single, 5, &mut interner)), "just one");
+        let v_single = brl_str_pick_variant(single, 5, &mut interner);
+        assert_eq!(interner.resolve(v_single), "just one");
     }
 
     #[test]
@@ -324,4 +1045,67 @@ mod tests {
         let single = interner.intern("just one");
         assert_eq!(brl_str_count_variants(single, &interner), 1);
     }
+
+    #[test]
+    fn test_str_split_join() {
+        let mut interner = crate::interning::StringInterner::new();
+        let text = interner.intern("sword, shield,potion");
+        let comma = interner.intern(",");
+        let parts = brl_str_split(text, comma, &mut interner);
+        let texts: Vec<&str> = parts.iter().map(|p| interner.resolve(*p)).collect();
+        assert_eq!(texts, vec!["sword", " shield", "potion"]);
+
+        let dash = interner.intern(" - ");
+        let joined = brl_str_join(&parts, dash, &mut interner);
+        assert_eq!(interner.resolve(joined), "sword -  shield - potion");
+
+        let word = interner.intern("héé");
+        let chars = brl_str_split(word, crate::interning::InternedString::NONE, &mut interner);
+        assert_eq!(chars.len(), 3);
+        assert_eq!(interner.resolve(chars[1]), "é");
+    }
+
+    #[test]
+    fn test_str_case_and_trim() {
+        let mut interner = crate::interning::StringInterner::new();
+        let text = interner.intern("  ärger im Dorf ");
+        let trimmed = brl_str_trim(text, &mut interner);
+        assert_eq!(interner.resolve(trimmed), "ärger im Dorf");
+        let upper = brl_str_upper(trimmed, &mut interner);
+        assert_eq!(interner.resolve(upper), "ÄRGER IM DORF");
+        let lower = brl_str_lower(upper, &mut interner);
+        assert_eq!(interner.resolve(lower), "ärger im dorf");
+        let capitalized = brl_str_capitalize(trimmed, &mut interner);
+        assert_eq!(interner.resolve(capitalized), "Ärger im Dorf");
+        let empty = crate::interning::InternedString::NONE;
+        assert_eq!(brl_str_capitalize(empty, &mut interner), empty);
+
+        let prefix = interner.intern("ärger");
+        let suffix = interner.intern("Dorf");
+        assert!(brl_str_starts_with(trimmed, prefix, &interner));
+        assert!(brl_str_ends_with(trimmed, suffix, &interner));
+        assert!(!brl_str_ends_with(trimmed, prefix, &interner));
+    }
+
+    #[test]
+    fn test_str_substring_length_padding() {
+        let mut interner = crate::interning::StringInterner::new();
+        let text = interner.intern("naïve café");
+        assert_eq!(brl_str_length(text, &interner), 10);
+        let head = brl_str_substring(text, 0, 5, &mut interner);
+        assert_eq!(interner.resolve(head), "naïve");
+        let tail = brl_str_substring(text, -4, 100, &mut interner);
+        assert_eq!(interner.resolve(tail), "café");
+
+        let seven = interner.intern("7");
+        let zero = interner.intern("0");
+        let padded = brl_str_pad_start(seven, 3, zero, &mut interner);
+        assert_eq!(interner.resolve(padded), "007");
+        let padded = brl_str_pad_end(seven, 3, crate::interning::InternedString::NONE, &mut interner);
+        assert_eq!(interner.resolve(padded), "7  ");
+        assert_eq!(brl_str_pad_start(text, 4, zero, &mut interner), text);
+        let padded = brl_str_pad_end(seven, i64::MAX, zero, &mut interner);
+        assert_eq!(brl_str_length(padded, &interner), MAX_PAD);
+        assert_eq!(brl_str_pad_start(seven, -5, zero, &mut interner), seven);
+    }
 }
@@ -0,0 +1,252 @@
+//! Choice points: decisions that suspend the simulation until answered.
+//!
+//! A rule raises a choice through `Engine::raise_choice` with a list of options.
+//! The rule itself runs to completion, but the engine stops handing out events
+//! (`Engine::next_event` returns `None`) until the host submits an answer.
+//! Submitting schedules the choice's resolve event immediately, carrying the
+//! chosen option, so rules react to the decision like any other event.
+//!
+//! Every answer is appended to a log of `ChoiceRecord`s. Feeding that log back
+//! through `Engine::load_choice_replay` answers the same choices the same way,
+//! which makes a run with player decisions deterministically replayable.
+//!
+//! BRL `choice fn` declarations do not go through this yet: they compile to
+//! ordinary functions that return their answer synchronously. Suspending
+//! choices are raised by the host or by hand-written runtime code.
+
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+
+use crate::interning::{InternedString, LiveStrings, MarkStrings, StringInterner};
+use crate::value::{EntityId, Value};
+
+/// Unique choice ID, assigned in the order choices are raised.
+pub type ChoiceId = u32;
+
+/// A decision waiting for an answer.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Choice {
+    pub id: ChoiceId,
+    /// Choice point name (e.g. `select_path`).
+    pub kind: InternedString,
+    /// Entity the decision is made for.
+    pub source: EntityId,
+    pub options: Vec<Value>,
+    /// Option used when the host asks for the default answer.
+    pub default_option: usize,
+    /// Event type scheduled once the choice is answered.
+    pub resolve_event: InternedString,
+    /// Simulation time at which the choice was raised.
+    pub raised_at: f64,
+}
+
+impl Choice {
+    /// Serialize for the host (JS) side.
+    pub fn to_json_value(&self, interner: &StringInterner) -> serde_json::Value {
+        serde_json::json!({
+            "id": self.id,
+            "kind": interner.resolve(self.kind),
+            "source": self.source,
+            "options": self.options.iter().map(|v| v.to_json_value(interner)).collect::<Vec<_>>(),
+            "defaultOption": self.default_option,
+            "resolveEvent": interner.resolve(self.resolve_event),
+            "raisedAt": self.raised_at,
+        })
+    }
+}
+
+/// A recorded answer, used for deterministic replay.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChoiceRecord {
+    pub id: ChoiceId,
+    pub index: usize,
+}
+
+/// Errors returned when raising or answering a choice.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ChoiceError {
+    /// There is no choice waiting for an answer.
+    NoPendingChoice,
+    /// A choice was raised without options; it could never be answered.
+    NoOptions,
+    /// The answer does not index into the choice's options.
+    OptionOutOfRange { index: usize, len: usize },
+}
+
+impl std::fmt::Display for ChoiceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ChoiceError::NoPendingChoice => write!(f, "no pending choice"),
+            ChoiceError::NoOptions => write!(f, "choice has no options"),
+            ChoiceError::OptionOutOfRange { index, len } => {
+                write!(f, "option {} out of range ({} options)", index, len)
+            }
+        }
+    }
+}
+
+/// Pending choices, answer log and replay queue.
+pub struct ChoiceState {
+    pending: VecDeque<Choice>,
+    next_choice_id: ChoiceId,
+    log: Vec<ChoiceRecord>,
+    replay: VecDeque<ChoiceRecord>,
+}
+
+impl ChoiceState {
+    pub fn new() -> Self {
+        ChoiceState {
+            pending: VecDeque::new(),
+            next_choice_id: 1,
+            log: Vec::new(),
+            replay: VecDeque::new(),
+        }
+    }
+
+    /// Allocate the next choice ID.
+    pub fn next_id(&mut self) -> ChoiceId {
+        let id = self.next_choice_id;
+        self.next_choice_id += 1;
+        id
+    }
+
+    /// Queue a choice until it is answered.
+    pub fn push(&mut self, choice: Choice) {
+        self.pending.push_back(choice);
+    }
+
+    /// The oldest unanswered choice.
+    pub fn pending(&self) -> Option<&Choice> {
+        self.pending.front()
+    }
+
+    /// All unanswered choices, oldest first.
+    pub fn all_pending(&self) -> impl Iterator<Item = &Choice> {
+        self.pending.iter()
+    }
+
+    /// True while any choice is unanswered.
+    pub fn is_suspended(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    /// Answer the oldest pending choice, recording the answer.
+    pub fn answer(&mut self, index: usize) -> Result<Choice, ChoiceError> {
+        let choice = self.pending.front().ok_or(ChoiceError::NoPendingChoice)?;
+        if index >= choice.options.len() {
+            return Err(ChoiceError::OptionOutOfRange {
+                index,
+                len: choice.options.len(),
+            });
+        }
+        let choice = self.pending.pop_front().unwrap();
+        self.record(choice.id, index);
+        Ok(choice)
+    }
+
+    /// Append an answer to the log.
+    pub fn record(&mut self, id: ChoiceId, index: usize) {
+        self.log.push(ChoiceRecord { id, index });
+    }
+
+    /// Take the replayed answer for `id`, if the replay queue has it next.
+    /// A mismatch means the run diverged from the recording, so replay stops.
+    pub fn take_replay(&mut self, id: ChoiceId, option_count: usize) -> Option<usize> {
+        let next = *self.replay.front()?;
+        if next.id != id || next.index >= option_count {
+            self.replay.clear();
+            return None;
+        }
+        self.replay.pop_front();
+        Some(next.index)
+    }
+
+    /// Answers given so far, in order.
+    pub fn log(&self) -> &[ChoiceRecord] {
+        &self.log
+    }
+
+    /// Queue recorded answers to be replayed as choices are raised.
+    pub fn load_replay(&mut self, records: Vec<ChoiceRecord>) {
+        self.replay = records.into();
+    }
+
+    /// Number of replayed answers not yet consumed.
+    pub fn replay_remaining(&self) -> usize {
+        self.replay.len()
+    }
+
+    /// Clear pending choices, log and replay queue.
+    pub fn reset(&mut self) {
+        self.pending.clear();
+        self.next_choice_id = 1;
+        self.log.clear();
+        self.replay.clear();
+    }
+}
+
+impl MarkStrings for ChoiceState {
+    fn mark_strings(&self, live: &mut LiveStrings) {
+        for choice in &self.pending {
+            choice.kind.mark_strings(live);
+            choice.resolve_event.mark_strings(live);
+            choice.options.mark_strings(live);
+        }
+    }
+}
+
+impl Default for ChoiceState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_choice(state: &mut ChoiceState, options: usize) -> Choice {
+        Choice {
+            id: state.next_id(),
+            kind: InternedString(1),
+            source: 1,
+            options: (0..options as i64).map(Value::Integer).collect(),
+            default_option: 0,
+            resolve_event: InternedString(2),
+            raised_at: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_answer_records() {
+        let mut state = ChoiceState::new();
+        let choice = make_choice(&mut state, 3);
+        state.push(choice);
+        assert!(state.is_suspended());
+
+        assert_eq!(
+            state.answer(5),
+            Err(ChoiceError::OptionOutOfRange { index: 5, len: 3 })
+        );
+        assert!(state.is_suspended());
+
+        let answered = state.answer(2).unwrap();
+        assert_eq!(answered.id, 1);
+        assert!(!state.is_suspended());
+        assert_eq!(state.log(), &[ChoiceRecord { id: 1, index: 2 }]);
+        assert_eq!(state.answer(0), Err(ChoiceError::NoPendingChoice));
+    }
+
+    #[test]
+    fn test_replay_stops_on_mismatch() {
+        let mut state = ChoiceState::new();
+        state.load_replay(vec![
+            ChoiceRecord { id: 1, index: 1 },
+            ChoiceRecord { id: 7, index: 0 },
+        ]);
+        assert_eq!(state.take_replay(1, 2), Some(1));
+        assert_eq!(state.take_replay(2, 2), None);
+        assert_eq!(state.replay_remaining(), 0);
+    }
+}
@@ -0,0 +1,220 @@
+//! Decision policies: automated answers to choice points.
+//!
+//! Story and idle modes run without a human at the keyboard, so every choice a
+//! rule raises has to be answered by a policy. When a policy is installed with
+//! `Engine::set_decision_policy`, the engine consults it as soon as a choice is
+//! raised and resolves the choice on the spot instead of suspending.
+//! Policies draw randomness from the engine `Rng`, so a given policy and seed
+//! always make the same decisions.
+
+use std::collections::VecDeque;
+
+use crate::builtins::Rng;
+use crate::choice::Choice;
+use crate::value::Value;
+
+/// Strategy for answering choices without a human.
+pub trait DecisionPolicy {
+    /// Pick an option index for `choice`. `choice.options` is never empty.
+    /// Out-of-range answers are clamped to the last option.
+    fn decide(&mut self, choice: &Choice, rng: &mut Rng) -> usize;
+
+    /// Short name used in batch reports.
+    fn name(&self) -> &str;
+}
+
+/// Picks uniformly at random.
+pub struct RandomPolicy;
+
+impl DecisionPolicy for RandomPolicy {
+    fn decide(&mut self, choice: &Choice, rng: &mut Rng) -> usize {
+        rng.random_int_range(0, choice.options.len() as i64 - 1) as usize
+    }
+
+    fn name(&self) -> &str {
+        "random"
+    }
+}
+
+/// Always picks the first option.
+pub struct FirstOptionPolicy;
+
+impl DecisionPolicy for FirstOptionPolicy {
+    fn decide(&mut self, _choice: &Choice, _rng: &mut Rng) -> usize {
+        0
+    }
+
+    fn name(&self) -> &str {
+        "first"
+    }
+}
+
+/// Picks at random, proportionally to a weight computed for each option.
+/// Options whose weight is not positive and finite (negative, zero, NaN or
+/// infinite) are never picked; if no option can be picked the first option is
+/// chosen.
+pub struct WeightedPolicy {
+    weight: Box<WeightFn>,
+}
+
+/// Weight of the option at an index of a choice.
+type WeightFn = dyn Fn(&Choice, usize, &Value) -> f64;
+
+impl WeightedPolicy {
+    /// Weight each option with `weight(choice, index, option)`.
+    pub fn new<F>(weight: F) -> Self
+    where
+        F: Fn(&Choice, usize, &Value) -> f64 + 'static,
+    {
+        WeightedPolicy {
+            weight: Box::new(weight),
+        }
+    }
+
+    /// Fixed weights by option index; missing indices weigh 0.
+    pub fn by_index(weights: Vec<f64>) -> Self {
+        Self::new(move |_, index, _| weights.get(index).copied().unwrap_or(0.0))
+    }
+
+    /// Use each option's numeric value as its weight.
+    pub fn by_value() -> Self {
+        Self::new(|_, _, option| option.as_number())
+    }
+}
+
+impl DecisionPolicy for WeightedPolicy {
+    fn decide(&mut self, choice: &Choice, rng: &mut Rng) -> usize {
+        let mut weights: Vec<f64> = choice
+            .options
+            .iter()
+            .enumerate()
+            .map(|(i, option)| {
+                let w = (self.weight)(choice, i, option);
+                if w.is_finite() && w > 0.0 { w } else { 0.0 }
+            })
+            .collect();
+        // Finite weights can still sum past f64::MAX; scale them down so the
+        // total stays finite.
+        if !weights.iter().sum::<f64>().is_finite() {
+            let largest = weights.iter().copied().fold(0.0, f64::max);
+            for w in &mut weights {
+                *w /= largest;
+            }
+        }
+        rng.weighted_index(&weights).unwrap_or(0)
+    }
+
+    fn name(&self) -> &str {
+        "weighted"
+    }
+}
+
+/// Answers from a fixed list, in order, then defers to a fallback policy.
+pub struct ScriptedPolicy {
+    answers: VecDeque<usize>,
+    fallback: Box<dyn DecisionPolicy>,
+}
+
+impl ScriptedPolicy {
+    /// Script the given answers, falling back to the first option.
+    pub fn new(answers: Vec<usize>) -> Self {
+        Self::with_fallback(answers, Box::new(FirstOptionPolicy))
+    }
+
+    /// Script the given answers, then defer to `fallback`.
+    pub fn with_fallback(answers: Vec<usize>, fallback: Box<dyn DecisionPolicy>) -> Self {
+        ScriptedPolicy {
+            answers: answers.into(),
+            fallback,
+        }
+    }
+
+    /// Scripted answers not yet used.
+    pub fn remaining(&self) -> usize {
+        self.answers.len()
+    }
+}
+
+impl DecisionPolicy for ScriptedPolicy {
+    fn decide(&mut self, choice: &Choice, rng: &mut Rng) -> usize {
+        match self.answers.pop_front() {
+            Some(index) => index,
+            None => self.fallback.decide(choice, rng),
+        }
+    }
+
+    fn name(&self) -> &str {
+        "scripted"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interning::InternedString;
+
+    fn make_choice(options: Vec<Value>) -> Choice {
+        Choice {
+            id: 1,
+            kind: InternedString(1),
+            source: 1,
+            options,
+            default_option: 0,
+            resolve_event: InternedString(2),
+            raised_at: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_random_policy_in_range_and_deterministic() {
+        let choice = make_choice(vec![Value::Integer(1), Value::Integer(2), Value::Integer(3)]);
+        let mut rng1 = Rng::new(7);
+        let mut rng2 = Rng::new(7);
+        for _ in 0..50 {
+            let a = RandomPolicy.decide(&choice, &mut rng1);
+            let b = RandomPolicy.decide(&choice, &mut rng2);
+            assert!(a < 3);
+            assert_eq!(a, b);
+        }
+    }
+
+    #[test]
+    fn test_weighted_policy_skips_zero_weights() {
+        let choice = make_choice(vec![Value::Integer(0), Value::Integer(5), Value::Integer(0)]);
+        let mut policy = WeightedPolicy::by_value();
+        let mut rng = Rng::new(1);
+        for _ in 0..50 {
+            assert_eq!(policy.decide(&choice, &mut rng), 1);
+        }
+        let mut none = WeightedPolicy::by_index(vec![]);
+        assert_eq!(none.decide(&choice, &mut rng), 0);
+    }
+
+    #[test]
+    fn test_weighted_policy_ignores_non_finite_weights() {
+        let choice = make_choice(vec![Value::Integer(0), Value::Integer(1), Value::Integer(2), Value::Integer(3)]);
+        let mut rng = Rng::new(3);
+        let mut policy = WeightedPolicy::by_index(vec![f64::INFINITY, f64::NAN, 2.0, -1.0]);
+        for _ in 0..50 {
+            assert_eq!(policy.decide(&choice, &mut rng), 2);
+        }
+        let mut broken = WeightedPolicy::by_index(vec![f64::NAN, f64::NEG_INFINITY]);
+        assert_eq!(broken.decide(&choice, &mut rng), 0);
+        // Two huge weights overflow the total but are still picked evenly.
+        let mut huge = WeightedPolicy::by_index(vec![0.0, f64::MAX, f64::MAX]);
+        let picks: Vec<usize> = (0..200).map(|_| huge.decide(&choice, &mut rng)).collect();
+        assert!(picks.contains(&1) && picks.contains(&2));
+        assert!(picks.iter().all(|&i| i == 1 || i == 2));
+    }
+
+    #[test]
+    fn test_scripted_policy_then_fallback() {
+        let choice = make_choice(vec![Value::Integer(0), Value::Integer(1), Value::Integer(2)]);
+        let mut policy = ScriptedPolicy::new(vec![2, 1]);
+        let mut rng = Rng::new(1);
+        assert_eq!(policy.decide(&choice, &mut rng), 2);
+        assert_eq!(policy.decide(&choice, &mut rng), 1);
+        assert_eq!(policy.remaining(), 0);
+        assert_eq!(policy.decide(&choice, &mut rng), 0);
+    }
+}
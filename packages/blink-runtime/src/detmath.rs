@@ -0,0 +1,149 @@
+//! Deterministic transcendental functions.
+//!
+//! `f64::ln` and `f64::exp` call the platform math library, whose results may
+//! differ in the last bit between native targets and WASM. A one-ulp
+//! difference in a random draw is enough to change which branch a rule takes,
+//! so everything that feeds the simulation uses these versions instead. They
+//! are built only from IEEE-754 `+ - * /` and bit manipulation, which give the
+//! same result everywhere; accuracy is within a few ulps.
+
+use std::f64::consts::{LN_2, SQRT_2};
+
+/// ln 2 split so that `k * LN2_HI` is exact for any exponent `k`
+/// (Cody–Waite reduction, constants from fdlibm).
+const LN2_HI: f64 = f64::from_bits(0x3fe62e42fee00000);
+const LN2_LO: f64 = f64::from_bits(0x3dea39ef35793c76);
+
+/// Natural logarithm.
+pub fn ln(x: f64) -> f64 {
+    if x.is_nan() || x < 0.0 {
+        return f64::NAN;
+    }
+    if x == 0.0 {
+        return f64::NEG_INFINITY;
+    }
+    if x == f64::INFINITY {
+        return x;
+    }
+    // Split x = m * 2^e with m in [sqrt(1/2), sqrt(2)).
+    let (mut m, mut e) = frexp(x);
+    if m > SQRT_2 {
+        m /= 2.0;
+        e += 1;
+    }
+    // ln(m) = 2 atanh(s) with s = (m - 1) / (m + 1), |s| < 0.172.
+    let s = (m - 1.0) / (m + 1.0);
+    let s2 = s * s;
+    let mut series = 0.0;
+    for k in (0..13).rev() {
+        series = series * s2 + 1.0 / (2 * k + 1) as f64;
+    }
+    let e = e as f64;
+    e * LN2_HI + (e * LN2_LO + 2.0 * s * series)
+}
+
+/// e raised to `x`.
+pub fn exp(x: f64) -> f64 {
+    if x.is_nan() {
+        return x;
+    }
+    if x > 709.8 {
+        return f64::INFINITY;
+    }
+    if x < -745.2 {
+        return 0.0;
+    }
+    // x = k ln2 + r with |r| <= ln2 / 2.
+    let k = (x / LN_2).round();
+    let r = (x - k * LN2_HI) - k * LN2_LO;
+    let mut series = 1.0;
+    for n in (1..=20).rev() {
+        series = 1.0 + series * r / n as f64;
+    }
+    scale2(series, k as i32)
+}
+
+/// `base` raised to `exponent`. Integer exponents use repeated
+/// multiplication; others go through `exp(exponent * ln(base))`.
+pub fn pow(base: f64, exponent: f64) -> f64 {
+    if exponent == 0.0 {
+        return 1.0;
+    }
+    if exponent.fract() == 0.0 && exponent.abs() <= 64.0 {
+        let mut result = 1.0;
+        for _ in 0..exponent.abs() as u32 {
+            result *= base;
+        }
+        return if exponent < 0.0 { 1.0 / result } else { result };
+    }
+    if base < 0.0 {
+        return f64::NAN;
+    }
+    exp(exponent * ln(base))
+}
+
+/// Split a positive finite `x` into a mantissa in [1, 2) and an exponent.
+fn frexp(x: f64) -> (f64, i32) {
+    let bits = x.to_bits();
+    let exponent = ((bits >> 52) & 0x7ff) as i32;
+    if exponent == 0 {
+        // Subnormal: normalize first.
+        let (m, e) = frexp(x * f64::from_bits((1023 + 54) << 52));
+        return (m, e - 54);
+    }
+    let mantissa = f64::from_bits((bits & !(0x7ff << 52)) | (1023 << 52));
+    (mantissa, exponent - 1023)
+}
+
+/// `x * 2^k`, exact unless the result over- or underflows.
+fn scale2(mut x: f64, mut k: i32) -> f64 {
+    while k > 1023 {
+        x *= f64::from_bits(2046 << 52);
+        k -= 1023;
+    }
+    while k < -1022 {
+        x *= f64::from_bits(1 << 52);
+        k += 1022;
+    }
+    x * f64::from_bits(((k + 1023) as u64) << 52)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() <= 4.0 * f64::EPSILON * b.abs().max(f64::MIN_POSITIVE)
+    }
+
+    #[test]
+    fn test_against_std() {
+        for &x in &[1e-300, 1e-5, 0.1, 0.5, 0.999, 1.0, 1.5, 2.0, 10.0, 12345.678, 1e300] {
+            assert!(close(ln(x), x.ln()), "ln({}) = {} vs {}", x, ln(x), x.ln());
+        }
+        for &x in &[-700.0, -20.0, -1.0, -1e-9, 0.0, 0.3, 1.0, 2.5, 50.0, 700.0] {
+            assert!(close(exp(x), x.exp()), "exp({}) = {} vs {}", x, exp(x), x.exp());
+        }
+        assert!(close(pow(2.0, 0.5), SQRT_2));
+        assert_eq!(pow(3.0, 4.0), 81.0);
+        assert_eq!(pow(2.0, -2.0), 0.25);
+    }
+
+    #[test]
+    fn test_special_values() {
+        assert_eq!(ln(0.0), f64::NEG_INFINITY);
+        assert!(ln(-1.0).is_nan());
+        assert_eq!(ln(1.0), 0.0);
+        assert_eq!(exp(0.0), 1.0);
+        assert_eq!(exp(1000.0), f64::INFINITY);
+        assert_eq!(exp(-1000.0), 0.0);
+        assert!(exp(-740.0) > 0.0);
+    }
+
+    #[test]
+    fn test_pinned_bits() {
+        // Fixed results: any change here breaks replay compatibility.
+        assert_eq!(ln(10.0).to_bits(), 0x40026bb1bbb55516);
+        assert_eq!(exp(1.0).to_bits(), 0x4005bf0a8b14576a);
+    }
+}
@@ -0,0 +1,437 @@
+//! Dice notation: "2d6+3", "4d6kh3", "d20!", "1d%-2".
+//!
+//! An expression is a sum of terms separated by `+` or `-`. A term is either
+//! an integer modifier or a dice group `[N]dM` (`N` defaults to 1, `d%` means
+//! `d100`) followed by any of:
+//!
+//! - `!`: exploding; a die showing its maximum is rolled again and added,
+//!   up to `MAX_EXPLOSIONS` times;
+//! - `khK` / `klK`: keep the highest / lowest `K` dice (`kK` is `khK`).
+//!
+//! Integer modifiers are at most `MAX_CONSTANT`. Notation is case-insensitive
+//! and ignores whitespace. Rolls draw from the `Rng` passed in, so results are
+//! reproducible from the engine seed; `DiceRoll` keeps every die for display.
+
+use crate::rng::Rng;
+
+/// Most dice in one group.
+pub const MAX_DICE: u32 = 1000;
+/// Most faces on one die.
+pub const MAX_SIDES: u32 = 1_000_000;
+/// Most re-rolls of one exploding die.
+pub const MAX_EXPLOSIONS: u32 = 100;
+/// Largest integer modifier.
+pub const MAX_CONSTANT: i64 = 1_000_000_000;
+
+/// Errors from parsing dice notation.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DiceError {
+    /// The expression has no terms.
+    Empty,
+    /// Unexpected character at a byte offset.
+    Unexpected { position: usize, found: char },
+    /// The expression ended where more was expected.
+    UnexpectedEnd,
+    /// Dice count, sides, keep count or modifier out of range.
+    OutOfRange { term: String },
+}
+
+impl std::fmt::Display for DiceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DiceError::Empty => write!(f, "empty dice expression"),
+            DiceError::Unexpected { position, found } => {
+                write!(f, "unexpected '{}' at position {} in dice expression", found, position)
+            }
+            DiceError::UnexpectedEnd => write!(f, "dice expression ends unexpectedly"),
+            DiceError::OutOfRange { term } => write!(f, "dice term '{}' is out of range", term),
+        }
+    }
+}
+
+impl std::error::Error for DiceError {}
+
+/// Which dice of a group count towards its total.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Keep {
+    All,
+    Highest(u32),
+    Lowest(u32),
+}
+
+/// One term of a dice expression.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DiceTerm {
+    Constant(i64),
+    Dice {
+        count: u32,
+        sides: u32,
+        exploding: bool,
+        keep: Keep,
+    },
+}
+
+impl DiceTerm {
+    /// Canonical notation, e.g. "4d6kh3".
+    pub fn notation(&self) -> String {
+        match self {
+            DiceTerm::Constant(value) => value.to_string(),
+            DiceTerm::Dice { count, sides, exploding, keep } => {
+                let mut text = format!("{}d{}", count, sides);
+                if *exploding {
+                    text.push('!');
+                }
+                match keep {
+                    Keep::All => {}
+                    Keep::Highest(k) => text.push_str(&format!("kh{}", k)),
+                    Keep::Lowest(k) => text.push_str(&format!("kl{}", k)),
+                }
+                text
+            }
+        }
+    }
+}
+
+/// A parsed dice expression: signed terms summed left to right.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DiceExpr {
+    pub terms: Vec<(i64, DiceTerm)>,
+}
+
+/// The outcome of rolling one term.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TermRoll {
+    /// +1 or -1.
+    pub sign: i64,
+    pub term: DiceTerm,
+    /// Every die, in roll order; an exploded die counts as one die with the
+    /// sum of its rolls. Empty for constants.
+    pub rolls: Vec<i64>,
+    /// Whether each die in `rolls` was kept.
+    pub kept: Vec<bool>,
+    /// Signed contribution to the total.
+    pub total: i64,
+}
+
+/// The outcome of rolling a whole expression.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DiceRoll {
+    pub total: i64,
+    pub terms: Vec<TermRoll>,
+}
+
+impl DiceExpr {
+    pub fn parse(text: &str) -> Result<DiceExpr, DiceError> {
+        let mut parser = Parser {
+            chars: text
+                .char_indices()
+                .filter(|(_, c)| !c.is_whitespace())
+                .map(|(i, c)| (i, c.to_ascii_lowercase()))
+                .collect(),
+            pos: 0,
+        };
+        if parser.chars.is_empty() {
+            return Err(DiceError::Empty);
+        }
+        let mut terms = Vec::new();
+        let mut sign = match parser.peek() {
+            Some('-') => {
+                parser.pos += 1;
+                -1
+            }
+            Some('+') => {
+                parser.pos += 1;
+                1
+            }
+            _ => 1,
+        };
+        loop {
+            terms.push((sign, parser.term()?));
+            sign = match parser.next() {
+                None => break,
+                Some((_, '+')) => 1,
+                Some((_, '-')) => -1,
+                Some((position, found)) => return Err(DiceError::Unexpected { position, found }),
+            };
+        }
+        Ok(DiceExpr { terms })
+    }
+
+    /// Roll every term and sum the results, saturating at the `i64` bounds.
+    pub fn roll(&self, rng: &mut Rng) -> DiceRoll {
+        let terms: Vec<TermRoll> = self.terms.iter().map(|(sign, term)| roll_term(*sign, term, rng)).collect();
+        DiceRoll {
+            total: terms.iter().fold(0, |total: i64, t| total.saturating_add(t.total)),
+            terms,
+        }
+    }
+
+    /// Smallest and largest possible totals, ignoring explosions.
+    pub fn range(&self) -> (i64, i64) {
+        let mut low: i64 = 0;
+        let mut high: i64 = 0;
+        for (sign, term) in &self.terms {
+            let (min, max) = match term {
+                DiceTerm::Constant(value) => (*value, *value),
+                DiceTerm::Dice { count, sides, keep, .. } => {
+                    let counted = match keep {
+                        Keep::All => *count,
+                        Keep::Highest(k) | Keep::Lowest(k) => *k,
+                    } as i64;
+                    (counted, counted.saturating_mul(*sides as i64))
+                }
+            };
+            if *sign > 0 {
+                low = low.saturating_add(min);
+                high = high.saturating_add(max);
+            } else {
+                low = low.saturating_sub(max);
+                high = high.saturating_sub(min);
+            }
+        }
+        (low, high)
+    }
+}
+
+fn roll_term(sign: i64, term: &DiceTerm, rng: &mut Rng) -> TermRoll {
+    let (count, sides, exploding, keep) = match term {
+        DiceTerm::Constant(value) => {
+            return TermRoll {
+                sign,
+                term: term.clone(),
+                rolls: Vec::new(),
+                kept: Vec::new(),
+                total: sign * value,
+            };
+        }
+        DiceTerm::Dice { count, sides, exploding, keep } => (*count, *sides as i64, *exploding, *keep),
+    };
+    let rolls: Vec<i64> = (0..count)
+        .map(|_| {
+            let mut value = rng.random_int_range(1, sides);
+            let mut last = value;
+            let mut explosions = 0;
+            while exploding && last == sides && explosions < MAX_EXPLOSIONS {
+                last = rng.random_int_range(1, sides);
+                value += last;
+                explosions += 1;
+            }
+            value
+        })
+        .collect();
+
+    let mut kept = vec![true; rolls.len()];
+    if let Keep::Highest(k) | Keep::Lowest(k) = keep {
+        // Stable order: among equal dice the earlier roll is kept.
+        let mut order: Vec<usize> = (0..rolls.len()).collect();
+        match keep {
+            Keep::Highest(_) => order.sort_by(|&a, &b| rolls[b].cmp(&rolls[a])),
+            _ => order.sort_by_key(|&i| rolls[i]),
+        }
+        for &i in &order[k as usize..] {
+            kept[i] = false;
+        }
+    }
+    let sum = rolls.iter().zip(&kept).filter(|(_, k)| **k).fold(0, |sum: i64, (r, _)| sum.saturating_add(*r));
+    TermRoll {
+        sign,
+        term: term.clone(),
+        rolls,
+        kept,
+        total: sign * sum,
+    }
+}
+
+struct Parser {
+    /// Non-whitespace characters, lowercased, with their byte offsets.
+    chars: Vec<(usize, char)>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).map(|(_, c)| *c)
+    }
+
+    fn next(&mut self) -> Option<(usize, char)> {
+        let next = self.chars.get(self.pos).copied();
+        self.pos += 1;
+        next
+    }
+
+    fn number(&mut self) -> Option<u64> {
+        let start = self.pos;
+        let mut value: u64 = 0;
+        while let Some(digit) = self.peek().and_then(|c| c.to_digit(10)) {
+            value = value.saturating_mul(10).saturating_add(digit as u64);
+            self.pos += 1;
+        }
+        (self.pos > start).then_some(value)
+    }
+
+    fn expect_number(&mut self) -> Result<u64, DiceError> {
+        match self.number() {
+            Some(value) => Ok(value),
+            None => Err(self.unexpected()),
+        }
+    }
+
+    fn unexpected(&self) -> DiceError {
+        match self.chars.get(self.pos) {
+            Some(&(position, found)) => DiceError::Unexpected { position, found },
+            None => DiceError::UnexpectedEnd,
+        }
+    }
+
+    /// Source text from `start` up to the current position.
+    fn text(&self, start: usize) -> String {
+        self.chars[start..self.pos].iter().map(|(_, c)| c).collect()
+    }
+
+    fn term(&mut self) -> Result<DiceTerm, DiceError> {
+        let start = self.pos;
+        let count = self.number();
+        if self.peek() != Some('d') {
+            return match count {
+                Some(value) if value <= MAX_CONSTANT as u64 => Ok(DiceTerm::Constant(value as i64)),
+                Some(_) => Err(DiceError::OutOfRange { term: self.text(start) }),
+                None => Err(self.unexpected()),
+            };
+        }
+        self.pos += 1;
+        let sides = if self.peek() == Some('%') {
+            self.pos += 1;
+            100
+        } else {
+            self.expect_number()?
+        };
+        let count = count.unwrap_or(1);
+
+        let mut exploding = false;
+        let mut keep = Keep::All;
+        loop {
+            match self.peek() {
+                Some('!') => {
+                    self.pos += 1;
+                    exploding = true;
+                }
+                Some('k') => {
+                    self.pos += 1;
+                    let lowest = self.peek() == Some('l');
+                    if matches!(self.peek(), Some('l' | 'h')) {
+                        self.pos += 1;
+                    }
+                    let k = self.expect_number()?.min(u32::MAX as u64) as u32;
+                    keep = if lowest { Keep::Lowest(k) } else { Keep::Highest(k) };
+                }
+                _ => break,
+            }
+        }
+
+        let in_range = (1..=MAX_DICE as u64).contains(&count)
+            && (1..=MAX_SIDES as u64).contains(&sides)
+            && !(exploding && sides == 1)
+            && match keep {
+                Keep::All => true,
+                Keep::Highest(k) | Keep::Lowest(k) => k as u64 <= count,
+            };
+        let term = DiceTerm::Dice {
+            count: count.min(u32::MAX as u64) as u32,
+            sides: sides.min(u32::MAX as u64) as u32,
+            exploding,
+            keep,
+        };
+        if !in_range {
+            return Err(DiceError::OutOfRange { term: term.notation() });
+        }
+        Ok(term)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dice(count: u32, sides: u32, exploding: bool, keep: Keep) -> DiceTerm {
+        DiceTerm::Dice { count, sides, exploding, keep }
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            DiceExpr::parse("2d6 + 3").unwrap().terms,
+            vec![(1, dice(2, 6, false, Keep::All)), (1, DiceTerm::Constant(3))]
+        );
+        assert_eq!(
+            DiceExpr::parse("-1 + 4D6kh3 - d%").unwrap().terms,
+            vec![
+                (-1, DiceTerm::Constant(1)),
+                (1, dice(4, 6, false, Keep::Highest(3))),
+                (-1, dice(1, 100, false, Keep::All)),
+            ]
+        );
+        assert_eq!(DiceExpr::parse("2d20kl1").unwrap().terms[0].1, dice(2, 20, false, Keep::Lowest(1)));
+        assert_eq!(DiceExpr::parse("3d6!k2").unwrap().terms[0].1, dice(3, 6, true, Keep::Highest(2)));
+        assert_eq!(DiceExpr::parse("3d6!k2").unwrap().terms[0].1.notation(), "3d6!kh2");
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(DiceExpr::parse("  "), Err(DiceError::Empty));
+        assert_eq!(DiceExpr::parse("2d"), Err(DiceError::UnexpectedEnd));
+        assert_eq!(DiceExpr::parse("2d6 x"), Err(DiceError::Unexpected { position: 4, found: 'x' }));
+        assert_eq!(DiceExpr::parse("2d6+"), Err(DiceError::UnexpectedEnd));
+        assert!(matches!(DiceExpr::parse("0d6"), Err(DiceError::OutOfRange { .. })));
+        assert!(matches!(DiceExpr::parse("2d6kh3"), Err(DiceError::OutOfRange { .. })));
+        assert!(matches!(DiceExpr::parse("1d1!"), Err(DiceError::OutOfRange { .. })));
+        assert_eq!(
+            DiceExpr::parse("1d6 + 99999999999999999999"),
+            Err(DiceError::OutOfRange { term: "99999999999999999999".to_string() })
+        );
+        assert!(DiceExpr::parse("1000000000").is_ok());
+        assert!(DiceExpr::parse("1000000001").is_err());
+    }
+
+    #[test]
+    fn test_totals_saturate() {
+        let mut rng = Rng::new(3);
+        let huge = DiceExpr {
+            terms: vec![(1, DiceTerm::Constant(i64::MAX)), (1, dice(2, 6, false, Keep::All))],
+        };
+        assert_eq!(huge.roll(&mut rng).total, i64::MAX);
+        assert_eq!(huge.range(), (i64::MAX, i64::MAX));
+        let negative = DiceExpr {
+            terms: vec![(-1, DiceTerm::Constant(i64::MAX)), (-1, DiceTerm::Constant(5))],
+        };
+        assert_eq!(negative.roll(&mut rng).total, i64::MIN);
+    }
+
+    #[test]
+    fn test_roll_totals() {
+        let mut rng = Rng::new(1);
+        let expr = DiceExpr::parse("4d6kh3+2").unwrap();
+        assert_eq!(expr.range(), (5, 20));
+        for _ in 0..200 {
+            let roll = expr.roll(&mut rng);
+            let (low, high) = expr.range();
+            assert!((low..=high).contains(&roll.total));
+            let dice = &roll.terms[0];
+            assert_eq!(dice.kept.iter().filter(|k| **k).count(), 3);
+            let dropped = dice.rolls.iter().zip(&dice.kept).find(|(_, k)| !**k).unwrap().0;
+            assert!(dice.rolls.iter().zip(&dice.kept).filter(|(_, k)| **k).all(|(r, _)| r >= dropped));
+        }
+        let roll = DiceExpr::parse("3d6-2").unwrap().roll(&mut rng);
+        assert_eq!(roll.terms.iter().map(|t| t.total).sum::<i64>(), roll.total);
+        assert_eq!(roll.terms[1].total, -2);
+        assert_eq!(DiceExpr::parse("10-3").unwrap().roll(&mut rng).total, 7);
+    }
+
+    #[test]
+    fn test_exploding_dice_exceed_sides() {
+        let mut rng = Rng::new(2);
+        let expr = DiceExpr::parse("d2!").unwrap();
+        let totals: Vec<i64> = (0..100).map(|_| expr.roll(&mut rng).total).collect();
+        assert!(totals.iter().all(|&t| t >= 1));
+        assert!(totals.iter().any(|&t| t > 2));
+    }
+}
@@ -0,0 +1,281 @@
+//! Schema-defined component storage.
+//!
+//! `TypedStorage<C>` needs a Rust type generated at compile time. Mods and
+//! content packs add components at runtime instead, so `DynamicStorage` stores
+//! each component as a row of `Value`s laid out by a `ComponentSchema`
+//! (field names, types and defaults). Dynamic storages are registered on the
+//! `World` by name and live alongside the typed ones: despawning, cloning,
+//! the JSON bridge and state snapshots all see both.
+
+use std::collections::{BTreeMap, HashMap};
+
+use serde::Deserialize;
+
+use crate::interning::{LiveStrings, MarkStrings, StringInterner};
+use crate::value::{EntityId, Value, ValueType};
+use crate::world::ComponentStorage;
+
+/// One field of a schema-defined component.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FieldSchema {
+    pub name: String,
+    pub value_type: ValueType,
+    pub default: Value,
+}
+
+/// Layout of a schema-defined component.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ComponentSchema {
+    pub name: String,
+    pub fields: Vec<FieldSchema>,
+}
+
+#[derive(Deserialize)]
+struct FieldSchemaJson {
+    name: String,
+    #[serde(rename = "type")]
+    value_type: ValueType,
+    #[serde(default)]
+    default: Option<serde_json::Value>,
+}
+
+#[derive(Deserialize)]
+struct ComponentSchemaJson {
+    name: String,
+    fields: Vec<FieldSchemaJson>,
+}
+
+impl ComponentSchema {
+    pub fn new(name: &str) -> Self {
+        ComponentSchema {
+            name: name.to_string(),
+            fields: Vec::new(),
+        }
+    }
+
+    /// Builder: add a field with the type's zero value as default.
+    pub fn with_field(self, name: &str, value_type: ValueType) -> Self {
+        self.with_field_default(name, value_type, value_type.default_value())
+    }
+
+    /// Builder: add a field with an explicit default.
+    pub fn with_field_default(mut self, name: &str, value_type: ValueType, default: Value) -> Self {
+        self.fields.push(FieldSchema {
+            name: name.to_string(),
+            value_type,
+            default: value_type.coerce(default),
+        });
+        self
+    }
+
+    /// Parse a schema such as
+    /// `{"name": "Poisoned", "fields": [{"name": "dps", "type": "number", "default": 1.5}]}`.
+    pub fn from_json(json: &str, interner: &mut StringInterner) -> Result<Self, String> {
+        let parsed: ComponentSchemaJson = serde_json::from_str(json).map_err(|e| e.to_string())?;
+        let mut schema = ComponentSchema::new(&parsed.name);
+        for field in parsed.fields {
+            let default = match &field.default {
+                Some(json) => Value::from_json_value(json, interner),
+                None => field.value_type.default_value(),
+            };
+            schema = schema.with_field_default(&field.name, field.value_type, default);
+        }
+        Ok(schema)
+    }
+
+    /// Index of a field in a component row.
+    pub fn field_index(&self, name: &str) -> Option<usize> {
+        self.fields.iter().position(|f| f.name == name)
+    }
+
+    /// A row holding every field's default.
+    pub fn default_row(&self) -> Vec<Value> {
+        self.fields.iter().map(|f| f.default.clone()).collect()
+    }
+}
+
+/// Component storage laid out by a runtime schema.
+/// Uses BTreeMap for deterministic iteration order, like `TypedStorage`.
+pub struct DynamicStorage {
+    schema: ComponentSchema,
+    pub data: BTreeMap<EntityId, Vec<Value>>,
+}
+
+impl DynamicStorage {
+    pub fn new(schema: ComponentSchema) -> Self {
+        DynamicStorage {
+            schema,
+            data: BTreeMap::new(),
+        }
+    }
+
+    pub fn schema(&self) -> &ComponentSchema {
+        &self.schema
+    }
+
+    /// Attach the component with default values for every field.
+    pub fn insert_default(&mut self, entity: EntityId) {
+        self.data.insert(entity, self.schema.default_row());
+    }
+
+    /// Attach the component, setting the named fields and defaulting the rest.
+    /// Values are coerced to the declared field types; unknown names are ignored.
+    pub fn insert_fields(&mut self, entity: EntityId, fields: Vec<(&str, Value)>) {
+        let mut row = self.schema.default_row();
+        for (name, value) in fields {
+            if let Some(i) = self.schema.field_index(name) {
+                row[i] = self.schema.fields[i].value_type.coerce(value);
+            }
+        }
+        self.data.insert(entity, row);
+    }
+
+    /// The full row of field values for an entity.
+    pub fn get(&self, entity: EntityId) -> Option<&[Value]> {
+        self.data.get(&entity).map(|row| row.as_slice())
+    }
+
+    /// Read a field by name.
+    pub fn get_field(&self, entity: EntityId, field: &str) -> Option<&Value> {
+        let i = self.schema.field_index(field)?;
+        self.data.get(&entity).map(|row| &row[i])
+    }
+
+    /// Write a field by name, coercing to its declared type.
+    /// Returns false if the entity lacks the component or the field is unknown.
+    pub fn set_field(&mut self, entity: EntityId, field: &str, value: Value) -> bool {
+        let Some(i) = self.schema.field_index(field) else {
+            return false;
+        };
+        let value_type = self.schema.fields[i].value_type;
+        match self.data.get_mut(&entity) {
+            Some(row) => {
+                row[i] = value_type.coerce(value);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Attach the component from a flat JSON object keyed by field name.
+    /// Missing fields take their defaults. Returns false if `json` is not an object.
+    pub fn insert_from_json(&mut self, entity: EntityId, json: &serde_json::Value, interner: &mut StringInterner) -> bool {
+        let Some(object) = json.as_object() else {
+            return false;
+        };
+        let mut row = self.schema.default_row();
+        for (i, field) in self.schema.fields.iter().enumerate() {
+            if let Some(v) = object.get(&field.name) {
+                row[i] = field.value_type.coerce(Value::from_json_value(v, interner));
+            }
+        }
+        self.data.insert(entity, row);
+        true
+    }
+
+    /// Serialize an entity's component as a map of field name to JSON value,
+    /// the shape used by state snapshots.
+    pub fn to_json(&self, entity: EntityId, interner: &StringInterner) -> Option<HashMap<String, serde_json::Value>> {
+        let row = self.data.get(&entity)?;
+        Some(
+            self.schema
+                .fields
+                .iter()
+                .zip(row)
+                .map(|(f, v)| (f.name.clone(), v.to_json_value(interner)))
+                .collect(),
+        )
+    }
+}
+
+impl ComponentStorage for DynamicStorage {
+    fn remove(&mut self, entity: EntityId) {
+        self.data.remove(&entity);
+    }
+
+    fn has(&self, entity: EntityId) -> bool {
+        self.data.contains_key(&entity)
+    }
+
+    fn entity_ids(&self) -> Vec<EntityId> {
+        self.data.keys().copied().collect()
+    }
+
+    fn clone_component(&mut self, source: EntityId, dest: EntityId) {
+        if let Some(row) = self.data.get(&source).cloned() {
+            self.data.insert(dest, row);
+        }
+    }
+
+    fn mark_strings(&self, live: &mut LiveStrings) {
+        for field in &self.schema.fields {
+            field.default.mark_strings(live);
+        }
+        for row in self.data.values() {
+            row.mark_strings(live);
+        }
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn poisoned() -> ComponentSchema {
+        ComponentSchema::new("Poisoned")
+            .with_field_default("dps", ValueType::Number, Value::Number(1.5))
+            .with_field("ticks", ValueType::Integer)
+            .with_field("source", ValueType::Entity)
+    }
+
+    #[test]
+    fn test_insert_and_access() {
+        let mut storage = DynamicStorage::new(poisoned());
+        storage.insert_fields(3, vec![("ticks", Value::Number(4.7)), ("bogus", Value::Integer(1))]);
+        assert!(storage.has(3));
+        assert_eq!(storage.get_field(3, "dps"), Some(&Value::Number(1.5)));
+        assert_eq!(storage.get_field(3, "ticks"), Some(&Value::Integer(4)));
+        assert!(storage.set_field(3, "source", Value::Integer(9)));
+        assert_eq!(storage.get_field(3, "source"), Some(&Value::Entity(9)));
+        assert!(!storage.set_field(4, "ticks", Value::Integer(1)));
+        assert!(!storage.set_field(3, "nope", Value::Integer(1)));
+    }
+
+    #[test]
+    fn test_schema_from_json_and_bridge() {
+        let mut interner = StringInterner::new();
+        let schema = ComponentSchema::from_json(
+            r#"{"name": "Tag", "fields": [
+                {"name": "label", "type": "string", "default": "none"},
+                {"name": "weight", "type": "decimal"}
+            ]}"#,
+            &mut interner,
+        )
+        .unwrap();
+        let mut storage = DynamicStorage::new(schema);
+        let json: serde_json::Value = serde_json::json!({ "weight": 2 });
+        assert!(storage.insert_from_json(1, &json, &mut interner));
+        let out = storage.to_json(1, &interner).unwrap();
+        assert_eq!(out["label"], serde_json::json!("none"));
+        assert_eq!(out["weight"], serde_json::json!(2.0));
+        assert!(!storage.insert_from_json(2, &serde_json::json!([1]), &mut interner));
+    }
+
+    #[test]
+    fn test_clone_and_remove() {
+        let mut storage = DynamicStorage::new(poisoned());
+        storage.insert_default(1);
+        storage.clone_component(1, 2);
+        assert_eq!(storage.entity_ids(), vec![1, 2]);
+        storage.remove(1);
+        assert_eq!(storage.entity_ids(), vec![2]);
+    }
+}
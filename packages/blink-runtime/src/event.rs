@@ -1,9 +1,7 @@
-/// Event struct for the timeline system.
-/// Events are the primary mechanism for triggering game logic.
+//! Event struct for the timeline system.
+//! Events are the primary mechanism for triggering game logic.
 
-use std::collections::HashMap;
-
-use crate::interning::InternedString;
+use crate::interning::{InternedString, LiveStrings, MarkStrings, StringInterner};
 use crate::value::{EntityId, Value, NO_ENTITY};
 
 /// Unique event ID for cancellation.
@@ -15,10 +13,194 @@ pub struct Event {
     pub event_type: InternedString,
     pub source: EntityId,
     pub target: EntityId,
-    pub fields: HashMap<InternedString, Value>,
+    pub fields: EventFields,
     pub event_id: EventId,
 }
 
+/// Number of fields stored without a heap allocation.
+pub const INLINE_FIELDS: usize = 2;
+
+type FieldEntry = (InternedString, Value);
+
+/// Event fields: a small map from field name to value.
+///
+/// Most events carry zero to two fields, and events are cloned into the
+/// timeline and popped millions of times in batch runs, so up to
+/// `INLINE_FIELDS` entries live inline in the event and only larger payloads
+/// spill to the heap. Lookups scan linearly, which beats hashing at these
+/// sizes. The API mirrors the `HashMap` it replaces; iteration follows
+/// insertion order and equality ignores it.
+pub struct EventFields {
+    repr: FieldsRepr,
+}
+
+enum FieldsRepr {
+    Inline { len: u8, entries: [FieldEntry; INLINE_FIELDS] },
+    Heap(Vec<FieldEntry>),
+}
+
+impl EventFields {
+    pub fn new() -> Self {
+        EventFields {
+            repr: FieldsRepr::Inline {
+                len: 0,
+                entries: Default::default(),
+            },
+        }
+    }
+
+    fn entries(&self) -> &[FieldEntry] {
+        match &self.repr {
+            FieldsRepr::Inline { len, entries } => &entries[..*len as usize],
+            FieldsRepr::Heap(entries) => entries,
+        }
+    }
+
+    fn entries_mut(&mut self) -> &mut [FieldEntry] {
+        match &mut self.repr {
+            FieldsRepr::Inline { len, entries } => &mut entries[..*len as usize],
+            FieldsRepr::Heap(entries) => entries,
+        }
+    }
+
+    /// Set a field, returning the previous value if it was present.
+    pub fn insert(&mut self, name: InternedString, value: Value) -> Option<Value> {
+        if let Some(slot) = self.get_mut(&name) {
+            return Some(std::mem::replace(slot, value));
+        }
+        match &mut self.repr {
+            FieldsRepr::Inline { len, entries } if (*len as usize) < INLINE_FIELDS => {
+                entries[*len as usize] = (name, value);
+                *len += 1;
+            }
+            FieldsRepr::Inline { entries, .. } => {
+                let mut spilled = Vec::with_capacity(INLINE_FIELDS * 2);
+                spilled.extend(entries.iter_mut().map(std::mem::take));
+                spilled.push((name, value));
+                self.repr = FieldsRepr::Heap(spilled);
+            }
+            FieldsRepr::Heap(entries) => entries.push((name, value)),
+        }
+        None
+    }
+
+    pub fn get(&self, name: &InternedString) -> Option<&Value> {
+        self.entries().iter().find(|(k, _)| k == name).map(|(_, v)| v)
+    }
+
+    pub fn get_mut(&mut self, name: &InternedString) -> Option<&mut Value> {
+        self.entries_mut().iter_mut().find(|(k, _)| k == name).map(|(_, v)| v)
+    }
+
+    pub fn contains_key(&self, name: &InternedString) -> bool {
+        self.get(name).is_some()
+    }
+
+    /// Remove a field, keeping the order of the others.
+    pub fn remove(&mut self, name: &InternedString) -> Option<Value> {
+        let index = self.entries().iter().position(|(k, _)| k == name)?;
+        match &mut self.repr {
+            FieldsRepr::Inline { len, entries } => {
+                let (_, value) = std::mem::take(&mut entries[index]);
+                entries[index..*len as usize].rotate_left(1);
+                *len -= 1;
+                Some(value)
+            }
+            FieldsRepr::Heap(entries) => Some(entries.remove(index).1),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries().is_empty()
+    }
+
+    /// Fields in insertion order.
+    pub fn iter(&self) -> impl Iterator<Item = (&InternedString, &Value)> {
+        self.entries().iter().map(|(k, v)| (k, v))
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &InternedString> {
+        self.entries().iter().map(|(k, _)| k)
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &Value> {
+        self.entries().iter().map(|(_, v)| v)
+    }
+
+    /// True once the fields no longer fit inline.
+    pub fn spilled(&self) -> bool {
+        matches!(self.repr, FieldsRepr::Heap(_))
+    }
+}
+
+impl Clone for EventFields {
+    /// Clones only the occupied inline slots.
+    fn clone(&self) -> Self {
+        let repr = match &self.repr {
+            FieldsRepr::Inline { len, entries } => FieldsRepr::Inline {
+                len: *len,
+                entries: std::array::from_fn(|i| {
+                    if i < *len as usize {
+                        entries[i].clone()
+                    } else {
+                        (InternedString::NONE, Value::None)
+                    }
+                }),
+            },
+            FieldsRepr::Heap(entries) => FieldsRepr::Heap(entries.clone()),
+        };
+        EventFields { repr }
+    }
+}
+
+impl Default for EventFields {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PartialEq for EventFields {
+    fn eq(&self, other: &EventFields) -> bool {
+        self.len() == other.len() && self.iter().all(|(k, v)| other.get(k) == Some(v))
+    }
+}
+
+impl std::fmt::Debug for EventFields {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl FromIterator<(InternedString, Value)> for EventFields {
+    fn from_iter<I: IntoIterator<Item = (InternedString, Value)>>(iter: I) -> Self {
+        let mut fields = EventFields::new();
+        fields.extend(iter);
+        fields
+    }
+}
+
+impl Extend<(InternedString, Value)> for EventFields {
+    fn extend<I: IntoIterator<Item = (InternedString, Value)>>(&mut self, iter: I) {
+        for (name, value) in iter {
+            self.insert(name, value);
+        }
+    }
+}
+
+impl MarkStrings for Event {
+    fn mark_strings(&self, live: &mut LiveStrings) {
+        self.event_type.mark_strings(live);
+        for (name, value) in self.fields.iter() {
+            name.mark_strings(live);
+            value.mark_strings(live);
+        }
+    }
+}
+
 impl Event {
     /// Create a new event with the given type.
     pub fn new(event_type: InternedString) -> Self {
@@ -26,7 +208,7 @@ impl Event {
             event_type,
             source: NO_ENTITY,
             target: NO_ENTITY,
-            fields: HashMap::new(),
+            fields: EventFields::new(),
             event_id: 0,
         }
     }
@@ -53,6 +235,89 @@ impl Event {
     pub fn get_field(&self, name: InternedString) -> &Value {
         self.fields.get(&name).unwrap_or(&Value::None)
     }
+
+    /// Whether the event refers to an entity: as source, as target, or as an
+    /// entity value in any field (including inside lists and maps).
+    pub fn references(&self, entity: EntityId) -> bool {
+        if entity == NO_ENTITY {
+            return false;
+        }
+        self.source == entity || self.target == entity || self.fields.values().any(|v| value_references(v, entity))
+    }
+
+    /// Parse an event such as
+    /// `{"type": "UseItem", "source": 3, "fields": {"item": "Potion"}}`.
+    /// `source`, `target`, `fields` and `id` are optional; the timeline
+    /// assigns a fresh ID when the event is scheduled.
+    pub fn from_json(json: &str, interner: &mut StringInterner) -> Result<Event, String> {
+        let parsed: serde_json::Value = serde_json::from_str(json).map_err(|e| e.to_string())?;
+        Event::from_json_value(&parsed, interner)
+    }
+
+    /// Build an event from an already-parsed JSON object (see `from_json`).
+    pub fn from_json_value(json: &serde_json::Value, interner: &mut StringInterner) -> Result<Event, String> {
+        let object = json.as_object().ok_or("event must be a JSON object")?;
+        let event_type = object
+            .get("type")
+            .and_then(|t| t.as_str())
+            .ok_or("event is missing a string \"type\"")?;
+        let mut event = Event::new(interner.intern(event_type));
+        event.source = id_field(object, "source")?;
+        event.target = id_field(object, "target")?;
+        event.event_id = id_field(object, "id")?;
+        match object.get("fields") {
+            None | Some(serde_json::Value::Null) => {}
+            Some(serde_json::Value::Object(fields)) => {
+                for (name, value) in fields {
+                    let name = interner.intern(name);
+                    event.fields.insert(name, Value::from_json_value(value, interner));
+                }
+            }
+            Some(_) => return Err("event \"fields\" must be an object".to_string()),
+        }
+        Ok(event)
+    }
+
+    /// Serialize for the host (JS) side, in the shape `from_json` accepts.
+    pub fn to_json_value(&self, interner: &StringInterner) -> serde_json::Value {
+        let fields: serde_json::Map<String, serde_json::Value> = self
+            .fields
+            .iter()
+            .map(|(k, v)| (interner.resolve(*k).to_string(), v.to_json_value(interner)))
+            .collect();
+        serde_json::json!({
+            "id": self.event_id,
+            "type": interner.resolve(self.event_type),
+            "source": self.source,
+            "target": self.target,
+            "fields": fields,
+        })
+    }
+
+    /// Serialize to a JSON string (see `to_json_value`).
+    pub fn to_json(&self, interner: &StringInterner) -> String {
+        self.to_json_value(interner).to_string()
+    }
+}
+
+fn value_references(value: &Value, entity: EntityId) -> bool {
+    match value {
+        Value::Entity(id) => *id == entity,
+        Value::List(items) => items.iter().any(|v| value_references(v, entity)),
+        Value::Map(entries) => entries.values().any(|v| value_references(v, entity)),
+        _ => false,
+    }
+}
+
+/// Read an optional non-negative integer property (entity or event ID).
+fn id_field(object: &serde_json::Map<String, serde_json::Value>, key: &str) -> Result<u32, String> {
+    match object.get(key) {
+        None | Some(serde_json::Value::Null) => Ok(NO_ENTITY),
+        Some(v) => v
+            .as_u64()
+            .and_then(|n| u32::try_from(n).ok())
+            .ok_or_else(|| format!("event \"{}\" must be a non-negative integer", key)),
+    }
 }
 
 #[cfg(test)]
@@ -77,6 +342,80 @@ mod tests {
         assert_eq!(*event.get_field(field_name), Value::Integer(10));
     }
 
+    #[test]
+    fn test_event_json_round_trip() {
+        let mut interner = StringInterner::new();
+        let event = Event::from_json(
+            r#"{"type":"UseItem","source":3,"fields":{"item":"Potion","uses":{"left":2}}}"#,
+            &mut interner,
+        )
+        .unwrap();
+        assert_eq!(interner.resolve(event.event_type), "UseItem");
+        assert_eq!(event.source, 3);
+        assert_eq!(event.target, NO_ENTITY);
+        let item = interner.intern("item");
+        assert_eq!(*event.get_field(item), Value::String(interner.intern("Potion")));
+
+        let back = Event::from_json(&event.to_json(&interner), &mut interner).unwrap();
+        assert_eq!(back.event_type, event.event_type);
+        assert_eq!(back.source, 3);
+        assert_eq!(back.fields, event.fields);
+    }
+
+    #[test]
+    fn test_event_json_errors() {
+        let mut interner = StringInterner::new();
+        assert!(Event::from_json("[]", &mut interner).is_err());
+        assert!(Event::from_json(r#"{"source":1}"#, &mut interner).is_err());
+        assert!(Event::from_json(r#"{"type":"A","source":-1}"#, &mut interner).is_err());
+        assert!(Event::from_json(r#"{"type":"A","fields":[1]}"#, &mut interner).is_err());
+    }
+
+    #[test]
+    fn test_event_fields_inline_then_spill() {
+        let mut fields = EventFields::new();
+        for i in 0..INLINE_FIELDS as u32 {
+            assert_eq!(fields.insert(InternedString(i + 1), Value::Integer(i as i64)), None);
+        }
+        assert!(!fields.spilled());
+        assert_eq!(fields.insert(InternedString(1), Value::Integer(10)), Some(Value::Integer(0)));
+        assert_eq!(fields.len(), INLINE_FIELDS);
+
+        fields.insert(InternedString(99), Value::Boolean(true));
+        assert!(fields.spilled());
+        assert_eq!(fields.get(&InternedString(1)), Some(&Value::Integer(10)));
+        assert_eq!(fields.get(&InternedString(99)), Some(&Value::Boolean(true)));
+        assert_eq!(fields.remove(&InternedString(99)), Some(Value::Boolean(true)));
+        assert_eq!(fields.len(), INLINE_FIELDS);
+    }
+
+    #[test]
+    fn test_event_fields_remove_and_equality() {
+        let a: EventFields = [(InternedString(1), Value::Integer(1)), (InternedString(2), Value::Integer(2))]
+            .into_iter()
+            .collect();
+        let mut b: EventFields = [(InternedString(2), Value::Integer(2)), (InternedString(1), Value::Integer(1))]
+            .into_iter()
+            .collect();
+        assert_eq!(a, b);
+        assert_eq!(b.remove(&InternedString(2)), Some(Value::Integer(2)));
+        assert_eq!(b.remove(&InternedString(2)), None);
+        assert_ne!(a, b);
+        assert_eq!(b.keys().copied().collect::<Vec<_>>(), vec![InternedString(1)]);
+        assert!(!b.contains_key(&InternedString(2)));
+    }
+
+    #[test]
+    fn test_event_references() {
+        let event = Event::new(InternedString(1))
+            .with_source(1)
+            .with_field(InternedString(2), Value::List(vec![Value::Entity(7)]));
+        assert!(event.references(1));
+        assert!(event.references(7));
+        assert!(!event.references(2));
+        assert!(!event.references(NO_ENTITY));
+    }
+
     #[test]
     fn test_event_missing_field() {
         let mut interner = StringInterner::new();
@@ -0,0 +1,389 @@
+//! Event schemas: declared field names, types and required flags per event.
+//!
+//! `Event.fields` is free-form, so a misspelt field name only shows up as a
+//! `Value::None` when a rule reads it. The registry holds one schema per event
+//! type (generated from BRL `event` declarations, or loaded from JSON) and the
+//! timeline checks every scheduled event against it. What happens on a
+//! mismatch depends on the `ValidationMode`: debug builds record violations,
+//! release builds skip the check, and strict mode panics at the offending
+//! `schedule` call.
+//!
+//! Events without a registered schema are never checked. The `source` and
+//! `target` properties are built into every event and are not schema fields.
+//! `EventSchemaRegistry::to_json` exports the registry in the same format
+//! `register_json` reads, so the host app can type-check events it injects.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::event::Event;
+use crate::interning::{InternedString, LiveStrings, MarkStrings, StringInterner};
+use crate::value::{Value, ValueType};
+
+/// One declared field of an event.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct EventFieldSchema {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub value_type: ValueType,
+    #[serde(default = "required_default")]
+    pub required: bool,
+}
+
+fn required_default() -> bool {
+    true
+}
+
+/// Declared fields of an event type.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct EventSchema {
+    pub name: String,
+    #[serde(default)]
+    pub fields: Vec<EventFieldSchema>,
+}
+
+impl EventSchema {
+    pub fn new(name: &str) -> Self {
+        EventSchema {
+            name: name.to_string(),
+            fields: Vec::new(),
+        }
+    }
+
+    /// Builder: add a required field.
+    pub fn with_field(mut self, name: &str, value_type: ValueType) -> Self {
+        self.fields.push(EventFieldSchema {
+            name: name.to_string(),
+            value_type,
+            required: true,
+        });
+        self
+    }
+
+    /// Builder: add a field that may be left out.
+    pub fn with_optional_field(mut self, name: &str, value_type: ValueType) -> Self {
+        self.fields.push(EventFieldSchema {
+            name: name.to_string(),
+            value_type,
+            required: false,
+        });
+        self
+    }
+}
+
+/// How scheduled events are checked against their schemas.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ValidationMode {
+    /// No checks (release default).
+    Off,
+    /// Record violations for later inspection (debug default).
+    Record,
+    /// Panic on the first violation.
+    Strict,
+}
+
+impl Default for ValidationMode {
+    fn default() -> Self {
+        if cfg!(debug_assertions) {
+            ValidationMode::Record
+        } else {
+            ValidationMode::Off
+        }
+    }
+}
+
+/// An event that does not match its schema.
+#[derive(Clone, Debug, PartialEq)]
+pub enum EventSchemaError {
+    /// A required field is missing.
+    MissingField { event: String, field: String },
+    /// The event carries a field its schema does not declare. The field is
+    /// not known to the registry, so only its interned ID is kept; use
+    /// `describe` to resolve it.
+    UnknownField { event: String, field: InternedString },
+    /// A field holds a value of the wrong type.
+    WrongType {
+        event: String,
+        field: String,
+        expected: ValueType,
+        found: &'static str,
+    },
+}
+
+impl EventSchemaError {
+    /// Human-readable message, resolving interned names.
+    pub fn describe(&self, interner: &StringInterner) -> String {
+        match self {
+            EventSchemaError::UnknownField { event, field } => {
+                format!("event '{}' has no field '{}'", event, interner.resolve(*field))
+            }
+            _ => self.to_string(),
+        }
+    }
+}
+
+impl std::fmt::Display for EventSchemaError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EventSchemaError::MissingField { event, field } => {
+                write!(f, "event '{}' is missing required field '{}'", event, field)
+            }
+            EventSchemaError::UnknownField { event, field } => {
+                write!(f, "event '{}' has no field #{}", event, field.0)
+            }
+            EventSchemaError::WrongType { event, field, expected, found } => write!(
+                f,
+                "event '{}' field '{}' expects {:?}, found {}",
+                event, field, expected, found
+            ),
+        }
+    }
+}
+
+/// A schema with its names interned for lookup.
+struct RegisteredSchema {
+    schema: EventSchema,
+    field_ids: Vec<InternedString>,
+}
+
+/// Event schemas keyed by event type.
+#[derive(Default)]
+pub struct EventSchemaRegistry {
+    schemas: HashMap<InternedString, RegisteredSchema>,
+    mode: ValidationMode,
+    violations: Vec<EventSchemaError>,
+}
+
+impl EventSchemaRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register (or replace) the schema of an event type.
+    pub fn register(&mut self, schema: EventSchema, interner: &mut StringInterner) {
+        let event_type = interner.intern(&schema.name);
+        let field_ids = schema.fields.iter().map(|f| interner.intern(&f.name)).collect();
+        self.schemas.insert(event_type, RegisteredSchema { schema, field_ids });
+    }
+
+    /// Register every schema of a JSON array such as
+    /// `[{"name": "UseItem", "fields": [{"name": "item", "type": "string"}]}]`.
+    /// Returns the number of schemas registered.
+    pub fn register_json(&mut self, json: &str, interner: &mut StringInterner) -> Result<usize, String> {
+        let schemas: Vec<EventSchema> = serde_json::from_str(json).map_err(|e| e.to_string())?;
+        let count = schemas.len();
+        for schema in schemas {
+            self.register(schema, interner);
+        }
+        Ok(count)
+    }
+
+    /// The schema of an event type, if registered.
+    pub fn get(&self, event_type: InternedString) -> Option<&EventSchema> {
+        self.schemas.get(&event_type).map(|r| &r.schema)
+    }
+
+    pub fn len(&self) -> usize {
+        self.schemas.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.schemas.is_empty()
+    }
+
+    pub fn mode(&self) -> ValidationMode {
+        self.mode
+    }
+
+    pub fn set_mode(&mut self, mode: ValidationMode) {
+        self.mode = mode;
+    }
+
+    /// Check an event against its schema. Events without a schema pass.
+    pub fn validate(&self, event: &Event) -> Result<(), EventSchemaError> {
+        let Some(registered) = self.schemas.get(&event.event_type) else {
+            return Ok(());
+        };
+        let schema = &registered.schema;
+        for (field, id) in schema.fields.iter().zip(&registered.field_ids) {
+            match event.fields.get(id) {
+                None | Some(Value::None) if field.required => {
+                    return Err(EventSchemaError::MissingField {
+                        event: schema.name.clone(),
+                        field: field.name.clone(),
+                    });
+                }
+                Some(value) if !value.is_none() && !matches_type(value, field.value_type) => {
+                    return Err(EventSchemaError::WrongType {
+                        event: schema.name.clone(),
+                        field: field.name.clone(),
+                        expected: field.value_type,
+                        found: value.type_name(),
+                    });
+                }
+                _ => {}
+            }
+        }
+        // Sorted so the reported field does not depend on HashMap order.
+        let mut unknown: Vec<InternedString> = event
+            .fields
+            .keys()
+            .filter(|k| !registered.field_ids.contains(k))
+            .copied()
+            .collect();
+        unknown.sort();
+        match unknown.first() {
+            Some(&field) => Err(EventSchemaError::UnknownField {
+                event: schema.name.clone(),
+                field,
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Validate according to the current mode. Called by the timeline for
+    /// every scheduled event.
+    pub fn check(&mut self, event: &Event) {
+        if self.mode == ValidationMode::Off || self.schemas.is_empty() {
+            return;
+        }
+        if let Err(error) = self.validate(event) {
+            if self.mode == ValidationMode::Strict {
+                panic!("event schema violation: {}", error);
+            }
+            self.violations.push(error);
+        }
+    }
+
+    /// Violations recorded in `Record` mode, oldest first.
+    pub fn violations(&self) -> &[EventSchemaError] {
+        &self.violations
+    }
+
+    /// Take and clear the recorded violations.
+    pub fn take_violations(&mut self) -> Vec<EventSchemaError> {
+        std::mem::take(&mut self.violations)
+    }
+
+    /// Forget recorded violations. Schemas stay registered.
+    pub fn clear_violations(&mut self) {
+        self.violations.clear();
+    }
+
+    /// Export all schemas as a JSON array, sorted by event name.
+    pub fn to_json(&self) -> String {
+        let mut schemas: Vec<&EventSchema> = self.schemas.values().map(|r| &r.schema).collect();
+        schemas.sort_by(|a, b| a.name.cmp(&b.name));
+        serde_json::to_string(&schemas).unwrap_or_else(|_| "[]".to_string())
+    }
+}
+
+/// Whether a value fits a declared field type. Integers are accepted for
+/// number and entity fields, since JSON from the host does not distinguish them.
+fn matches_type(value: &Value, value_type: ValueType) -> bool {
+    match value_type {
+        ValueType::Integer => matches!(value, Value::Integer(_)),
+        ValueType::Number => matches!(value, Value::Number(_) | Value::Integer(_)),
+        ValueType::String => matches!(value, Value::String(_)),
+        ValueType::Boolean => matches!(value, Value::Boolean(_)),
+        ValueType::Entity => matches!(value, Value::Entity(_) | Value::Integer(0..)),
+        ValueType::List => matches!(value, Value::List(_)),
+        ValueType::Map => matches!(value, Value::Map(_)),
+        ValueType::Any => true,
+    }
+}
+
+impl MarkStrings for EventSchemaRegistry {
+    fn mark_strings(&self, live: &mut LiveStrings) {
+        for (event_type, registered) in &self.schemas {
+            event_type.mark_strings(live);
+            registered.field_ids.mark_strings(live);
+        }
+        for violation in &self.violations {
+            if let EventSchemaError::UnknownField { field, .. } = violation {
+                field.mark_strings(live);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn registry(interner: &mut StringInterner) -> EventSchemaRegistry {
+        let mut registry = EventSchemaRegistry::new();
+        registry
+            .register_json(
+                r#"[{"name": "UseItem", "fields": [
+                    {"name": "item", "type": "string"},
+                    {"name": "count", "type": "integer", "required": false}
+                ]}]"#,
+                interner,
+            )
+            .unwrap();
+        registry
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut interner = StringInterner::new();
+        let registry = registry(&mut interner);
+        let use_item = interner.intern("UseItem");
+        let item = interner.intern("item");
+        let count = interner.intern("count");
+        let itme = interner.intern("itme");
+        let potion = Value::String(interner.intern("Potion"));
+
+        let ok = Event::new(use_item).with_field(item, potion.clone());
+        assert_eq!(registry.validate(&ok), Ok(()));
+
+        let missing = Event::new(use_item).with_field(count, Value::Integer(1));
+        assert_eq!(
+            registry.validate(&missing),
+            Err(EventSchemaError::MissingField { event: "UseItem".into(), field: "item".into() })
+        );
+
+        let wrong = Event::new(use_item).with_field(item, potion.clone()).with_field(count, Value::Number(1.5));
+        assert!(matches!(registry.validate(&wrong), Err(EventSchemaError::WrongType { found: "number", .. })));
+
+        let typo = Event::new(use_item).with_field(item, potion).with_field(itme, Value::Integer(1));
+        let error = registry.validate(&typo).unwrap_err();
+        assert_eq!(error.describe(&interner), "event 'UseItem' has no field 'itme'");
+
+        let unregistered = Event::new(interner.intern("Other")).with_field(itme, Value::None);
+        assert_eq!(registry.validate(&unregistered), Ok(()));
+    }
+
+    #[test]
+    fn test_modes_and_export() {
+        let mut interner = StringInterner::new();
+        let mut registry = registry(&mut interner);
+        let bad = Event::new(interner.intern("UseItem"));
+
+        registry.set_mode(ValidationMode::Off);
+        registry.check(&bad);
+        assert!(registry.violations().is_empty());
+
+        registry.set_mode(ValidationMode::Record);
+        registry.check(&bad);
+        assert_eq!(registry.take_violations().len(), 1);
+        assert!(registry.violations().is_empty());
+
+        let exported = registry.to_json();
+        let mut other = EventSchemaRegistry::new();
+        assert_eq!(other.register_json(&exported, &mut interner), Ok(1));
+        assert_eq!(other.to_json(), exported);
+        assert!(exported.contains(r#"{"name":"count","type":"integer","required":false}"#));
+    }
+
+    #[test]
+    #[should_panic(expected = "event schema violation")]
+    fn test_strict_mode_panics() {
+        let mut interner = StringInterner::new();
+        let mut registry = registry(&mut interner);
+        registry.set_mode(ValidationMode::Strict);
+        registry.check(&Event::new(interner.intern("UseItem")));
+    }
+}
@@ -0,0 +1,215 @@
+//! Template string formatting.
+//!
+//! Combat and story text used to be built with chains of `concat` and
+//! `str_replace`, interning every intermediate string. `format_template`
+//! builds the whole text in one pass and leaves interning the result to the
+//! caller.
+//!
+//! Placeholders:
+//!
+//! - `{hero}` — a key of a map argument
+//! - `{0}`, `{1}` — an element of a list argument; `{}` takes the next one
+//! - `{{` and `}}` — literal braces
+//!
+//! A spec after a colon formats numbers: `{dmg:.1}` rounds to one decimal,
+//! `{gold:,}` adds thousands separators and `{crit:%}` multiplies by 100 and
+//! appends `%`. Specs combine in that order, e.g. `{gold:,.2}` or `{crit:.1%}`.
+//! Strings format as their text and entities as their name. A placeholder
+//! that cannot be resolved is copied to the output as written, so a typo
+//! shows up in the text instead of silently disappearing.
+
+use crate::interning::StringInterner;
+use crate::value::{EntityId, Value};
+
+/// Number formatting options of a placeholder.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FormatSpec {
+    pub thousands: bool,
+    pub decimals: Option<usize>,
+    pub percent: bool,
+}
+
+impl FormatSpec {
+    /// Parse `[,][.N][%]`. Returns None for anything else.
+    pub fn parse(spec: &str) -> Option<FormatSpec> {
+        let mut result = FormatSpec::default();
+        let mut rest = spec;
+        if let Some(r) = rest.strip_prefix(',') {
+            result.thousands = true;
+            rest = r;
+        }
+        if let Some(r) = rest.strip_suffix('%') {
+            result.percent = true;
+            rest = r;
+        }
+        if let Some(digits) = rest.strip_prefix('.') {
+            result.decimals = Some(digits.parse().ok().filter(|d| *d <= 20)?);
+            rest = "";
+        }
+        rest.is_empty().then_some(result)
+    }
+}
+
+/// Fill in the placeholders of `template` from `args`, a `Value::Map` for
+/// named placeholders or a `Value::List` for positional ones. Entities are
+/// formatted with `entity_name`, or as `#id` if it returns None.
+pub fn format_template(
+    template: &str,
+    args: &Value,
+    interner: &StringInterner,
+    entity_name: impl Fn(EntityId) -> Option<String>,
+) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut next_index = 0;
+    let mut rest = template;
+    while let Some(i) = rest.find(['{', '}']) {
+        out.push_str(&rest[..i]);
+        let brace = rest.as_bytes()[i];
+        rest = &rest[i + 1..];
+        // `{{`, `}}` and a stray `}` are literal.
+        if brace == b'}' || rest.starts_with('{') {
+            out.push(brace as char);
+            if rest.starts_with(brace as char) {
+                rest = &rest[1..];
+            }
+            continue;
+        }
+        let Some(end) = rest.find('}') else {
+            out.push('{');
+            break;
+        };
+        let placeholder = &rest[..end];
+        rest = &rest[end + 1..];
+        let (key, spec) = placeholder.split_once(':').unwrap_or((placeholder, ""));
+        let value = if key.is_empty() {
+            next_index += 1;
+            args.as_list().and_then(|items| items.get(next_index - 1))
+        } else if let Ok(index) = key.parse::<usize>() {
+            args.as_list().and_then(|items| items.get(index))
+        } else {
+            interner.lookup(key).and_then(|id| args.get(id))
+        };
+        let text = value
+            .zip(FormatSpec::parse(spec))
+            .and_then(|(value, spec)| format_value(value, spec, interner, &entity_name));
+        match text {
+            Some(text) => out.push_str(&text),
+            None => {
+                out.push('{');
+                out.push_str(placeholder);
+                out.push('}');
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Text of one value. Maps have no text form.
+pub fn format_value(
+    value: &Value,
+    spec: FormatSpec,
+    interner: &StringInterner,
+    entity_name: &impl Fn(EntityId) -> Option<String>,
+) -> Option<String> {
+    let number = |n: f64| {
+        let n = if spec.percent { n * 100.0 } else { n };
+        let mut text = match spec.decimals {
+            Some(decimals) => format!("{:.*}", decimals, n),
+            None if n.fract() == 0.0 && n.abs() < i64::MAX as f64 => (n as i64).to_string(),
+            None => n.to_string(),
+        };
+        if spec.thousands {
+            text = group_thousands(&text);
+        }
+        if spec.percent {
+            text.push('%');
+        }
+        text
+    };
+    Some(match value {
+        Value::None => String::new(),
+        Value::Boolean(b) => b.to_string(),
+        Value::Integer(n) if spec == FormatSpec::default() => n.to_string(),
+        Value::Integer(n) if !spec.percent && spec.decimals.is_none() => group_thousands(&n.to_string()),
+        Value::Integer(n) => number(*n as f64),
+        Value::Number(n) => number(*n),
+        Value::String(s) => interner.resolve(*s).to_string(),
+        Value::Entity(e) => entity_name(*e).unwrap_or_else(|| format!("#{}", e)),
+        Value::List(items) => items
+            .iter()
+            .map(|item| format_value(item, spec, interner, entity_name))
+            .collect::<Option<Vec<_>>>()?
+            .join(", "),
+        Value::Map(_) => return None,
+    })
+}
+
+/// Insert `,` between groups of three digits of the integer part.
+fn group_thousands(number: &str) -> String {
+    let (sign, unsigned) = number.strip_prefix('-').map_or(("", number), |n| ("-", n));
+    let int_len = unsigned.find(|c: char| !c.is_ascii_digit()).unwrap_or(unsigned.len());
+    let (int_part, tail) = unsigned.split_at(int_len);
+    let mut out = String::from(sign);
+    for (i, digit) in int_part.chars().enumerate() {
+        if i > 0 && (int_len - i) % 3 == 0 {
+            out.push(',');
+        }
+        out.push(digit);
+    }
+    out.push_str(tail);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    fn named(interner: &mut StringInterner, pairs: &[(&str, Value)]) -> Value {
+        let map: BTreeMap<_, _> = pairs.iter().map(|(k, v)| (interner.intern(k), v.clone())).collect();
+        Value::Map(map)
+    }
+
+    fn no_names(_: EntityId) -> Option<String> {
+        None
+    }
+
+    #[test]
+    fn test_named_and_positional() {
+        let mut interner = StringInterner::new();
+        let goblin = Value::String(interner.intern("Goblin"));
+        let args = named(&mut interner, &[("enemy", goblin.clone()), ("dmg", Value::Integer(7)), ("hero", Value::Entity(3))]);
+        let names = |e: EntityId| (e == 3).then(|| "Aria".to_string());
+        assert_eq!(format_template("{hero} hits {enemy} for {dmg}", &args, &interner, names), "Aria hits Goblin for 7");
+
+        let list = Value::List(vec![goblin, Value::Entity(9), Value::Number(2.5)]);
+        assert_eq!(format_template("{} and {} ({2}, {0})", &list, &interner, no_names), "Goblin and #9 (2.5, Goblin)");
+    }
+
+    #[test]
+    fn test_number_specs() {
+        let interner = StringInterner::new();
+        let args = Value::List(vec![Value::Number(1234567.891), Value::Integer(-1234), Value::Number(0.125)]);
+        let format = |template: &str| format_template(template, &args, &interner, no_names);
+        assert_eq!(format("{0:.2}"), "1234567.89");
+        assert_eq!(format("{0:,.1}"), "1,234,567.9");
+        assert_eq!(format("{0:,}"), "1,234,567.891");
+        assert_eq!(format("{1:,}"), "-1,234");
+        assert_eq!(format("{1:.1}"), "-1234.0");
+        assert_eq!(format("{2:%}"), "12.5%");
+        assert_eq!(format("{2:.0%}"), "12%");
+        assert_eq!(FormatSpec::parse("x"), None);
+    }
+
+    #[test]
+    fn test_escapes_and_unresolved() {
+        let interner = StringInterner::new();
+        let args = Value::List(vec![Value::Integer(1)]);
+        let format = |template: &str| format_template(template, &args, &interner, no_names);
+        assert_eq!(format("{{{0}}}"), "{1}");
+        assert_eq!(format("{missing} {5} {0:bad}"), "{missing} {5} {0:bad}");
+        assert_eq!(format("open {0"), "open {0");
+        assert_eq!(format("a } b"), "a } b");
+    }
+}
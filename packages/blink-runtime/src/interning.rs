@@ -1,32 +1,101 @@
-/// String interning for efficient string comparisons.
-/// At compile time, all string literals in BRL are assigned integer IDs.
-/// At runtime, comparisons use integer equality instead of string comparison.
+//! String interning for efficient string comparisons.
+//! At compile time, all string literals in BRL are assigned integer IDs.
+//! At runtime, comparisons use integer equality instead of string comparison.
+//!
+//! Strings interned before `mark_static` (the compiled string table) live for
+//! the whole run. Strings interned afterwards — concatenations, numbers turned
+//! to text, formatted messages — are dynamic: at a safe point between events
+//! the engine marks every string still reachable from components, pending
+//! events and choices, and `collect` frees the rest. `pin` keeps a single
+//! dynamic string, such as an event name a dispatcher is keyed by, alive.
+//!
+//! Freed slots are reused, but an ID carries its slot's generation, so a freed
+//! ID resolves to "" rather than to the newer string in its slot. An
+//! `InternedString` kept anywhere the engine cannot see still goes stale
+//! across a collection, but reads as empty instead of as someone else's text.
+//! The table holds at most 2^24 slots. A slot is retired once 256 strings
+//! have used it, so churn grows the table by one slot per 256 strings freed
+//! rather than one per string.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 /// An interned string represented as a u32 index.
-#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default)]
 pub struct InternedString(pub u32);
 
 impl InternedString {
     pub const NONE: InternedString = InternedString(0);
 }
 
+/// Strings found reachable while marking, kept by `StringInterner::collect`.
+pub type LiveStrings = HashSet<InternedString>;
+
+/// Implemented by anything that holds interned strings the collector must
+/// keep. Generated code implements it for components with string fields.
+pub trait MarkStrings {
+    fn mark_strings(&self, live: &mut LiveStrings);
+}
+
+impl MarkStrings for InternedString {
+    fn mark_strings(&self, live: &mut LiveStrings) {
+        live.insert(*self);
+    }
+}
+
+impl<T: MarkStrings> MarkStrings for Vec<T> {
+    fn mark_strings(&self, live: &mut LiveStrings) {
+        for item in self {
+            item.mark_strings(live);
+        }
+    }
+}
+
+impl<T: MarkStrings> MarkStrings for Option<T> {
+    fn mark_strings(&self, live: &mut LiveStrings) {
+        if let Some(item) = self {
+            item.mark_strings(live);
+        }
+    }
+}
+
+/// Bits of an ID that index the table; the bits above count how often the
+/// slot has been reused (its generation).
+const SLOT_BITS: u32 = 24;
+const SLOT_MASK: u32 = (1 << SLOT_BITS) - 1;
+/// Generations a slot goes through before it is retired for good.
+const MAX_GENERATION: u32 = u32::MAX >> SLOT_BITS;
+
 /// Global string intern table.
 /// Maps string values to integer IDs and back.
 pub struct StringInterner {
     to_id: HashMap<String, u32>,
-    to_string: Vec<String>,
+    /// Text and generation of each slot; a freed slot holds "".
+    slots: Vec<(String, u32)>,
+    /// Slots below this are static and never collected.
+    static_len: u32,
+    /// Dynamic slots that are never collected.
+    pinned: HashSet<u32>,
+    /// Freed dynamic slots, highest first so the lowest is reused first.
+    free: Vec<u32>,
+    /// Number of dynamic strings currently interned.
+    dynamic: usize,
+    /// Number of freed slots that are never reused.
+    retired: usize,
 }
 
 impl StringInterner {
     pub fn new() -> Self {
         let mut interner = StringInterner {
             to_id: HashMap::new(),
-            to_string: Vec::new(),
+            slots: Vec::new(),
+            static_len: 1,
+            pinned: HashSet::new(),
+            free: Vec::new(),
+            dynamic: 0,
+            retired: 0,
         };
         // Reserve index 0 for the empty/none string
-        interner.to_string.push(String::new());
+        interner.slots.push((String::new(), 0));
         interner.to_id.insert(String::new(), 0);
         interner
     }
@@ -36,18 +105,110 @@ impl StringInterner {
         if let Some(&id) = self.to_id.get(s) {
             return InternedString(id);
         }
-        let id = self.to_string.len() as u32;
-        self.to_string.push(s.to_string());
+        let slot = match self.free.pop() {
+            Some(slot) => {
+                self.slots[slot as usize].0 = s.to_string();
+                slot
+            }
+            None => {
+                let slot = self.slots.len() as u32;
+                assert!(slot <= SLOT_MASK, "string table full ({} slots)", SLOT_MASK as u64 + 1);
+                self.slots.push((s.to_string(), 0));
+                slot
+            }
+        };
+        if slot >= self.static_len {
+            self.dynamic += 1;
+        }
+        let id = slot | self.slots[slot as usize].1 << SLOT_BITS;
         self.to_id.insert(s.to_string(), id);
         InternedString(id)
     }
 
-    /// Resolve an interned string back to its string value.
+    /// ID of an already interned string, without interning it.
+    pub fn lookup(&self, s: &str) -> Option<InternedString> {
+        self.to_id.get(s).map(|&id| InternedString(id))
+    }
+
+    /// Resolve an interned string back to its string value. A freed ID
+    /// resolves to "", even once its slot holds a newer string.
     pub fn resolve(&self, id: InternedString) -> &str {
-        self.to_string
-            .get(id.0 as usize)
-            .map(|s| s.as_str())
-            .unwrap_or("")
+        match self.slots.get((id.0 & SLOT_MASK) as usize) {
+            Some((text, generation)) if *generation == id.0 >> SLOT_BITS => text,
+            _ => "",
+        }
+    }
+
+    /// Make every string interned so far static. Called once the compiled
+    /// string table and schemas are loaded, and again on re-initialisation.
+    /// Slots freed before the call are retired.
+    pub fn mark_static(&mut self) {
+        self.static_len = self.slots.len() as u32;
+        self.retired += self.free.len();
+        self.free.clear();
+        self.dynamic = 0;
+    }
+
+    /// Whether a string is static (never collected).
+    pub fn is_static(&self, id: InternedString) -> bool {
+        id.0 & SLOT_MASK < self.static_len
+    }
+
+    /// Keep a dynamic string across collections without making everything
+    /// interned so far static.
+    pub fn pin(&mut self, id: InternedString) {
+        if !self.is_static(id) {
+            self.pinned.insert(id.0 & SLOT_MASK);
+        }
+    }
+
+    /// Number of strings currently interned.
+    pub fn len(&self) -> usize {
+        self.slots.len() - self.free.len() - self.retired
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Number of dynamic strings currently interned.
+    pub fn dynamic_len(&self) -> usize {
+        self.dynamic
+    }
+
+    /// Number of slots in the table, including free and retired ones.
+    pub fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Free every unpinned dynamic string not in `live`. Returns the number
+    /// freed. Only call this at a safe point with `live` marked from
+    /// everything that can hold a string.
+    pub fn collect(&mut self, live: &LiveStrings) -> usize {
+        let mut freed = 0;
+        for slot in self.static_len..self.slots.len() as u32 {
+            let (text, generation) = &mut self.slots[slot as usize];
+            // Dynamic slots are never "" (that is ID 0), so "" marks a freed slot.
+            if text.is_empty()
+                || self.pinned.contains(&slot)
+                || live.contains(&InternedString(slot | *generation << SLOT_BITS))
+            {
+                continue;
+            }
+            self.to_id.remove(std::mem::take(text).as_str());
+            // The next string in this slot gets a new ID, so stale copies of
+            // the old one resolve to "" instead of to it.
+            if *generation < MAX_GENERATION {
+                *generation += 1;
+                self.free.push(slot);
+            } else {
+                self.retired += 1;
+            }
+            freed += 1;
+        }
+        self.dynamic -= freed;
+        self.free.sort_unstable_by(|a, b| b.cmp(a));
+        freed
     }
 }
 
@@ -72,6 +233,87 @@ mod tests {
         assert_ne!(id1, id2); // different strings = different IDs
         assert_eq!(interner.resolve(id1), "hello");
         assert_eq!(interner.resolve(id2), "world");
+        assert_eq!(interner.lookup("world"), Some(id2));
+        assert_eq!(interner.lookup("missing"), None);
+    }
+
+    #[test]
+    fn test_collect_dynamic_strings() {
+        let mut interner = StringInterner::new();
+        let fixed = interner.intern("Damage");
+        interner.mark_static();
+        let kept = interner.intern("12");
+        let dropped = interner.intern("13");
+        let also_dropped = interner.intern("14");
+        assert!(interner.is_static(fixed) && !interner.is_static(kept));
+        assert_eq!(interner.dynamic_len(), 3);
+
+        let live: LiveStrings = [kept].into_iter().collect();
+        assert_eq!(interner.collect(&live), 2);
+        assert_eq!(interner.collect(&live), 0);
+        assert_eq!(interner.resolve(fixed), "Damage");
+        assert_eq!(interner.resolve(kept), "12");
+        assert_eq!(interner.lookup("13"), None);
+        assert_eq!(interner.len(), 3);
+
+        // Freed slots are reused lowest first under a new ID; stale IDs read
+        // as "" rather than as the new text. Live IDs keep their text.
+        let fresh = interner.intern("15");
+        assert_ne!(fresh, dropped);
+        assert_eq!(interner.resolve(fresh), "15");
+        assert_eq!(interner.resolve(dropped), "");
+        assert_ne!(interner.intern("13"), dropped);
+        assert_eq!(interner.resolve(also_dropped), "");
+        assert_eq!(interner.intern("12"), kept);
+        assert_eq!(interner.intern("Damage"), fixed);
+        assert_eq!(interner.len(), 5);
+        assert_eq!(interner.capacity(), 5);
+
+        // Pinned strings survive without being referenced.
+        let pinned = interner.intern("Attack");
+        interner.pin(pinned);
+        assert_eq!(interner.collect(&LiveStrings::new()), 3);
+        assert_eq!(interner.resolve(pinned), "Attack");
+        assert_eq!(interner.lookup("Attack"), Some(pinned));
+        assert_eq!(interner.dynamic_len(), 1);
+    }
+
+    #[test]
+    fn test_mark_static_after_collect() {
+        let mut interner = StringInterner::new();
+        interner.intern("Damage");
+        interner.mark_static();
+        interner.intern("12");
+        interner.intern("13");
+        assert_eq!(interner.collect(&LiveStrings::new()), 2);
+
+        // Re-initialising marks static again over the freed slots.
+        interner.intern("Heal");
+        interner.mark_static();
+        assert_eq!(interner.dynamic_len(), 0);
+        assert_eq!(interner.len(), 3);
+        let dynamic = interner.intern("14");
+        assert!(!interner.is_static(dynamic));
+        assert_eq!(interner.dynamic_len(), 1);
+        assert_eq!(interner.collect(&LiveStrings::new()), 1);
+        assert_eq!(interner.dynamic_len(), 0);
+        assert_eq!(interner.resolve(interner.lookup("Heal").unwrap()), "Heal");
+    }
+
+    #[test]
+    fn test_churn_reuses_slots() {
+        let mut interner = StringInterner::new();
+        interner.mark_static();
+        let first = interner.intern("0");
+        for n in 1..1000 {
+            assert_eq!(interner.collect(&LiveStrings::new()), 1);
+            let id = interner.intern(&n.to_string());
+            assert_eq!(interner.resolve(first), "");
+            assert_eq!(interner.resolve(id), n.to_string());
+        }
+        // One slot per 256 generations, not one per string.
+        assert_eq!(interner.capacity(), 1 + 4);
+        assert_eq!(interner.len(), 2);
     }
 
     #[test]
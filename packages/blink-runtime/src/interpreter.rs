@@ -0,0 +1,999 @@
+//! Dynamic IR interpreter: runs BRL rules straight from the compiler's IR.
+//!
+//! The compiled path (BRL → Rust → cargo build) stays the production backend.
+//! The interpreter exists for fast iteration: designers can tweak a rule,
+//! recompile to IR JSON and rerun immediately. It drives the same `Engine`
+//! (timeline, RNG, interner) and calls the same `brl_*` builtins as generated
+//! code, in the same order, so a run with a given seed matches the compiled
+//! build of the same rules.
+//!
+//! Components live in the world's schema-defined storages, one per IR
+//! component declaration, so snapshots and the JSON bridge see them like any
+//! other component.
+
+use std::collections::HashMap;
+
+use crate::builtins::*;
+use crate::dynamic::ComponentSchema;
+use crate::event::Event;
+use crate::interning::InternedString;
+use crate::ir::*;
+use crate::value::{EntityId, Value, NO_ENTITY};
+use crate::Engine;
+
+/// The loaded module plus lookup tables built once at load time.
+struct Program {
+    module: IrModule,
+    function_index: HashMap<String, usize>,
+    rules_by_event: HashMap<InternedString, Vec<usize>>,
+}
+
+/// Interpreter for a compiled BRL module.
+pub struct Interpreter {
+    program: Program,
+    named: HashMap<String, EntityId>,
+}
+
+impl Interpreter {
+    /// Load a module from the compiler's IR JSON.
+    pub fn from_json(ir_json: &str) -> Result<Self, String> {
+        Ok(Self::new(IrModule::from_json(ir_json)?))
+    }
+
+    pub fn new(module: IrModule) -> Self {
+        let function_index = module
+            .functions
+            .iter()
+            .enumerate()
+            .map(|(i, f)| (f.name.clone(), i))
+            .collect();
+        Interpreter {
+            program: Program {
+                module,
+                function_index,
+                rules_by_event: HashMap::new(),
+            },
+            named: HashMap::new(),
+        }
+    }
+
+    /// Bind the module to an engine: register component schemas, intern and
+    /// pin event names, and create the initial entities. Call once before
+    /// stepping, like `init_game`. Other strings interned so far stay
+    /// dynamic; the host decides whether to call `mark_static`.
+    pub fn init(&mut self, engine: &mut Engine) {
+        for component in &self.program.module.components {
+            let mut schema = ComponentSchema::new(&component.name);
+            for field in &component.fields {
+                let value_type = field.field_type.value_type();
+                let default = match &field.default {
+                    Some(json) => Value::from_json_value(json, &mut engine.interner),
+                    None => value_type.default_value(),
+                };
+                schema = schema.with_field_default(&field.name, value_type, default);
+            }
+            engine.world.register_dynamic_component(schema);
+        }
+
+        self.program.rules_by_event.clear();
+        for (i, rule) in self.program.module.rules.iter().enumerate() {
+            if let Some(event) = &rule.trigger.event {
+                let event_type = engine.interner.intern(event);
+                // Dispatch is keyed by this ID, so it must survive collection.
+                engine.interner.pin(event_type);
+                self.program
+                    .rules_by_event
+                    .entry(event_type)
+                    .or_default()
+                    .push(i);
+            }
+        }
+
+        // Named entities: the last definition of a variable wins, but it is
+        // created at its original position (same order as generated code).
+        let Some(state) = &self.program.module.initial_state else {
+            return;
+        };
+        let mut last_definition: HashMap<&str, usize> = HashMap::new();
+        for (i, def) in state.entities.iter().enumerate() {
+            if let Some(var) = &def.variable {
+                last_definition.insert(var.as_str(), i);
+            }
+        }
+        for (i, def) in state.entities.iter().enumerate() {
+            let id = match &def.variable {
+                Some(var) if last_definition[var.as_str()] != i => continue,
+                Some(var) => {
+                    let id = engine.world.spawn_named(var);
+                    self.named.insert(var.clone(), id);
+                    id
+                }
+                None => engine.world.spawn(),
+            };
+            for (name, fields) in def.components.iter() {
+                let fields = fields
+                    .iter()
+                    .map(|(field, json)| (field, Value::from_json_value(json, &mut engine.interner)))
+                    .collect();
+                if let Some(storage) = engine.world.dynamic_storage_mut(name) {
+                    storage.insert_fields(id, fields);
+                }
+            }
+        }
+    }
+
+    /// Process one event. Returns false if the timeline is empty or the
+    /// engine is suspended on a choice.
+    pub fn step(&mut self, engine: &mut Engine) -> bool {
+        if let Some(event) = engine.next_event() {
+            self.dispatch_event(&event, engine);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Process up to `max_steps` events. Returns the number processed.
+    pub fn run_steps(&mut self, engine: &mut Engine, max_steps: u32) -> u32 {
+        let mut count = 0;
+        for _ in 0..max_steps {
+            if !self.step(engine) {
+                break;
+            }
+            count += 1;
+        }
+        count
+    }
+
+    /// Run every rule triggered by `event`, in declaration order.
+    pub fn dispatch_event(&mut self, event: &Event, engine: &mut Engine) {
+        let Some(rules) = self.program.rules_by_event.get(&event.event_type) else {
+            return;
+        };
+        for &rule_index in rules {
+            let rule = &self.program.module.rules[rule_index];
+            let mut exec = Exec {
+                program: &self.program,
+                named: &self.named,
+                engine,
+                event,
+                locals: HashMap::new(),
+                returned: false,
+            };
+            exec.run_rule(rule);
+        }
+    }
+
+    /// ID of a named entity from the initial state.
+    pub fn named_entity(&self, variable: &str) -> Option<EntityId> {
+        self.named.get(variable).copied()
+    }
+
+    /// Attach a component, filling unspecified fields with their defaults.
+    /// Returns false if the component is not declared in the module.
+    pub fn insert_component(
+        &mut self,
+        engine: &mut Engine,
+        entity: EntityId,
+        component: &str,
+        fields: Vec<(&str, Value)>,
+    ) -> bool {
+        match engine.world.dynamic_storage_mut(component) {
+            Some(storage) => {
+                storage.insert_fields(entity, fields);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Read a component field.
+    pub fn get_field<'e>(&self, engine: &'e Engine, entity: EntityId, component: &str, field: &str) -> Option<&'e Value> {
+        engine.world.dynamic_storage(component)?.get_field(entity, field)
+    }
+}
+
+/// Execution state for one rule invocation.
+struct Exec<'a> {
+    program: &'a Program,
+    named: &'a HashMap<String, EntityId>,
+    engine: &'a mut Engine,
+    event: &'a Event,
+    locals: HashMap<String, Value>,
+    /// Set by a `return` action; stops the rule's remaining actions.
+    returned: bool,
+}
+
+impl<'a> Exec<'a> {
+    fn run_rule(&mut self, rule: &'a IrRule) {
+        if let Some(condition) = &rule.condition
+            && !self.eval(condition).as_boolean()
+        {
+            return;
+        }
+        match &rule.query {
+            Some(query) => {
+                for id in self.engine.world.query_dynamic(&query.component) {
+                    self.locals.insert(query.variable.clone(), Value::Entity(id));
+                    self.run_actions(&rule.actions);
+                    if self.returned {
+                        break;
+                    }
+                }
+            }
+            None => self.run_actions(&rule.actions),
+        }
+    }
+
+    fn run_actions(&mut self, actions: &'a [IrAction]) {
+        for action in actions {
+            if self.returned {
+                break;
+            }
+            self.run_action(action);
+        }
+    }
+
+    fn run_action(&mut self, action: &'a IrAction) {
+        match action {
+            IrAction::Modify { entity, component, field, op, value } => {
+                let id = self.eval(entity).as_entity();
+                let rhs = self.eval(value);
+                // Missing components swallow writes, like `World::get_mut`.
+                let Some(current) = self
+                    .engine
+                    .world
+                    .dynamic_storage(component)
+                    .and_then(|s| s.get_field(id, field))
+                    .cloned()
+                else {
+                    return;
+                };
+                let updated = match op {
+                    ModifyOp::Set => rhs,
+                    ModifyOp::Add => binary(BinaryOp::Add, current, rhs, self.engine),
+                    ModifyOp::Subtract => binary(BinaryOp::Subtract, current, rhs, self.engine),
+                    ModifyOp::Multiply => binary(BinaryOp::Multiply, current, rhs, self.engine),
+                    ModifyOp::Divide => binary(BinaryOp::Divide, current, rhs, self.engine),
+                };
+                if let Some(storage) = self.engine.world.dynamic_storage_mut(component) {
+                    storage.set_field(id, field, updated);
+                }
+            }
+            IrAction::Schedule { event, source, target, delay, fields } => {
+                let mut scheduled = self.build_event(event, fields.as_ref());
+                if let Some(source) = source {
+                    scheduled.source = self.eval(source).as_entity();
+                }
+                if let Some(target) = target {
+                    scheduled.target = self.eval(target).as_entity();
+                }
+                match delay {
+                    Some(delay) => {
+                        let delay = self.eval(delay).as_number();
+                        self.engine.timeline.schedule_delay(delay, scheduled);
+                    }
+                    None => {
+                        self.engine.timeline.schedule_immediate(scheduled);
+                    }
+                }
+            }
+            IrAction::Emit { event, fields } => {
+                let emitted = self.build_event(event, fields.as_ref());
+                self.engine.timeline.schedule_immediate(emitted);
+            }
+            IrAction::Spawn { components } => {
+                let id = self.engine.world.spawn();
+                self.insert_components(id, components);
+            }
+            IrAction::Despawn { entity } => {
+                let id = self.eval(entity).as_entity();
+                self.engine.despawn(id);
+            }
+            IrAction::Conditional { condition, then_actions, else_actions } => {
+                if self.eval(condition).as_boolean() {
+                    self.run_actions(then_actions);
+                } else if let Some(else_actions) = else_actions {
+                    self.run_actions(else_actions);
+                }
+            }
+            IrAction::Loop { variable, iterable, body } => {
+                let items = match self.eval(iterable) {
+                    Value::List(items) => items,
+                    _ => Vec::new(),
+                };
+                for item in items {
+                    if self.returned {
+                        break;
+                    }
+                    self.locals.insert(variable.clone(), item);
+                    self.run_actions(body);
+                }
+            }
+            IrAction::Let { name, value } => {
+                let value = self.eval(value);
+                self.locals.insert(name.clone(), value);
+            }
+            IrAction::While { condition, body } => {
+                let max = self.engine.max_while_iterations();
+                let mut iterations = 0u32;
+                while !self.returned && self.eval(condition).as_boolean() {
+                    iterations += 1;
+                    if iterations > max {
+                        break;
+                    }
+                    self.run_actions(body);
+                }
+            }
+            IrAction::Return => self.returned = true,
+        }
+    }
+
+    /// Build an event, evaluating fields in source order.
+    /// `source` and `target` fields set the built-in event properties.
+    fn build_event(&mut self, event: &str, fields: Option<&'a OrderedFields<IrExpr>>) -> Event {
+        let event_type = self.engine.interner.intern(event);
+        let mut built = Event::new(event_type);
+        for (name, expr) in fields.into_iter().flat_map(|f| f.iter()) {
+            // ...but to `Value::None` as an event field.
+            let value = match expr {
+                IrExpr::Literal { value: serde_json::Value::Null } => Value::None,
+                _ => self.eval(expr),
+            };
+            match name {
+                "source" => built.source = value.as_entity(),
+                "target" => built.target = value.as_entity(),
+                _ => {
+                    let field = self.engine.interner.intern(name);
+                    built.fields.insert(field, value);
+                }
+            }
+        }
+        built
+    }
+
+    fn insert_components(&mut self, id: EntityId, components: &'a [IrComponentInit]) {
+        for init in components {
+            if self.engine.world.dynamic_storage(&init.name).is_none() {
+                continue;
+            }
+            let mut fields = Vec::new();
+            for (field, expr) in init.fields.iter() {
+                fields.push((field, self.eval(expr)));
+            }
+            if let Some(storage) = self.engine.world.dynamic_storage_mut(&init.name) {
+                storage.insert_fields(id, fields);
+            }
+        }
+    }
+
+    fn eval(&mut self, expr: &'a IrExpr) -> Value {
+        match expr {
+            // Generated code lowers `null` to `NO_ENTITY` in expressions.
+            IrExpr::Literal { value: serde_json::Value::Null } => Value::Entity(NO_ENTITY),
+            IrExpr::Literal { value } => Value::from_json_value(value, &mut self.engine.interner),
+            IrExpr::Field { entity, component, field } => {
+                let id = match entity {
+                    IrEntityRef::Name(name) => self.lookup_var(name),
+                    IrEntityRef::Expr(expr) => self.eval(expr),
+                }
+                .as_entity();
+                let Some(storage) = self.engine.world.dynamic_storage(component) else {
+                    return Value::None;
+                };
+                let Some(index) = storage.schema().field_index(field) else {
+                    return Value::None;
+                };
+                // Missing components read as defaults, like `World::get`.
+                match storage.get(id) {
+                    Some(values) => values[index].clone(),
+                    None => storage.schema().fields[index].value_type.default_value(),
+                }
+            }
+            IrExpr::Var { name } => self.lookup_var(name),
+            IrExpr::Param { name } => match name.as_str() {
+                "source" => Value::Entity(self.event.source),
+                "target" => Value::Entity(self.event.target),
+                _ => {
+                    let field = self.engine.interner.intern(name);
+                    self.event.get_field(field).clone()
+                }
+            },
+            IrExpr::Binary { op: BinaryOp::And, left, right } => {
+                Value::Boolean(self.eval(left).as_boolean() && self.eval(right).as_boolean())
+            }
+            IrExpr::Binary { op: BinaryOp::Or, left, right } => {
+                Value::Boolean(self.eval(left).as_boolean() || self.eval(right).as_boolean())
+            }
+            IrExpr::Binary { op, left, right } => {
+                let l = self.eval(left);
+                let r = self.eval(right);
+                binary(*op, l, r, self.engine)
+            }
+            IrExpr::Unary { op: UnaryOp::Not, expr } => Value::Boolean(!self.eval(expr).as_boolean()),
+            IrExpr::Unary { op: UnaryOp::Negate, expr } => match self.eval(expr) {
+                Value::Integer(v) => Value::Integer(v.wrapping_neg()),
+                other => Value::Number(-other.as_number()),
+            },
+            IrExpr::Call { function, args } => self.call(function, args),
+            IrExpr::If { condition, then_expr, else_expr } => {
+                if self.eval(condition).as_boolean() {
+                    self.eval(then_expr)
+                } else {
+                    self.eval(else_expr)
+                }
+            }
+            IrExpr::Clone { source, overrides } => {
+                let source = self.eval(source).as_entity();
+                let id = self.engine.world.spawn();
+                for component in &self.program.module.components {
+                    self.engine.world.clone_component_by_name(&component.name, source, id);
+                }
+                self.insert_components(id, overrides);
+                Value::Entity(id)
+            }
+            IrExpr::HasComponent { entity, component } => {
+                let id = self.eval(entity).as_entity();
+                Value::Boolean(self.engine.world.has_dynamic(id, component))
+            }
+        }
+    }
+
+    fn lookup_var(&self, name: &str) -> Value {
+        if let Some(value) = self.locals.get(name) {
+            return value.clone();
+        }
+        let variable = name.strip_prefix('@').unwrap_or(name);
+        match self.named.get(variable) {
+            Some(&id) => Value::Entity(id),
+            None => Value::None,
+        }
+    }
+
+    fn call(&mut self, function: &str, args: &'a [IrExpr]) -> Value {
+        // `entities_having` takes a component name, not a value.
+        if function == "entities_having" {
+            let component = match args.first() {
+                Some(IrExpr::Literal { value: serde_json::Value::String(name) }) => name.as_str(),
+                _ => return Value::List(Vec::new()),
+            };
+            return Value::List(self.engine.world.query_dynamic(component).into_iter().map(Value::Entity).collect());
+        }
+
+        // `sort_by(list, "Component", "field")` and `filter_having(list,
+        // "Component")` take names after the list.
+        if function == "sort_by" || function == "filter_having" {
+            let list = args.first().map(|a| self.eval(a)).unwrap_or_default();
+            let items = list.as_list().unwrap_or(&[]);
+            let name = |i: usize| match args.get(i) {
+                Some(IrExpr::Literal { value: serde_json::Value::String(name) }) => name.as_str(),
+                _ => "",
+            };
+            let world = &self.engine.world;
+            return Value::List(if function == "sort_by" {
+                let storage = world.dynamic_storage(name(1));
+                let key = |v: &Value| storage.and_then(|s| s.get_field(v.as_entity(), name(2))).cloned().unwrap_or_default();
+                brl_list_sort_by(items, key, &self.engine.interner)
+            } else {
+                brl_list_filter(items, |v| world.has_dynamic(v.as_entity(), name(1)))
+            });
+        }
+
+        let values: Vec<Value> = args.iter().map(|a| self.eval(a)).collect();
+        if function == "list" {
+            return Value::List(values);
+        }
+        let arg = |i: usize| values.get(i).cloned().unwrap_or_default();
+        let list = |i: usize| values.get(i).and_then(Value::as_list).unwrap_or(&[]);
+        let engine = &mut *self.engine;
+        match function {
+            "min" if values.len() == 1 => brl_list_min(list(0), &engine.interner),
+            "max" if values.len() == 1 => brl_list_max(list(0), &engine.interner),
+            "min" => Value::Number(brl_min(arg(0).as_number(), arg(1).as_number())),
+            "max" => Value::Number(brl_max(arg(0).as_number(), arg(1).as_number())),
+            "floor" => Value::Number(brl_floor(arg(0).as_number())),
+            "ceil" => Value::Number(brl_ceil(arg(0).as_number())),
+            "round" => Value::Number(brl_round(arg(0).as_number())),
+            "abs" => Value::Number(brl_abs(arg(0).as_number())),
+            "clamp" => Value::Number(brl_clamp(arg(0).as_number(), arg(1).as_number(), arg(2).as_number())),
+            "pow" => Value::Number(brl_pow(arg(0).as_number(), arg(1).as_number())),
+            "sqrt" => Value::Number(brl_sqrt(arg(0).as_number())),
+            "log" => Value::Number(brl_log(arg(0).as_number())),
+            "exp" => Value::Number(brl_exp(arg(0).as_number())),
+            "lerp" => Value::Number(brl_lerp(arg(0).as_number(), arg(1).as_number(), arg(2).as_number())),
+            "inverse_lerp" => {
+                Value::Number(brl_inverse_lerp(arg(0).as_number(), arg(1).as_number(), arg(2).as_number()))
+            }
+            "sign" => Value::Number(brl_sign(arg(0).as_number())),
+            "div_floor" => Value::Integer(brl_div_floor(arg(0).as_integer(), arg(1).as_integer())),
+            "mod_floor" => Value::Integer(brl_mod_floor(arg(0).as_integer(), arg(1).as_integer())),
+            "round_to" => Value::Number(brl_round_to(arg(0).as_number(), arg(1).as_integer())),
+            "curve_exp" => Value::Number(brl_curve_exp(arg(0).as_number(), arg(1).as_number(), arg(2).as_number())),
+            "curve_logistic" => Value::Number(brl_curve_logistic(
+                arg(0).as_number(),
+                arg(1).as_number(),
+                arg(2).as_number(),
+                arg(3).as_number(),
+                arg(4).as_number(),
+            )),
+            "random" => Value::Number(engine.rng.random()),
+            "random_range" => Value::Number(engine.rng.random_range(arg(0).as_number(), arg(1).as_number())),
+            "random_int" => Value::Integer(brl_random_int(arg(0).as_integer(), arg(1).as_integer(), &mut engine.rng)),
+            "random_normal" => Value::Number(brl_random_normal(arg(0).as_number(), arg(1).as_number(), &mut engine.rng)),
+            "random_exponential" => Value::Number(brl_random_exponential(arg(0).as_number(), &mut engine.rng)),
+            "random_poisson" => Value::Integer(brl_random_poisson(arg(0).as_number(), &mut engine.rng)),
+            "random_binomial" => {
+                Value::Integer(brl_random_binomial(arg(0).as_integer(), arg(1).as_number(), &mut engine.rng))
+            }
+            "random_weighted_index" => {
+                let weights: Vec<f64> = arg(0).as_list().unwrap_or(&[]).iter().map(Value::as_number).collect();
+                Value::Integer(brl_random_weighted_index(&weights, &mut engine.rng))
+            }
+            "roll" => Value::Integer(brl_roll(arg(0).as_string_id(), &mut engine.rng, &engine.interner)),
+            "roll_breakdown" => {
+                Value::List(brl_roll_breakdown(arg(0).as_string_id(), &mut engine.rng, &mut engine.interner))
+            }
+            "roll_loot" => {
+                Value::List(brl_roll_loot(arg(0).as_string_id(), engine).into_iter().map(Value::String).collect())
+            }
+            "shuffle" => Value::List(brl_shuffle(arg(0).as_list().unwrap_or(&[]), &mut engine.rng)),
+            "sample" => Value::List(brl_sample(arg(0).as_list().unwrap_or(&[]), arg(1).as_integer(), &mut engine.rng)),
+            "len" => match values.first() {
+                Some(Value::List(items)) => Value::Integer(brl_len(items)),
+                _ => Value::Integer(0),
+            },
+            "get" => match (arg(0), arg(1).as_integer()) {
+                (Value::List(items), i) if i >= 0 => items.get(i as usize).cloned().unwrap_or_default(),
+                _ => Value::None,
+            },
+            "push" => Value::List(brl_list_push(list(0), arg(1))),
+            "pop" => Value::List(brl_list_pop(list(0))),
+            "insert" => Value::List(brl_list_insert(list(0), arg(1).as_integer(), arg(2))),
+            "remove" => Value::List(brl_list_remove(list(0), arg(1).as_integer())),
+            "contains" => Value::Boolean(brl_list_contains(list(0), &arg(1))),
+            "index_of" => Value::Integer(brl_list_index_of(list(0), &arg(1))),
+            "slice" => Value::List(brl_list_slice(list(0), arg(1).as_integer(), arg(2).as_integer())),
+            "list_concat" => Value::List(brl_list_concat(list(0), list(1))),
+            "sort" => Value::List(brl_list_sort(list(0), &engine.interner)),
+            "reverse" => Value::List(brl_list_reverse(list(0))),
+            "unique" => Value::List(brl_list_unique(list(0))),
+            "sum" => {
+                let items = list(0);
+                if items.iter().all(|v| matches!(v, Value::Integer(_))) {
+                    Value::Integer(brl_list_sum(&items.iter().map(Value::as_integer).collect::<Vec<_>>()))
+                } else {
+                    Value::Number(brl_list_sum(&items.iter().map(Value::as_number).collect::<Vec<_>>()))
+                }
+            }
+            "new_entity" => Value::Entity(engine.world.spawn()),
+            "concat" => {
+                let a = to_string_id(&arg(0), engine);
+                let b = to_string_id(&arg(1), engine);
+                Value::String(brl_concat(a, b, &mut engine.interner))
+            }
+            "format" | "format_named" | "tr" | "tr_named" => {
+                let args = if function.ends_with("_named") {
+                    Value::Map(values.get(1..).unwrap_or(&[]).chunks_exact(2).map(|kv| (kv[0].as_string_id(), kv[1].clone())).collect())
+                } else {
+                    Value::List(values.get(1..).unwrap_or(&[]).to_vec())
+                };
+                // An entity's name is the `name` field of the first component declaring one.
+                let name_component = self.program.module.components.iter().find(|c| {
+                    c.fields.iter().any(|f| f.name == "name" && f.field_type == IrType::String)
+                });
+                let name_of = |world: &crate::world::World, e| {
+                    let storage = world.dynamic_storage(&name_component?.name)?;
+                    storage.get_field(e, "name").map(Value::as_string_id).filter(|s| *s != InternedString::NONE)
+                };
+                Value::String(if matches!(function, "tr" | "tr_named") {
+                    brl_tr(arg(0).as_string_id(), &args, name_of, engine)
+                } else {
+                    brl_format(arg(0).as_string_id(), &args, name_of, engine)
+                })
+            }
+            "to_string" => Value::String(to_string_id(&arg(0), engine)),
+            "str_replace" => Value::String(brl_str_replace(
+                arg(0).as_string_id(),
+                arg(1).as_string_id(),
+                arg(2).as_string_id(),
+                &mut engine.interner,
+            )),
+            "str_contains" => Value::Boolean(brl_str_contains(
+                arg(0).as_string_id(),
+                arg(1).as_string_id(),
+                &engine.interner,
+            )),
+            "str_pick_variant" => Value::String(brl_str_pick_variant(
+                arg(0).as_string_id(),
+                arg(1).as_integer(),
+                &mut engine.interner,
+            )),
+            "str_count_variants" => {
+                Value::Integer(brl_str_count_variants(arg(0).as_string_id(), &engine.interner))
+            }
+            "str_split" => Value::List(
+                brl_str_split(arg(0).as_string_id(), arg(1).as_string_id(), &mut engine.interner)
+                    .into_iter()
+                    .map(Value::String)
+                    .collect(),
+            ),
+            "str_join" => {
+                let parts: Vec<InternedString> = list(0).iter().map(|v| to_string_id(v, engine)).collect();
+                Value::String(brl_str_join(&parts, arg(1).as_string_id(), &mut engine.interner))
+            }
+            "str_upper" => Value::String(brl_str_upper(arg(0).as_string_id(), &mut engine.interner)),
+            "str_lower" => Value::String(brl_str_lower(arg(0).as_string_id(), &mut engine.interner)),
+            "str_capitalize" => Value::String(brl_str_capitalize(arg(0).as_string_id(), &mut engine.interner)),
+            "str_trim" => Value::String(brl_str_trim(arg(0).as_string_id(), &mut engine.interner)),
+            "str_starts_with" => {
+                Value::Boolean(brl_str_starts_with(arg(0).as_string_id(), arg(1).as_string_id(), &engine.interner))
+            }
+            "str_ends_with" => {
+                Value::Boolean(brl_str_ends_with(arg(0).as_string_id(), arg(1).as_string_id(), &engine.interner))
+            }
+            "str_substring" => Value::String(brl_str_substring(
+                arg(0).as_string_id(),
+                arg(1).as_integer(),
+                arg(2).as_integer(),
+                &mut engine.interner,
+            )),
+            "str_length" => Value::Integer(brl_str_length(arg(0).as_string_id(), &engine.interner)),
+            "str_pad_start" => Value::String(brl_str_pad_start(
+                arg(0).as_string_id(),
+                arg(1).as_integer(),
+                arg(2).as_string_id(),
+                &mut engine.interner,
+            )),
+            "str_pad_end" => Value::String(brl_str_pad_end(
+                arg(0).as_string_id(),
+                arg(1).as_integer(),
+                arg(2).as_string_id(),
+                &mut engine.interner,
+            )),
+            _ => self.call_function(function, values),
+        }
+    }
+
+    fn call_function(&mut self, name: &str, args: Vec<Value>) -> Value {
+        let Some(&index) = self.program.function_index.get(name) else {
+            return Value::None;
+        };
+        let function = &self.program.module.functions[index];
+        let params = function
+            .params
+            .iter()
+            .zip(args)
+            .map(|(p, v)| (p.name.clone(), p.param_type.value_type().coerce(v)))
+            .collect();
+        let saved = std::mem::replace(&mut self.locals, params);
+        let result = self.eval(&function.body);
+        self.locals = saved;
+        function.return_type.value_type().coerce(result)
+    }
+}
+
+/// Apply a binary operator with BRL semantics: integer arithmetic when both
+/// sides are integers, float arithmetic otherwise, string concatenation when
+/// either side of `+` is a string.
+fn binary(op: BinaryOp, l: Value, r: Value, engine: &mut Engine) -> Value {
+    let both_int = matches!((&l, &r), (Value::Integer(_), Value::Integer(_)));
+    match op {
+        BinaryOp::Add if matches!(l, Value::String(_)) || matches!(r, Value::String(_)) => {
+            let a = to_string_id(&l, engine);
+            let b = to_string_id(&r, engine);
+            Value::String(brl_concat(a, b, &mut engine.interner))
+        }
+        BinaryOp::Add if both_int => Value::Integer(l.as_integer().wrapping_add(r.as_integer())),
+        BinaryOp::Subtract if both_int => Value::Integer(l.as_integer().wrapping_sub(r.as_integer())),
+        BinaryOp::Multiply if both_int => Value::Integer(l.as_integer().wrapping_mul(r.as_integer())),
+        BinaryOp::Divide if both_int => Value::Integer(l.as_integer().checked_div(r.as_integer()).unwrap_or(0)),
+        BinaryOp::Modulo if both_int => Value::Integer(l.as_integer().checked_rem(r.as_integer()).unwrap_or(0)),
+        BinaryOp::Add => Value::Number(l.as_number() + r.as_number()),
+        BinaryOp::Subtract => Value::Number(l.as_number() - r.as_number()),
+        BinaryOp::Multiply => Value::Number(l.as_number() * r.as_number()),
+        BinaryOp::Divide => Value::Number(l.as_number() / r.as_number()),
+        BinaryOp::Modulo => Value::Number(l.as_number() % r.as_number()),
+        BinaryOp::Eq => Value::Boolean(values_equal(&l, &r)),
+        BinaryOp::Neq => Value::Boolean(!values_equal(&l, &r)),
+        BinaryOp::Lt => Value::Boolean(l.as_number() < r.as_number()),
+        BinaryOp::Lte => Value::Boolean(l.as_number() <= r.as_number()),
+        BinaryOp::Gt => Value::Boolean(l.as_number() > r.as_number()),
+        BinaryOp::Gte => Value::Boolean(l.as_number() >= r.as_number()),
+        BinaryOp::And => Value::Boolean(l.as_boolean() && r.as_boolean()),
+        BinaryOp::Or => Value::Boolean(l.as_boolean() || r.as_boolean()),
+    }
+}
+
+/// `Value` equality. Generated code types entity IDs as integers, so an
+/// entity compared with an integer compares the ID.
+fn values_equal(l: &Value, r: &Value) -> bool {
+    match (l, r) {
+        (Value::Entity(e), Value::Integer(n)) | (Value::Integer(n), Value::Entity(e)) => i64::from(*e) == *n,
+        _ => l == r,
+    }
+}
+
+/// Convert a value to an interned string, like the `brl_to_string_*` calls
+/// generated for string concatenation.
+fn to_string_id(value: &Value, engine: &mut Engine) -> InternedString {
+    match value {
+        Value::String(s) => *s,
+        Value::Integer(v) => brl_to_string_int(*v, &mut engine.interner),
+        Value::Number(v) => brl_to_string_float(*v, &mut engine.interner),
+        Value::Boolean(v) => brl_to_string_int(*v as i64, &mut engine.interner),
+        Value::Entity(v) => brl_to_string_int(*v as i64, &mut engine.interner),
+        _ => InternedString::NONE,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Heroes attack a dummy until it dies; damage is rolled each hit.
+    const COMBAT_IR: &str = r#"{
+        "version": "1.0",
+        "module": "combat",
+        "components": [
+            { "id": 0, "name": "Health", "fields": [
+                { "name": "current", "type": { "type": "integer" } },
+                { "name": "max", "type": { "type": "integer" } }
+            ] },
+            { "id": 1, "name": "Combat", "fields": [
+                { "name": "damage", "type": { "type": "number" } },
+                { "name": "speed", "type": { "type": "number" } }
+            ] }
+        ],
+        "rules": [
+            { "id": 0, "name": "attack", "trigger": { "type": "event", "event": "Attack" }, "actions": [
+                { "type": "let", "name": "roll", "value": { "type": "call", "function": "floor", "args": [
+                    { "type": "binary", "op": "multiply",
+                      "left": { "type": "field", "entity": { "type": "param", "name": "source" }, "component": "Combat", "field": "damage" },
+                      "right": { "type": "call", "function": "random_range", "args": [
+                          { "type": "literal", "value": 0.5 }, { "type": "literal", "value": 1.5 } ] } } ] } },
+                { "type": "modify", "entity": { "type": "param", "name": "target" },
+                  "component": "Health", "field": "current", "op": "subtract",
+                  "value": { "type": "var", "name": "roll" } },
+                { "type": "conditional",
+                  "condition": { "type": "binary", "op": "gt",
+                      "left": { "type": "field", "entity": { "type": "param", "name": "target" }, "component": "Health", "field": "current" },
+                      "right": { "type": "literal", "value": 0 } },
+                  "then_actions": [
+                      { "type": "schedule", "event": "Attack",
+                        "delay": { "type": "field", "entity": { "type": "param", "name": "source" }, "component": "Combat", "field": "speed" },
+                        "fields": {
+                            "source": { "type": "param", "name": "source" },
+                            "target": { "type": "param", "name": "target" } } } ],
+                  "else_actions": [
+                      { "type": "schedule", "event": "Died",
+                        "fields": { "target": { "type": "param", "name": "target" } } } ] }
+            ] },
+            { "id": 1, "name": "died", "trigger": { "type": "event", "event": "Died" }, "actions": [
+                { "type": "despawn", "entity": { "type": "param", "name": "target" } }
+            ] }
+        ],
+        "functions": [],
+        "initial_state": { "entities": [
+            { "id": 0, "variable": "hero", "components": {
+                "Combat": { "damage": 12, "speed": 1.5 } } },
+            { "id": 1, "variable": "dummy", "components": {
+                "Health": { "current": 100, "max": 100 } } }
+        ] }
+    }"#;
+
+    #[derive(Clone, Default)]
+    struct Health {
+        current: i64,
+    }
+
+    #[derive(Clone, Default)]
+    struct Combat {
+        damage: f64,
+        speed: f64,
+    }
+
+    /// The same rules as `COMBAT_IR`, written the way the Rust codegen emits them.
+    fn run_compiled(seed: u64) -> (f64, Vec<i64>) {
+        let mut engine = Engine::with_seed(seed);
+        engine.world.register_component::<Health>();
+        engine.world.register_component::<Combat>();
+        let attack = engine.interner.intern("Attack");
+        let died = engine.interner.intern("Died");
+        let hero = engine.world.spawn_named("hero");
+        engine.world.insert(hero, Combat { damage: 12.0, speed: 1.5 });
+        let dummy = engine.world.spawn_named("dummy");
+        engine.world.insert(dummy, Health { current: 100 });
+        engine
+            .timeline
+            .schedule_immediate(Event::new(attack).with_source(hero).with_target(dummy));
+
+        let mut hp_trace = Vec::new();
+        while let Some(event) = engine.next_event() {
+            if event.event_type == attack {
+                let roll = brl_floor(
+                    engine.world.get::<Combat>(event.source).damage * engine.rng.random_range(0.5, 1.5),
+                );
+                engine.world.get_mut::<Health>(event.target).current -= roll as i64;
+                hp_trace.push(engine.world.get::<Health>(event.target).current);
+                if engine.world.get::<Health>(event.target).current > 0 {
+                    let delay = engine.world.get::<Combat>(event.source).speed;
+                    let next = Event::new(attack).with_source(event.source).with_target(event.target);
+                    engine.timeline.schedule_delay(delay, next);
+                } else {
+                    engine.timeline.schedule_immediate(Event::new(died).with_target(event.target));
+                }
+            } else if event.event_type == died {
+                engine.world.despawn(event.target);
+            }
+        }
+        (engine.get_time(), hp_trace)
+    }
+
+    fn run_interpreted(seed: u64) -> (f64, Vec<i64>) {
+        let mut engine = Engine::with_seed(seed);
+        let mut interp = Interpreter::from_json(COMBAT_IR).unwrap();
+        interp.init(&mut engine);
+        let hero = interp.named_entity("hero").unwrap();
+        let dummy = interp.named_entity("dummy").unwrap();
+        let attack = engine.interner.intern("Attack");
+        engine
+            .timeline
+            .schedule_immediate(Event::new(attack).with_source(hero).with_target(dummy));
+
+        let mut hp_trace = Vec::new();
+        while let Some(event) = engine.next_event() {
+            interp.dispatch_event(&event, &mut engine);
+            if event.event_type == attack
+                && let Some(hp) = interp.get_field(&engine, dummy, "Health", "current")
+            {
+                hp_trace.push(hp.as_integer());
+            }
+        }
+        assert!(!engine.world.is_alive(dummy));
+        assert!(!engine.world.has_dynamic(dummy, "Health"));
+        (engine.get_time(), hp_trace)
+    }
+
+    #[test]
+    fn test_matches_compiled_rules() {
+        for seed in [1, 42, 12345] {
+            let compiled = run_compiled(seed);
+            let interpreted = run_interpreted(seed);
+            assert_eq!(compiled.0.to_bits(), interpreted.0.to_bits());
+            assert_eq!(compiled.1, interpreted.1);
+            assert!(compiled.1.len() > 1);
+        }
+    }
+
+    #[test]
+    fn test_defaults_and_coercion() {
+        let mut engine = Engine::new();
+        let mut interp = Interpreter::from_json(COMBAT_IR).unwrap();
+        interp.init(&mut engine);
+        let e = engine.world.spawn();
+        assert!(interp.insert_component(&mut engine, e, "Health", vec![("current", Value::Number(7.9))]));
+        assert_eq!(interp.get_field(&engine, e, "Health", "current"), Some(&Value::Integer(7)));
+        assert_eq!(interp.get_field(&engine, e, "Health", "max"), Some(&Value::Integer(0)));
+        assert!(!interp.insert_component(&mut engine, e, "Missing", vec![]));
+        assert_eq!(engine.world.query_dynamic("Health"), vec![interp.named_entity("dummy").unwrap(), e]);
+        let component = engine.world.dynamic_component_json(e, "Health", &engine.interner).unwrap();
+        assert!(component.contains("\"current\":7"));
+    }
+
+    #[test]
+    fn test_functions_and_loops() {
+        let json = r#"{
+            "version": "1.0", "module": "m",
+            "components": [ { "id": 0, "name": "Counter", "fields": [
+                { "name": "total", "type": { "type": "integer" } } ] } ],
+            "rules": [ { "id": 0, "trigger": { "type": "event", "event": "Go" }, "actions": [
+                { "type": "loop", "variable": "x",
+                  "iterable": { "type": "call", "function": "list", "args": [
+                      { "type": "literal", "value": 1 }, { "type": "literal", "value": 2 }, { "type": "literal", "value": 3 } ] },
+                  "body": [ { "type": "modify", "entity": { "type": "var", "name": "@counter" },
+                      "component": "Counter", "field": "total", "op": "add",
+                      "value": { "type": "call", "function": "double", "args": [ { "type": "var", "name": "x" } ] } } ] }
+            ] } ],
+            "functions": [ { "id": 0, "name": "double",
+                "params": [ { "name": "n", "type": { "type": "integer" } } ],
+                "return_type": { "type": "integer" },
+                "body": { "type": "binary", "op": "multiply",
+                    "left": { "type": "var", "name": "n" }, "right": { "type": "literal", "value": 2 } } } ],
+            "initial_state": { "entities": [
+                { "id": 0, "variable": "counter", "components": { "Counter": { "total": 0 } } } ] }
+        }"#;
+        let mut engine = Engine::new();
+        let mut interp = Interpreter::from_json(json).unwrap();
+        interp.init(&mut engine);
+        // Rule event names are pinned, so collecting does not unhook dispatch.
+        engine.collect_strings();
+        let go = engine.interner.intern("Go");
+        engine.timeline.schedule_immediate(Event::new(go));
+        assert_eq!(interp.run_steps(&mut engine, 10), 1);
+        let counter = interp.named_entity("counter").unwrap();
+        assert_eq!(interp.get_field(&engine, counter, "Counter", "total"), Some(&Value::Integer(12)));
+    }
+
+    #[test]
+    fn test_list_builtins() {
+        // Tagged Health entities, weakest first: visits 12 then 30.
+        let json = r#"{
+            "version": "1.0", "module": "m",
+            "components": [
+                { "id": 0, "name": "Health", "fields": [ { "name": "current", "type": { "type": "integer" } } ] },
+                { "id": 1, "name": "Tag", "fields": [] },
+                { "id": 2, "name": "Log", "fields": [ { "name": "order", "type": { "type": "integer" } } ] } ],
+            "rules": [ { "id": 0, "trigger": { "type": "event", "event": "Go" }, "actions": [
+                { "type": "loop", "variable": "e",
+                  "iterable": { "type": "call", "function": "sort_by", "args": [
+                      { "type": "call", "function": "filter_having", "args": [
+                          { "type": "call", "function": "entities_having", "args": [ { "type": "literal", "value": "Health" } ] },
+                          { "type": "literal", "value": "Tag" } ] },
+                      { "type": "literal", "value": "Health" }, { "type": "literal", "value": "current" } ] },
+                  "body": [ { "type": "modify", "entity": { "type": "var", "name": "@log" },
+                      "component": "Log", "field": "order", "op": "set",
+                      "value": { "type": "binary", "op": "add",
+                          "left": { "type": "binary", "op": "multiply",
+                              "left": { "type": "field", "entity": { "type": "var", "name": "@log" }, "component": "Log", "field": "order" },
+                              "right": { "type": "literal", "value": 100 } },
+                          "right": { "type": "field", "entity": { "type": "var", "name": "e" }, "component": "Health", "field": "current" } } } ] },
+                { "type": "modify", "entity": { "type": "var", "name": "@log" }, "component": "Log", "field": "order", "op": "add",
+                  "value": { "type": "call", "function": "sum", "args": [
+                      { "type": "call", "function": "push", "args": [
+                          { "type": "call", "function": "list", "args": [ { "type": "literal", "value": 1 } ] },
+                          { "type": "literal", "value": 2 } ] } ] } }
+            ] } ],
+            "initial_state": { "entities": [
+                { "id": 0, "variable": "a", "components": { "Health": { "current": 30 }, "Tag": {} } },
+                { "id": 1, "variable": "b", "components": { "Health": { "current": 5 } } },
+                { "id": 2, "variable": "c", "components": { "Health": { "current": 12 }, "Tag": {} } },
+                { "id": 3, "variable": "log", "components": { "Log": { "order": 0 } } } ] }
+        }"#;
+        let mut engine = Engine::new();
+        let mut interp = Interpreter::from_json(json).unwrap();
+        interp.init(&mut engine);
+        let go = engine.interner.intern("Go");
+        engine.timeline.schedule_immediate(Event::new(go));
+        interp.run_steps(&mut engine, 10);
+        let log = interp.named_entity("log").unwrap();
+        assert_eq!(interp.get_field(&engine, log, "Log", "order"), Some(&Value::Integer(1233)));
+    }
+
+    #[test]
+    fn test_query_and_return() {
+        // Bumps each Counter in ID order; `return` stops at the first above 3.
+        let json = r#"{
+            "version": "1.0", "module": "m",
+            "components": [ { "id": 0, "name": "Counter", "fields": [
+                { "name": "total", "type": { "type": "integer" } } ] } ],
+            "rules": [ { "id": 0, "trigger": { "type": "event", "event": "Go" },
+                "query": { "component": "Counter", "variable": "entity" }, "actions": [
+                { "type": "modify", "entity": { "type": "var", "name": "entity" },
+                  "component": "Counter", "field": "total", "op": "add", "value": { "type": "literal", "value": 1 } },
+                { "type": "conditional",
+                  "condition": { "type": "binary", "op": "gt",
+                      "left": { "type": "field", "entity": "entity", "component": "Counter", "field": "total" },
+                      "right": { "type": "literal", "value": 3 } },
+                  "then_actions": [ { "type": "return" } ] }
+            ] } ],
+            "initial_state": { "entities": [
+                { "id": 0, "variable": "a", "components": { "Counter": { "total": 0 } } },
+                { "id": 1, "variable": "b", "components": { "Counter": { "total": 5 } } },
+                { "id": 2, "variable": "c", "components": { "Counter": { "total": 0 } } } ] }
+        }"#;
+        let mut engine = Engine::new();
+        let mut interp = Interpreter::from_json(json).unwrap();
+        interp.init(&mut engine);
+        let go = engine.interner.intern("Go");
+        engine.timeline.schedule_immediate(Event::new(go));
+        interp.run_steps(&mut engine, 10);
+        let totals: Vec<_> = ["a", "b", "c"]
+            .iter()
+            .map(|name| {
+                let id = interp.named_entity(name).unwrap();
+                interp.get_field(&engine, id, "Counter", "total").cloned()
+            })
+            .collect();
+        assert_eq!(totals, [Some(Value::Integer(1)), Some(Value::Integer(6)), Some(Value::Integer(0))]);
+    }
+}
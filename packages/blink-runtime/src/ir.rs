@@ -0,0 +1,393 @@
+//! IR types: the compiler's JSON intermediate representation.
+//!
+//! Mirrors `packages/blink-compiler-ts/src/ir.ts` so the interpreter can load
+//! the output of the TypeScript compiler directly. Only the parts the runtime
+//! executes are modelled; metadata, source maps and choice-point descriptions
+//! are ignored on load.
+
+use std::fmt;
+use std::marker::PhantomData;
+
+use serde::de::{Deserializer, MapAccess, Visitor};
+use serde::Deserialize;
+
+use crate::value::ValueType;
+
+/// A JSON object whose entries keep their document order.
+/// Field initialisers and event fields are evaluated in source order, which
+/// matters as soon as an expression draws from the RNG.
+#[derive(Clone, Debug, PartialEq)]
+pub struct OrderedFields<T>(pub Vec<(String, T)>);
+
+impl<T> Default for OrderedFields<T> {
+    fn default() -> Self {
+        OrderedFields(Vec::new())
+    }
+}
+
+impl<T> OrderedFields<T> {
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &T)> {
+        self.0.iter().map(|(k, v)| (k.as_str(), v))
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for OrderedFields<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct OrderedVisitor<T>(PhantomData<T>);
+
+        impl<'de, T: Deserialize<'de>> Visitor<'de> for OrderedVisitor<T> {
+            type Value = OrderedFields<T>;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a JSON object")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+                let mut entries = Vec::new();
+                while let Some((key, value)) = map.next_entry::<String, T>()? {
+                    entries.push((key, value));
+                }
+                Ok(OrderedFields(entries))
+            }
+        }
+
+        deserializer.deserialize_map(OrderedVisitor(PhantomData))
+    }
+}
+
+/// A compiled BRL module.
+#[derive(Clone, Debug, Deserialize)]
+pub struct IrModule {
+    pub version: String,
+    pub module: String,
+    #[serde(default)]
+    pub components: Vec<IrComponent>,
+    #[serde(default)]
+    pub rules: Vec<IrRule>,
+    #[serde(default)]
+    pub functions: Vec<IrFunction>,
+    #[serde(default)]
+    pub initial_state: Option<IrInitialState>,
+}
+
+impl IrModule {
+    /// Parse a module from the compiler's JSON output.
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        serde_json::from_str(json).map_err(|e| e.to_string())
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct IrComponent {
+    pub id: u32,
+    pub name: String,
+    pub fields: Vec<IrField>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct IrField {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub field_type: IrType,
+    #[serde(default)]
+    pub default: Option<serde_json::Value>,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum IrType {
+    Number,
+    Integer,
+    String,
+    Boolean,
+    Entity,
+    List { element: Box<IrType> },
+    Map { key: Box<IrType>, value: Box<IrType> },
+}
+
+impl IrType {
+    /// Runtime value type used for storage and coercion.
+    pub fn value_type(&self) -> ValueType {
+        match self {
+            IrType::Number => ValueType::Number,
+            IrType::Integer => ValueType::Integer,
+            IrType::String => ValueType::String,
+            IrType::Boolean => ValueType::Boolean,
+            IrType::Entity => ValueType::Entity,
+            IrType::List { .. } => ValueType::List,
+            IrType::Map { .. } => ValueType::Map,
+        }
+    }
+}
+
+// ── Rules ──
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct IrRule {
+    pub id: u32,
+    #[serde(default)]
+    pub name: Option<String>,
+    pub trigger: IrTrigger,
+    #[serde(default)]
+    pub query: Option<IrRuleQuery>,
+    #[serde(default)]
+    pub condition: Option<IrExpr>,
+    pub actions: Vec<IrAction>,
+}
+
+/// Runs a rule's actions once per entity having `component`, bound to
+/// `variable`.
+#[derive(Clone, Debug, Deserialize)]
+pub struct IrRuleQuery {
+    pub component: String,
+    pub variable: String,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct IrTrigger {
+    #[serde(rename = "type")]
+    pub trigger_type: String,
+    #[serde(default)]
+    pub event: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ModifyOp {
+    Set,
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum IrAction {
+    Modify {
+        entity: IrExpr,
+        component: String,
+        field: String,
+        op: ModifyOp,
+        value: IrExpr,
+    },
+    Schedule {
+        event: String,
+        #[serde(default)]
+        source: Option<IrExpr>,
+        #[serde(default)]
+        target: Option<IrExpr>,
+        #[serde(default)]
+        delay: Option<IrExpr>,
+        #[serde(default)]
+        fields: Option<OrderedFields<IrExpr>>,
+    },
+    Emit {
+        event: String,
+        #[serde(default)]
+        fields: Option<OrderedFields<IrExpr>>,
+    },
+    Spawn {
+        components: Vec<IrComponentInit>,
+    },
+    Despawn {
+        entity: IrExpr,
+    },
+    Conditional {
+        condition: IrExpr,
+        then_actions: Vec<IrAction>,
+        #[serde(default)]
+        else_actions: Option<Vec<IrAction>>,
+    },
+    Loop {
+        variable: String,
+        iterable: IrExpr,
+        body: Vec<IrAction>,
+    },
+    Let {
+        name: String,
+        value: IrExpr,
+    },
+    While {
+        condition: IrExpr,
+        body: Vec<IrAction>,
+    },
+    Return,
+}
+
+// ── Expressions ──
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BinaryOp {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Modulo,
+    Eq,
+    Neq,
+    Lt,
+    Lte,
+    Gt,
+    Gte,
+    And,
+    Or,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UnaryOp {
+    Not,
+    Negate,
+}
+
+/// The entity of a field expression: a variable name or an expression.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(untagged)]
+pub enum IrEntityRef {
+    Name(String),
+    Expr(Box<IrExpr>),
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum IrExpr {
+    Literal {
+        value: serde_json::Value,
+    },
+    Field {
+        entity: IrEntityRef,
+        component: String,
+        field: String,
+    },
+    Var {
+        name: String,
+    },
+    Param {
+        name: String,
+    },
+    Binary {
+        op: BinaryOp,
+        left: Box<IrExpr>,
+        right: Box<IrExpr>,
+    },
+    Unary {
+        op: UnaryOp,
+        expr: Box<IrExpr>,
+    },
+    Call {
+        function: String,
+        #[serde(default)]
+        args: Vec<IrExpr>,
+    },
+    If {
+        condition: Box<IrExpr>,
+        #[serde(rename = "then")]
+        then_expr: Box<IrExpr>,
+        #[serde(rename = "else")]
+        else_expr: Box<IrExpr>,
+    },
+    Clone {
+        source: Box<IrExpr>,
+        #[serde(default)]
+        overrides: Vec<IrComponentInit>,
+    },
+    HasComponent {
+        entity: Box<IrExpr>,
+        component: String,
+    },
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct IrComponentInit {
+    pub name: String,
+    #[serde(default)]
+    pub fields: OrderedFields<IrExpr>,
+}
+
+// ── Functions ──
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct IrFunction {
+    pub id: u32,
+    pub name: String,
+    pub params: Vec<IrParam>,
+    pub return_type: IrType,
+    pub body: IrExpr,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct IrParam {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub param_type: IrType,
+}
+
+// ── Initial state ──
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct IrInitialState {
+    pub entities: Vec<IrEntityDefinition>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct IrEntityDefinition {
+    pub id: serde_json::Value,
+    #[serde(default)]
+    pub variable: Option<String>,
+    pub components: OrderedFields<OrderedFields<serde_json::Value>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_module() {
+        let json = r#"{
+            "version": "1.0",
+            "module": "test",
+            "metadata": { "compiled_at": "now", "compiler_version": "1.0.0-ts" },
+            "components": [
+                { "id": 0, "name": "Health", "fields": [
+                    { "name": "current", "type": { "type": "integer" } },
+                    { "name": "tags", "type": { "type": "list", "element": { "type": "string" } } }
+                ] }
+            ],
+            "rules": [
+                { "id": 0, "trigger": { "type": "event", "event": "Hit" }, "actions": [
+                    { "type": "schedule", "event": "Done",
+                      "fields": { "zeta": { "type": "literal", "value": 1 },
+                                  "alpha": { "type": "param", "name": "amount" } } },
+                    { "type": "modify", "entity": { "type": "param", "name": "target" },
+                      "component": "Health", "field": "current", "op": "subtract",
+                      "value": { "type": "field", "entity": "attacker", "component": "Combat", "field": "damage" } }
+                ] }
+            ],
+            "functions": []
+        }"#;
+        let module = IrModule::from_json(json).unwrap();
+        assert_eq!(module.components[0].fields[1].field_type,
+            IrType::List { element: Box::new(IrType::String) });
+        match &module.rules[0].actions[0] {
+            IrAction::Schedule { fields: Some(fields), .. } => {
+                let names: Vec<&str> = fields.iter().map(|(k, _)| k).collect();
+                assert_eq!(names, vec!["zeta", "alpha"]);
+            }
+            other => panic!("unexpected action {:?}", other),
+        }
+        match &module.rules[0].actions[1] {
+            IrAction::Modify { op, value: IrExpr::Field { entity: IrEntityRef::Name(name), .. }, .. } => {
+                assert_eq!(*op, ModifyOp::Subtract);
+                assert_eq!(name, "attacker");
+            }
+            other => panic!("unexpected action {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_invalid_module() {
+        assert!(IrModule::from_json("{\"version\": 1}").is_err());
+    }
+}
@@ -1,34 +1,93 @@
-/// Blink Runtime Library
-///
-/// Game-agnostic runtime for the Blink WASM engine.
-/// Provides ECS World, Timeline, Event system, string interning, and built-in functions.
-///
-/// Generated game code (from BRL → Rust compilation) links against this crate
-/// and provides concrete component types, rule functions, and entity initializers.
+//! Blink Runtime Library
+//!
+//! Game-agnostic runtime for the Blink WASM engine.
+//! Provides ECS World, Timeline, Event system, string interning, and built-in functions.
+//!
+//! Generated game code (from BRL → Rust compilation) links against this crate
+//! and provides concrete component types, rule functions, and entity initializers.
 
+pub mod balance;
 pub mod builtins;
+pub mod choice;
+pub mod decision;
+pub mod detmath;
+pub mod dice;
+pub mod dynamic;
 pub mod event;
+pub mod event_schema;
+pub mod format;
 pub mod interning;
+pub mod interpreter;
+pub mod ir;
+pub mod locale;
+pub mod loot;
+pub mod phase;
+pub mod rng;
+pub mod time_scale;
 pub mod timeline;
 pub mod value;
+pub mod value_ops;
 pub mod world;
 
 // Re-export key types for convenience
-pub use builtins::{brl_abs, brl_ceil, brl_floor, brl_max, brl_min, brl_round, brl_concat, brl_to_string_int, brl_to_string_float, brl_str_replace, brl_str_contains, Rng};
-pub use event::{Event, EventId};
-pub use interning::{InternedString, StringInterner};
+pub use balance::{BalanceCheck, BalanceCheckKind, BalanceEvaluator, CheckOutcome, RunResult, SequentialPlan, Verdict};
+pub use builtins::{brl_abs, brl_ceil, brl_floor, brl_max, brl_min, brl_round, brl_concat, brl_to_string_int, brl_to_string_float, brl_str_replace, brl_str_contains, brl_format, Rng};
+pub use choice::{Choice, ChoiceError, ChoiceId, ChoiceRecord, ChoiceState};
+pub use dice::{DiceError, DiceExpr, DiceRoll};
+pub use decision::{DecisionPolicy, FirstOptionPolicy, RandomPolicy, ScriptedPolicy, WeightedPolicy};
+pub use dynamic::{ComponentSchema, DynamicStorage, FieldSchema};
+pub use event::{Event, EventFields, EventId};
+pub use event_schema::{EventFieldSchema, EventSchema, EventSchemaError, EventSchemaRegistry, ValidationMode};
+pub use format::{format_template, FormatSpec};
+pub use interning::{InternedString, LiveStrings, MarkStrings, StringInterner};
+pub use interpreter::Interpreter;
+pub use locale::{Locales, PluralCategory, Translation};
+pub use loot::{LootDrop, LootTable, LootTables};
+pub use phase::{Phase, PhaseTable};
+pub use rng::RngState;
+pub use time_scale::TimeScales;
 pub use timeline::Timeline;
-pub use value::{EntityId, EntitySnapshot, GameStateSnapshot, Value, NO_ENTITY};
+pub use value::{EntityId, EntitySnapshot, EventSnapshot, GameStateSnapshot, UpcomingEvents, Value, ValueType, NO_ENTITY};
+pub use value_ops::ValueError;
 pub use world::{ComponentStorage, TypedStorage, World};
 
+use std::collections::BTreeMap;
+
+/// Look up a named stream, forking it from `master` on first use.
+fn stream<'a>(streams: &'a mut BTreeMap<String, Rng>, master: &Rng, label: &str) -> &'a mut Rng {
+    if !streams.contains_key(label) {
+        streams.insert(label.to_string(), master.fork(label));
+    }
+    streams.get_mut(label).unwrap()
+}
+
+/// Number of upcoming events included in `get_state_json` snapshots.
+pub const SNAPSHOT_UPCOMING_EVENTS: usize = 16;
+
 /// The main game engine that coordinates World, Timeline, and event dispatch.
 /// Generated code provides the dispatch function and initial entity setup.
 pub struct Engine {
     pub world: World,
     pub timeline: Timeline,
     pub interner: StringInterner,
+    /// Master random stream, used by `random()` in generated code.
     pub rng: Rng,
+    /// Named streams forked from `rng`, created on first use.
+    rng_streams: BTreeMap<String, Rng>,
+    /// Loot tables, rolled through the "loot" stream.
+    pub loot: LootTables,
+    /// Locale tables used by `tr`. Presentation only: not reset with the game.
+    pub locales: Locales,
+    pub choices: ChoiceState,
+    decision_policy: Option<Box<dyn DecisionPolicy>>,
     max_while_iterations: u32,
+    cancel_events_on_despawn: bool,
+    paused: bool,
+    /// Collect dynamic strings when more than this many are interned.
+    string_collection_threshold: Option<usize>,
+    /// Twice the dynamic strings left by the last collection; automatic
+    /// collection waits until the count also exceeds this.
+    string_collection_floor: usize,
 }
 
 impl Engine {
@@ -39,7 +98,16 @@ impl Engine {
             timeline: Timeline::new(),
             interner: StringInterner::new(),
             rng: Rng::new(42),
+            rng_streams: BTreeMap::new(),
+            loot: LootTables::new(),
+            locales: Locales::new(),
+            choices: ChoiceState::new(),
+            decision_policy: None,
             max_while_iterations: 10_000,
+            cancel_events_on_despawn: false,
+            paused: false,
+            string_collection_threshold: None,
+            string_collection_floor: 0,
         }
     }
 
@@ -50,7 +118,16 @@ impl Engine {
             timeline: Timeline::new(),
             interner: StringInterner::new(),
             rng: Rng::new(seed),
+            rng_streams: BTreeMap::new(),
+            loot: LootTables::new(),
+            locales: Locales::new(),
+            choices: ChoiceState::new(),
+            decision_policy: None,
             max_while_iterations: 10_000,
+            cancel_events_on_despawn: false,
+            paused: false,
+            string_collection_threshold: None,
+            string_collection_floor: 0,
         }
     }
 
@@ -64,6 +141,58 @@ impl Engine {
         self.timeline.has_events()
     }
 
+    /// Event schema violations recorded so far, as readable messages.
+    pub fn event_schema_violations(&self) -> Vec<String> {
+        self.timeline
+            .schemas
+            .violations()
+            .iter()
+            .map(|v| v.describe(&self.interner))
+            .collect()
+    }
+
+    /// Despawn an entity. If `set_cancel_events_on_despawn(true)` was called,
+    /// also cancel every pending event that references it, so e.g. a dead
+    /// hero's queued attacks never fire. Returns the number of events cancelled.
+    pub fn despawn(&mut self, entity: EntityId) -> usize {
+        self.world.despawn(entity);
+        self.timeline.time_scales.remove_entity(entity);
+        if self.cancel_events_on_despawn {
+            self.timeline.cancel_referencing(entity)
+        } else {
+            0
+        }
+    }
+
+    /// Choose whether `despawn` cancels events referencing the entity.
+    pub fn set_cancel_events_on_despawn(&mut self, enabled: bool) {
+        self.cancel_events_on_despawn = enabled;
+    }
+
+    /// Collect dynamic strings automatically, before popping an event, once
+    /// more than `threshold` are interned and the count has doubled since the
+    /// last collection, so a large live set does not trigger a collection on
+    /// every event. `None` (the default) leaves collection to explicit
+    /// `collect_strings` calls.
+    pub fn set_string_collection_threshold(&mut self, threshold: Option<usize>) {
+        self.string_collection_threshold = threshold;
+        self.string_collection_floor = 0;
+    }
+
+    /// Free dynamic strings that no component, pending event, pending choice
+    /// or registry still holds. Returns the number freed. Only call this
+    /// between events: an `InternedString` held in a rule's local variable is
+    /// invisible to the collector.
+    pub fn collect_strings(&mut self) -> usize {
+        let mut live = LiveStrings::new();
+        self.world.mark_strings(&mut live);
+        self.timeline.mark_strings(&mut live);
+        self.choices.mark_strings(&mut live);
+        let freed = self.interner.collect(&live);
+        self.string_collection_floor = 2 * self.interner.dynamic_len();
+        freed
+    }
+
     /// Get the max while loop iterations guard.
     pub fn max_while_iterations(&self) -> u32 {
         self.max_while_iterations
@@ -73,10 +202,207 @@ impl Engine {
     pub fn reset(&mut self) {
         self.world.reset();
         self.timeline.reset();
+        self.choices.reset();
+        self.loot.reset_pity();
+        self.paused = false;
+    }
+
+    /// The named random stream `label` ("combat", "loot", "encounters", ...),
+    /// forked from the master stream's seed on first use. Draws from one
+    /// stream never shift the rolls of another. Decision policies use "ai".
+    pub fn rng_stream(&mut self, label: &str) -> &mut Rng {
+        stream(&mut self.rng_streams, &self.rng, label)
+    }
+
+    /// Roll a loot table with the "loot" stream. Unknown tables drop nothing.
+    pub fn roll_loot(&mut self, table: &str) -> Vec<LootDrop> {
+        let rng = stream(&mut self.rng_streams, &self.rng, "loot");
+        self.loot.roll(table, rng)
+    }
+
+    /// Save the state of the master stream and every named stream.
+    pub fn rng_state(&self) -> RngState {
+        RngState {
+            master: self.rng.clone(),
+            streams: self.rng_streams.clone(),
+        }
+    }
+
+    /// Restore streams saved with `rng_state`. Streams not in the saved
+    /// state are forked afresh on next use.
+    pub fn restore_rng_state(&mut self, state: RngState) {
+        self.rng = state.master;
+        self.rng_streams = state.streams;
+    }
+
+    /// Pause simulated time: `next_event` returns nothing until `resume`.
+    /// Events can still be scheduled while paused.
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    pub fn resume(&mut self) {
+        self.paused = false;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Pop the next event, unless the engine is paused or a choice is waiting
+    /// for an answer. Generated `step` functions use this instead of
+    /// `timeline.pop()` so the simulation suspends on pending choices.
+    pub fn next_event(&mut self) -> Option<Event> {
+        if self.paused || self.choices.is_suspended() {
+            return None;
+        }
+        // Between events is a safe point: no rule holds strings in locals.
+        if self
+            .string_collection_threshold
+            .is_some_and(|t| self.interner.dynamic_len() > t.max(self.string_collection_floor))
+        {
+            self.collect_strings();
+        }
+        self.timeline.pop()
+    }
+
+    /// Raise a choice for `source` between `options`, `default_option` being
+    /// the answer of `submit_default_choice`.
+    /// Once answered, `resolve_event` is scheduled immediately with the fields
+    /// `choice` (the choice ID), `index` and `value` (the chosen option).
+    /// If a loaded replay holds the answer, or a decision policy is installed,
+    /// the choice resolves right away; otherwise the engine suspends until
+    /// `submit_choice` is called.
+    ///
+    /// Fails without raising anything if `options` is empty or
+    /// `default_option` is out of range.
+    pub fn raise_choice(
+        &mut self,
+        kind: InternedString,
+        source: EntityId,
+        options: Vec<Value>,
+        default_option: usize,
+        resolve_event: InternedString,
+    ) -> Result<ChoiceId, ChoiceError> {
+        if options.is_empty() {
+            return Err(ChoiceError::NoOptions);
+        }
+        if default_option >= options.len() {
+            return Err(ChoiceError::OptionOutOfRange {
+                index: default_option,
+                len: options.len(),
+            });
+        }
+        let choice = Choice {
+            id: self.choices.next_id(),
+            kind,
+            source,
+            options,
+            default_option,
+            resolve_event,
+            raised_at: self.timeline.get_time(),
+        };
+        let id = choice.id;
+        let mut index = self.choices.take_replay(id, choice.options.len());
+        if index.is_none()
+            && let Some(policy) = self.decision_policy.as_mut()
+        {
+            let rng = stream(&mut self.rng_streams, &self.rng, "ai");
+            let decided = policy.decide(&choice, rng);
+            index = Some(decided.min(choice.options.len() - 1));
+        }
+        match index {
+            Some(index) => {
+                self.choices.record(id, index);
+                self.schedule_choice_resolution(&choice, index);
+            }
+            None => self.choices.push(choice),
+        }
+        Ok(id)
+    }
+
+    /// Answer every future choice with `policy` instead of suspending.
+    pub fn set_decision_policy(&mut self, policy: Box<dyn DecisionPolicy>) {
+        self.decision_policy = Some(policy);
+    }
+
+    /// Remove the decision policy; future choices suspend the engine again.
+    pub fn clear_decision_policy(&mut self) {
+        self.decision_policy = None;
+    }
+
+    /// Name of the installed decision policy, if any.
+    pub fn decision_policy_name(&self) -> Option<&str> {
+        self.decision_policy.as_ref().map(|p| p.name())
+    }
+
+    /// The oldest choice waiting for an answer.
+    pub fn pending_choice(&self) -> Option<&Choice> {
+        self.choices.pending()
+    }
+
+    /// True while a choice is waiting for an answer.
+    pub fn is_suspended(&self) -> bool {
+        self.choices.is_suspended()
+    }
+
+    /// Answer the oldest pending choice with the option at `index` and resume.
+    pub fn submit_choice(&mut self, index: usize) -> Result<ChoiceId, ChoiceError> {
+        let choice = self.choices.answer(index)?;
+        self.schedule_choice_resolution(&choice, index);
+        Ok(choice.id)
+    }
+
+    /// Answer the oldest pending choice with its default option.
+    pub fn submit_default_choice(&mut self) -> Result<ChoiceId, ChoiceError> {
+        let index = self
+            .choices
+            .pending()
+            .ok_or(ChoiceError::NoPendingChoice)?
+            .default_option;
+        self.submit_choice(index)
+    }
+
+    /// Answers given so far, for deterministic replay.
+    pub fn choice_log(&self) -> &[ChoiceRecord] {
+        self.choices.log()
+    }
+
+    /// Replay recorded answers as the same choices are raised again.
+    pub fn load_choice_replay(&mut self, records: Vec<ChoiceRecord>) {
+        self.choices.load_replay(records);
+    }
+
+    fn schedule_choice_resolution(&mut self, choice: &Choice, index: usize) {
+        let choice_field = self.interner.intern("choice");
+        let index_field = self.interner.intern("index");
+        let value_field = self.interner.intern("value");
+        let event = Event::new(choice.resolve_event)
+            .with_source(choice.source)
+            .with_field(choice_field, Value::Integer(choice.id as i64))
+            .with_field(index_field, Value::Integer(index as i64))
+            .with_field(value_field, choice.options[index].clone());
+        self.timeline.schedule_immediate(event);
+    }
+
+    /// A page of upcoming events in firing order, for debugging UIs.
+    pub fn upcoming_events(&self, offset: usize, limit: usize) -> UpcomingEvents {
+        UpcomingEvents {
+            total: self.timeline.len(),
+            offset,
+            events: self
+                .timeline
+                .upcoming(offset, limit)
+                .into_iter()
+                .map(|e| e.snapshot(&self.interner))
+                .collect(),
+        }
     }
 
     /// Export the game state as a JSON string.
     /// This is the primary mechanism for the JS wrapper to read state.
+    /// Schema-defined (dynamic) components are added after the typed ones
+    /// produced by `entity_serializer`.
     pub fn get_state_json<F>(&self, entity_serializer: F) -> String
     where
         F: Fn(&World, &StringInterner, EntityId) -> std::collections::HashMap<String, std::collections::HashMap<String, serde_json::Value>>,
@@ -88,7 +414,15 @@ impl Engine {
             .map(|&id| EntitySnapshot {
                 id,
                 variable: self.world.get_variable_name(id).map(|s| s.to_string()),
-                components: entity_serializer(&self.world, &self.interner, id),
+                components: {
+                    let mut components = entity_serializer(&self.world, &self.interner, id);
+                    for (name, storage) in self.world.dynamic_storages() {
+                        if let Some(fields) = storage.to_json(id, &self.interner) {
+                            components.insert(name.to_string(), fields);
+                        }
+                    }
+                    components
+                },
             })
             .collect();
 
@@ -96,6 +430,7 @@ impl Engine {
             time: self.timeline.get_time(),
             entities,
             pending_events: self.timeline.len(),
+            upcoming_events: self.upcoming_events(0, SNAPSHOT_UPCOMING_EVENTS).events,
         };
 
         serde_json::to_string(&snapshot).unwrap_or_else(|_| "{}".to_string())
@@ -135,4 +470,291 @@ mod tests {
         engine.reset();
         assert!(!engine.has_events());
     }
+
+    #[test]
+    fn test_state_json_includes_dynamic_components() {
+        let mut engine = Engine::new();
+        engine
+            .world
+            .register_dynamic_component(ComponentSchema::new("Mood").with_field("level", ValueType::Integer));
+        let e = engine.world.spawn();
+        engine.world.dynamic_storage_mut("Mood").unwrap().insert_default(e);
+        let json = engine.get_state_json(|_, _, _| std::collections::HashMap::new());
+        let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed["entities"][0]["components"]["Mood"]["level"], serde_json::json!(0));
+        assert_eq!(parsed["upcoming_events"], serde_json::json!([]));
+    }
+
+    #[test]
+    fn test_state_json_includes_upcoming_events() {
+        let mut engine = Engine::new();
+        let attack = engine.interner.intern("Attack");
+        let damage = engine.interner.intern("damage");
+        for i in 0..(SNAPSHOT_UPCOMING_EVENTS + 4) {
+            let event = Event::new(attack).with_source(1).with_field(damage, Value::Integer(i as i64));
+            engine.timeline.schedule_delay(100.0 - i as f64, event);
+        }
+        let json = engine.get_state_json(|_, _, _| std::collections::HashMap::new());
+        let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed["pending_events"], serde_json::json!(SNAPSHOT_UPCOMING_EVENTS + 4));
+        let upcoming = parsed["upcoming_events"].as_array().unwrap();
+        assert_eq!(upcoming.len(), SNAPSHOT_UPCOMING_EVENTS);
+        assert_eq!(upcoming[0]["type"], serde_json::json!("Attack"));
+        assert_eq!(upcoming[0]["time"], serde_json::json!(81.0));
+        assert_eq!(upcoming[0]["fields"]["damage"], serde_json::json!(SNAPSHOT_UPCOMING_EVENTS + 3));
+
+        let page = engine.upcoming_events(18, 5);
+        assert_eq!((page.total, page.offset, page.events.len()), (20, 18, 2));
+    }
+
+    #[test]
+    fn test_scheduling_checks_event_schemas() {
+        let mut engine = Engine::new();
+        engine.timeline.schemas.set_mode(ValidationMode::Record);
+        engine
+            .timeline
+            .schemas
+            .register(EventSchema::new("Damage").with_field("amount", ValueType::Integer), &mut engine.interner);
+        let damage = engine.interner.intern("Damage");
+        let amount = engine.interner.intern("amount");
+        let amonut = engine.interner.intern("amonut");
+        engine
+            .timeline
+            .schedule_immediate(Event::new(damage).with_field(amount, Value::Integer(3)));
+        assert!(engine.event_schema_violations().is_empty());
+        engine.timeline.schedule_delay(
+            1.0,
+            Event::new(damage).with_field(amount, Value::Integer(3)).with_field(amonut, Value::Integer(1)),
+        );
+        assert_eq!(engine.event_schema_violations(), vec!["event 'Damage' has no field 'amonut'"]);
+    }
+
+    #[test]
+    fn test_rng_streams_are_independent_and_restorable() {
+        let mut engine = Engine::with_seed(9);
+        let first_loot = engine.rng_stream("loot").random();
+
+        let mut other = Engine::with_seed(9);
+        other.rng.random();
+        other.rng_stream("combat").random();
+        assert_eq!(other.rng_stream("loot").random(), first_loot);
+
+        let saved = serde_json::to_string(&engine.rng_state()).unwrap();
+        let expected = (engine.rng.random(), engine.rng_stream("loot").random());
+        engine.restore_rng_state(serde_json::from_str(&saved).unwrap());
+        assert_eq!((engine.rng.random(), engine.rng_stream("loot").random()), expected);
+    }
+
+    #[test]
+    fn test_roll_loot_uses_loot_stream() {
+        let tables = r#"[{"name": "chest", "entries": [{"item": "a"}, {"item": "b"}, {"item": "c"}]}]"#;
+        let mut engine = Engine::with_seed(3);
+        engine.loot.load_json(tables).unwrap();
+        let mut other = Engine::with_seed(3);
+        other.loot.load_json(tables).unwrap();
+        other.rng.random();
+
+        let drops: Vec<_> = (0..5).map(|_| engine.roll_loot("chest")).collect();
+        let other_drops: Vec<_> = (0..5).map(|_| other.roll_loot("chest")).collect();
+        assert_eq!(drops, other_drops);
+    }
+
+    #[test]
+    fn test_pause_and_resume() {
+        let mut engine = Engine::new();
+        let tick = engine.interner.intern("Tick");
+        engine.timeline.schedule_delay(1.0, Event::new(tick));
+        engine.pause();
+        assert!(engine.next_event().is_none());
+        assert_eq!(engine.get_time(), 0.0);
+        engine.resume();
+        assert!(engine.next_event().is_some());
+        assert_eq!(engine.get_time(), 1.0);
+    }
+
+    #[test]
+    fn test_despawn_optionally_cancels_events() {
+        let mut engine = Engine::new();
+        let attack = engine.interner.intern("Attack");
+        let hero = engine.world.spawn();
+        let enemy = engine.world.spawn();
+        engine.timeline.schedule_delay(1.0, Event::new(attack).with_source(hero).with_target(enemy));
+        engine.timeline.schedule_delay(1.0, Event::new(attack).with_source(enemy).with_target(hero));
+
+        assert_eq!(engine.despawn(enemy), 0);
+        assert_eq!(engine.timeline.len(), 2);
+
+        engine.set_cancel_events_on_despawn(true);
+        assert_eq!(engine.despawn(hero), 2);
+        assert!(!engine.has_events());
+    }
+
+    #[test]
+    fn test_choice_suspends_and_resumes() {
+        let mut engine = Engine::new();
+        let kind = engine.interner.intern("select_path");
+        let resolved = engine.interner.intern("PathChosen");
+        let tick = engine.interner.intern("Tick");
+        engine.timeline.schedule_delay(1.0, Event::new(tick));
+
+        let id = engine
+            .raise_choice(kind, 3, vec![Value::Integer(10), Value::Integer(20)], 0, resolved)
+            .unwrap();
+        assert!(engine.is_suspended());
+        assert_eq!(engine.pending_choice().unwrap().id, id);
+        assert!(engine.next_event().is_none());
+
+        assert_eq!(engine.submit_choice(1), Ok(id));
+        let event = engine.next_event().unwrap();
+        assert_eq!(event.event_type, resolved);
+        assert_eq!(event.source, 3);
+        let value = engine.interner.intern("value");
+        assert_eq!(*event.get_field(value), Value::Integer(20));
+        assert_eq!(engine.next_event().unwrap().event_type, tick);
+        assert_eq!(engine.choice_log(), &[ChoiceRecord { id, index: 1 }]);
+    }
+
+    #[test]
+    fn test_choice_rejects_unanswerable() {
+        let mut engine = Engine::new();
+        let kind = engine.interner.intern("select_path");
+        let resolved = engine.interner.intern("PathChosen");
+        let tick = engine.interner.intern("Tick");
+        engine.timeline.schedule_delay(1.0, Event::new(tick));
+
+        assert_eq!(engine.raise_choice(kind, 1, vec![], 0, resolved), Err(ChoiceError::NoOptions));
+        assert_eq!(
+            engine.raise_choice(kind, 1, vec![Value::Integer(0)], 1, resolved),
+            Err(ChoiceError::OptionOutOfRange { index: 1, len: 1 })
+        );
+        assert!(!engine.is_suspended());
+        assert_eq!(engine.next_event().unwrap().event_type, tick);
+        // Rejected choices do not use up IDs.
+        assert_eq!(engine.raise_choice(kind, 1, vec![Value::Integer(0)], 0, resolved), Ok(1));
+    }
+
+    #[test]
+    fn test_choice_replay() {
+        let mut engine = Engine::new();
+        let kind = engine.interner.intern("select_loot");
+        let resolved = engine.interner.intern("LootChosen");
+        engine.load_choice_replay(vec![ChoiceRecord { id: 1, index: 2 }]);
+
+        engine.raise_choice(kind, 1, vec![Value::Integer(0), Value::Integer(1), Value::Integer(2)], 0, resolved).unwrap();
+        assert!(!engine.is_suspended());
+        let index = engine.interner.intern("index");
+        assert_eq!(*engine.next_event().unwrap().get_field(index), Value::Integer(2));
+
+        // Replay exhausted: the next choice waits for the host.
+        engine.raise_choice(kind, 1, vec![Value::Integer(0), Value::Integer(1)], 1, resolved).unwrap();
+        assert!(engine.is_suspended());
+        assert_eq!(engine.submit_default_choice(), Ok(2));
+        assert_eq!(engine.choice_log().last(), Some(&ChoiceRecord { id: 2, index: 1 }));
+    }
+
+    #[test]
+    fn test_decision_policy_resolves_without_suspending() {
+        let mut engine = Engine::new();
+        let kind = engine.interner.intern("select_skill");
+        let resolved = engine.interner.intern("SkillChosen");
+        engine.set_decision_policy(Box::new(ScriptedPolicy::new(vec![1, 9])));
+        assert_eq!(engine.decision_policy_name(), Some("scripted"));
+
+        let options = vec![Value::Integer(0), Value::Integer(1)];
+        engine.raise_choice(kind, 1, options.clone(), 0, resolved).unwrap();
+        engine.raise_choice(kind, 1, options.clone(), 0, resolved).unwrap();
+        assert!(!engine.is_suspended());
+        // The out-of-range scripted answer is clamped to the last option.
+        assert_eq!(
+            engine.choice_log(),
+            &[ChoiceRecord { id: 1, index: 1 }, ChoiceRecord { id: 2, index: 1 }]
+        );
+
+        engine.clear_decision_policy();
+        engine.raise_choice(kind, 1, options, 0, resolved).unwrap();
+        assert!(engine.is_suspended());
+    }
+
+    #[derive(Clone, Default)]
+    struct Label {
+        text: InternedString,
+    }
+
+    impl MarkStrings for Label {
+        fn mark_strings(&self, live: &mut LiveStrings) {
+            self.text.mark_strings(live);
+        }
+    }
+
+    #[test]
+    fn test_collect_strings_keeps_live_references() {
+        let mut engine = Engine::new();
+        let hit = engine.interner.intern("Hit");
+        engine.world.register_component_strings::<Label>();
+        engine
+            .world
+            .register_dynamic_component(ComponentSchema::new("Note").with_field("text", ValueType::String));
+        engine.interner.mark_static();
+
+        let e = engine.world.spawn();
+        let label = engine.interner.intern("Sir Aldric");
+        engine.world.insert(e, Label { text: label });
+        let note = engine.interner.intern("owes 12 gold");
+        engine.world.dynamic_storage_mut("Note").unwrap().insert_fields(e, vec![("text", Value::String(note))]);
+        let message = engine.interner.intern("hit for 7");
+        let field = engine.interner.intern("message");
+        engine.timeline.schedule_delay(1.0, Event::new(hit).with_field(field, Value::List(vec![Value::String(message)])));
+        for n in 0..100 {
+            engine.interner.intern(&n.to_string());
+        }
+
+        assert_eq!(engine.collect_strings(), 100);
+        assert_eq!(engine.interner.dynamic_len(), 4);
+        assert_eq!(engine.interner.resolve(engine.world.get::<Label>(e).text), "Sir Aldric");
+        assert_eq!(engine.interner.resolve(note), "owes 12 gold");
+        let event = engine.next_event().unwrap();
+        assert_eq!(event.get_field(field), &Value::List(vec![Value::String(message)]));
+        assert_eq!(engine.interner.resolve(message), "hit for 7");
+        assert!(engine.interner.is_static(hit));
+
+        // Automatic collection runs before the next event is popped.
+        engine.set_string_collection_threshold(Some(3));
+        engine.timeline.schedule_delay(1.0, Event::new(hit));
+        engine.next_event();
+        assert_eq!(engine.interner.dynamic_len(), 2);
+        assert_eq!(engine.interner.lookup("hit for 7"), None);
+    }
+
+    #[test]
+    fn test_string_collection_backs_off_with_live_strings() {
+        let mut engine = Engine::new();
+        let tick = engine.interner.intern("Tick");
+        engine.world.register_component_strings::<Label>();
+        engine.interner.mark_static();
+        for n in 0..10 {
+            let e = engine.world.spawn();
+            let text = engine.interner.intern(&format!("hero {}", n));
+            engine.world.insert(e, Label { text });
+        }
+        for _ in 0..4 {
+            engine.timeline.schedule_delay(1.0, Event::new(tick));
+        }
+        engine.set_string_collection_threshold(Some(3));
+
+        // The first collection keeps the 10 labels; the next waits for 20.
+        engine.interner.intern("garbage 0");
+        engine.next_event();
+        assert_eq!(engine.interner.dynamic_len(), 10);
+        for n in 1..=10 {
+            engine.interner.intern(&format!("garbage {}", n));
+        }
+        engine.next_event();
+        assert_eq!(engine.interner.dynamic_len(), 20);
+        assert!(engine.interner.lookup("garbage 1").is_some());
+
+        engine.interner.intern("garbage 11");
+        engine.next_event();
+        assert_eq!(engine.interner.dynamic_len(), 10);
+        assert_eq!(engine.interner.lookup("garbage 1"), None);
+    }
 }
@@ -0,0 +1,319 @@
+//! Localization tables.
+//!
+//! User-visible text in BRL used to be English literals. A locale table maps
+//! keys to translated templates (see `format` for the placeholder syntax);
+//! an entry can instead hold one template per plural category, picked by a
+//! count. `tr` looks keys up in the active locale, falling back from a region
+//! ("de-AT") to its language ("de"), then to the fallback locale, then to the
+//! key itself.
+//!
+//! Tables load from JSON:
+//!
+//! ```json
+//! {"greeting": "Hallo {hero}!",
+//!  "gold": {"one": "{0} Goldstück", "other": "{0} Goldstücke"}}
+//! ```
+//!
+//! or from TSV, one `key<TAB>template` per line, plural forms as `key[one]`,
+//! `#` starting a comment line and `\t`, `\n`, `\\` as escapes.
+//!
+//! Switching locale only changes the text produced. Lookups never draw random
+//! numbers or schedule events, so a run takes the same course in any locale
+//! as long as rules do not branch on translated text.
+
+use std::collections::{BTreeMap, HashMap};
+
+use serde::Deserialize;
+
+use crate::interning::{InternedString, StringInterner};
+
+/// CLDR plural category.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PluralCategory {
+    Zero,
+    One,
+    Two,
+    Few,
+    Many,
+    Other,
+}
+
+impl PluralCategory {
+    pub fn parse(name: &str) -> Option<PluralCategory> {
+        Some(match name {
+            "zero" => PluralCategory::Zero,
+            "one" => PluralCategory::One,
+            "two" => PluralCategory::Two,
+            "few" => PluralCategory::Few,
+            "many" => PluralCategory::Many,
+            "other" => PluralCategory::Other,
+            _ => return None,
+        })
+    }
+
+    /// Category of `n` in a language ("ru", "pt-BR", ...). Covers the common
+    /// rule families; unknown languages use the English rule.
+    pub fn of(language: &str, n: f64) -> PluralCategory {
+        use PluralCategory::*;
+        let language = language.split(['-', '_']).next().unwrap_or("");
+        let integer = n.fract() == 0.0 && n.abs() < 1e15;
+        let i = n.abs() as u64;
+        let (mod10, mod100) = (i % 10, i % 100);
+        match language {
+            "ja" | "zh" | "ko" | "th" | "vi" | "id" | "ms" | "tr" => Other,
+            "fr" if n.abs() < 2.0 => One,
+            "fr" => Other,
+            "ru" | "uk" | "be" | "sr" | "hr" | "bs" if integer => {
+                if mod10 == 1 && mod100 != 11 {
+                    One
+                } else if (2..=4).contains(&mod10) && !(12..=14).contains(&mod100) {
+                    Few
+                } else {
+                    Many
+                }
+            }
+            "pl" if integer => {
+                if i == 1 {
+                    One
+                } else if (2..=4).contains(&mod10) && !(12..=14).contains(&mod100) {
+                    Few
+                } else {
+                    Many
+                }
+            }
+            "cs" | "sk" if integer => match i {
+                1 => One,
+                2..=4 => Few,
+                _ => Other,
+            },
+            "ar" if integer => match (i, mod100) {
+                (0, _) => Zero,
+                (1, _) => One,
+                (2, _) => Two,
+                (_, 3..=10) => Few,
+                (_, 11..=99) => Many,
+                _ => Other,
+            },
+            "ru" | "uk" | "be" | "sr" | "hr" | "bs" | "pl" | "cs" | "sk" | "ar" => Other,
+            _ if integer && i == 1 => One,
+            _ => Other,
+        }
+    }
+}
+
+/// A translated entry: one template, or one per plural category.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(untagged)]
+pub enum Translation {
+    Text(String),
+    Plural(BTreeMap<PluralCategory, String>),
+}
+
+impl Translation {
+    /// Template for a count. Plural entries fall back to `other`, then to
+    /// their first form; without a count they use `other`.
+    pub fn template(&self, language: &str, count: Option<f64>) -> &str {
+        match self {
+            Translation::Text(text) => text,
+            Translation::Plural(forms) => {
+                let category = count.map_or(PluralCategory::Other, |n| PluralCategory::of(language, n));
+                forms
+                    .get(&category)
+                    .or_else(|| forms.get(&PluralCategory::Other))
+                    .or_else(|| forms.values().next())
+                    .map_or("", String::as_str)
+            }
+        }
+    }
+}
+
+/// Locale tables and the active locale.
+pub struct Locales {
+    tables: HashMap<String, HashMap<String, Translation>>,
+    active: String,
+    fallback: String,
+}
+
+impl Default for Locales {
+    fn default() -> Self {
+        Locales {
+            tables: HashMap::new(),
+            active: "en".to_string(),
+            fallback: "en".to_string(),
+        }
+    }
+}
+
+impl Locales {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the entries of a JSON object to a locale, replacing existing keys.
+    /// Returns the number of entries loaded.
+    pub fn load_json(&mut self, locale: &str, json: &str) -> Result<usize, String> {
+        let entries: HashMap<String, Translation> = serde_json::from_str(json).map_err(|e| e.to_string())?;
+        let count = entries.len();
+        self.tables.entry(locale.to_string()).or_default().extend(entries);
+        Ok(count)
+    }
+
+    /// Add the entries of a TSV table to a locale, replacing existing keys.
+    /// Returns the number of lines loaded.
+    pub fn load_tsv(&mut self, locale: &str, tsv: &str) -> Result<usize, String> {
+        let mut parsed = Vec::new();
+        for (n, line) in tsv.lines().enumerate() {
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let Some((key, text)) = line.split_once('\t') else {
+                return Err(format!("line {}: expected key<TAB>text", n + 1));
+            };
+            let text = unescape(text);
+            let plural = key.strip_suffix(']').and_then(|k| k.split_once('['));
+            match plural {
+                Some((key, category)) => {
+                    let category = PluralCategory::parse(category)
+                        .ok_or_else(|| format!("line {}: unknown plural category '{}'", n + 1, category))?;
+                    parsed.push((key.to_string(), Some(category), text));
+                }
+                None => parsed.push((key.to_string(), None, text)),
+            }
+        }
+        let count = parsed.len();
+        let table = self.tables.entry(locale.to_string()).or_default();
+        for (key, category, text) in parsed {
+            match category {
+                None => {
+                    table.insert(key, Translation::Text(text));
+                }
+                Some(category) => {
+                    let entry = table.entry(key).or_insert_with(|| Translation::Plural(BTreeMap::new()));
+                    if let Translation::Text(_) = entry {
+                        *entry = Translation::Plural(BTreeMap::new());
+                    }
+                    if let Translation::Plural(forms) = entry {
+                        forms.insert(category, text);
+                    }
+                }
+            }
+        }
+        Ok(count)
+    }
+
+    /// Switch the active locale. Returns false (and keeps the current one)
+    /// unless a table for it, or for its language, is loaded.
+    pub fn set_locale(&mut self, locale: &str) -> bool {
+        if self.tables.contains_key(locale) || self.tables.contains_key(language(locale)) {
+            self.active = locale.to_string();
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn locale(&self) -> &str {
+        &self.active
+    }
+
+    /// Locale used for keys the active locale lacks ("en" by default).
+    pub fn set_fallback(&mut self, locale: &str) {
+        self.fallback = locale.to_string();
+    }
+
+    /// Loaded locale names, sorted.
+    pub fn available(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.tables.keys().map(String::as_str).collect();
+        names.sort();
+        names
+    }
+
+    /// Template for a key in the active locale, following the fallback
+    /// chain; None if no table has the key.
+    pub fn translate(&self, key: &str, count: Option<f64>) -> Option<&str> {
+        [self.active.as_str(), language(&self.active), self.fallback.as_str()]
+            .into_iter()
+            .find_map(|locale| {
+                let translation = self.tables.get(locale)?.get(key)?;
+                Some(translation.template(locale, count))
+            })
+    }
+
+    /// Text of an interned string for display: its translation if it is a
+    /// key of the active locale, otherwise the string itself.
+    pub fn resolve<'a>(&'a self, id: InternedString, interner: &'a StringInterner) -> &'a str {
+        let text = interner.resolve(id);
+        self.translate(text, None).unwrap_or(text)
+    }
+}
+
+/// Language part of a locale: "de" for "de-AT".
+fn language(locale: &str) -> &str {
+    locale.split(['-', '_']).next().unwrap_or(locale)
+}
+
+fn unescape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('t') => out.push('\t'),
+            Some('n') => out.push('\n'),
+            Some(other) => out.push(other),
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plural_rules() {
+        use PluralCategory::*;
+        assert_eq!(PluralCategory::of("en", 1.0), One);
+        assert_eq!(PluralCategory::of("en-GB", 0.0), Other);
+        assert_eq!(PluralCategory::of("en", 1.5), Other);
+        assert_eq!(PluralCategory::of("fr", 0.0), One);
+        assert_eq!(PluralCategory::of("fr", 1.5), One);
+        let ru: Vec<_> = [1.0, 2.0, 5.0, 11.0, 21.0, 22.0, 112.0, 1.5].iter().map(|n| PluralCategory::of("ru", *n)).collect();
+        assert_eq!(ru, vec![One, Few, Many, Many, One, Few, Many, Other]);
+        assert_eq!(PluralCategory::of("pl", 21.0), Many);
+        assert_eq!(PluralCategory::of("ja", 1.0), Other);
+        assert_eq!(PluralCategory::of("ar", 2.0), Two);
+    }
+
+    #[test]
+    fn test_load_and_fallback() {
+        let mut locales = Locales::new();
+        locales.load_json("en", r#"{"greeting": "Hello {0}!", "gold": {"one": "{0} gold coin", "other": "{0} gold coins"}}"#).unwrap();
+        let tsv = "# German\ngreeting\tHallo {0}!\ngold[one]\t{0} Goldstück\ngold[other]\t{0} Goldstücke\nmotto\tEins\\tZwei\n";
+        assert_eq!(locales.load_tsv("de", tsv), Ok(4));
+        assert!(locales.load_tsv("de", "no tab here").is_err());
+        assert!(locales.load_tsv("de", "x[lots]\ty").is_err());
+
+        assert_eq!(locales.translate("gold", Some(1.0)), Some("{0} gold coin"));
+        assert!(!locales.set_locale("fr"));
+        assert!(locales.set_locale("de-AT"));
+        assert_eq!(locales.translate("gold", Some(3.0)), Some("{0} Goldstücke"));
+        assert_eq!(locales.translate("motto", None), Some("Eins\tZwei"));
+        assert_eq!(locales.translate("missing", None), None);
+        assert_eq!(locales.available(), vec!["de", "en"]);
+
+        locales.load_json("en", r#"{"farewell": "Goodbye"}"#).unwrap();
+        assert_eq!(locales.translate("farewell", None), Some("Goodbye"));
+
+        let mut interner = StringInterner::new();
+        let key = interner.intern("greeting");
+        let plain = interner.intern("Goblin");
+        assert_eq!(locales.resolve(key, &interner), "Hallo {0}!");
+        assert_eq!(locales.resolve(plain, &interner), "Goblin");
+    }
+}
@@ -0,0 +1,404 @@
+//! Weighted loot tables.
+//!
+//! Enemy drops and encounter picks used to be chains of `random()`
+//! comparisons in BRL. A loot table holds weighted entries instead; each roll
+//! picks one entry, which is an item, a nested table (rolled in turn) or
+//! nothing. A table is rolled `rolls` times and its `guaranteed` entries drop
+//! every time. An entry with `pity: N` is forced after N - 1 consecutive rolls
+//! of its table that missed it, so a rare drop is never more than N rolls away.
+//!
+//! Tables load from JSON:
+//!
+//! ```json
+//! [{"name": "goblin", "rolls": 2,
+//!   "guaranteed": [{"item": "gold", "count": [3, 8]}],
+//!   "entries": [
+//!     {"weight": 60},
+//!     {"item": "dagger", "weight": 30},
+//!     {"table": "gems", "weight": 9},
+//!     {"item": "crown", "weight": 1, "pity": 50}]}]
+//! ```
+//!
+//! `weight` defaults to 1, `count` (a number or `[min, max]`) to 1 and
+//! `rolls` to 1. Tables may not nest themselves, directly or through other
+//! tables, and one roll resolves at most `MAX_PICKS` entries. Pity counters
+//! are part of the game state and can be saved and restored with
+//! `pity_counters` / `set_pity_counters`.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::rng::Rng;
+
+/// Deepest chain of nested tables followed in one roll.
+pub const MAX_DEPTH: u32 = 16;
+/// Most entries (guaranteed or picked) resolved in one roll, nested tables
+/// included; `rolls` multiply down a chain of tables.
+pub const MAX_PICKS: u32 = 10_000;
+
+/// How many of an item an entry drops.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum LootCount {
+    Fixed(u32),
+    Range([u32; 2]),
+}
+
+impl Default for LootCount {
+    fn default() -> Self {
+        LootCount::Fixed(1)
+    }
+}
+
+/// One entry of a table: an item, a nested table, or (with neither) nothing.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LootEntry {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub item: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub table: Option<String>,
+    #[serde(default = "one")]
+    pub weight: f64,
+    #[serde(default)]
+    pub count: LootCount,
+    /// Force this entry after this many rolls without it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pity: Option<u32>,
+}
+
+fn one() -> f64 {
+    1.0
+}
+
+fn one_roll() -> u32 {
+    1
+}
+
+/// A named loot table.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LootTable {
+    pub name: String,
+    #[serde(default = "one_roll")]
+    pub rolls: u32,
+    #[serde(default)]
+    pub guaranteed: Vec<LootEntry>,
+    #[serde(default)]
+    pub entries: Vec<LootEntry>,
+}
+
+/// An item that dropped.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LootDrop {
+    pub item: String,
+    pub count: u32,
+}
+
+/// All loot tables of a game, with their pity counters.
+#[derive(Default)]
+pub struct LootTables {
+    tables: HashMap<String, LootTable>,
+    /// Consecutive misses per pity entry, keyed "table#index".
+    pity: BTreeMap<String, u32>,
+}
+
+impl LootTables {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add (or replace) the tables of a JSON array, then check that every
+    /// nested table exists. Returns the number of tables loaded. On error no
+    /// table is added or replaced.
+    pub fn load_json(&mut self, json: &str) -> Result<usize, String> {
+        let loaded: Vec<LootTable> = serde_json::from_str(json).map_err(|e| e.to_string())?;
+        let count = loaded.len();
+        let mut tables = self.tables.clone();
+        for table in loaded {
+            tables.insert(table.name.clone(), table);
+        }
+        validate(&tables)?;
+        self.tables = tables;
+        Ok(count)
+    }
+
+    /// Add or replace a table.
+    pub fn insert(&mut self, table: LootTable) {
+        self.tables.insert(table.name.clone(), table);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&LootTable> {
+        self.tables.get(name)
+    }
+
+    pub fn len(&self) -> usize {
+        self.tables.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tables.is_empty()
+    }
+
+    /// Check entries: nested tables exist and do not nest themselves, weights
+    /// are finite and not negative, no entry is both an item and a table,
+    /// count ranges are ordered.
+    pub fn validate(&self) -> Result<(), String> {
+        validate(&self.tables)
+    }
+
+    /// Roll a table. Unknown tables drop nothing. Drops are listed in the
+    /// order they were rolled; the same item may appear more than once.
+    pub fn roll(&mut self, name: &str, rng: &mut Rng) -> Vec<LootDrop> {
+        let mut roller = Roller {
+            tables: &self.tables,
+            pity: &mut self.pity,
+            rng,
+            picks: 0,
+            drops: Vec::new(),
+        };
+        roller.roll(name, 0);
+        roller.drops
+    }
+
+    /// Consecutive misses of every pity entry, for saving.
+    pub fn pity_counters(&self) -> &BTreeMap<String, u32> {
+        &self.pity
+    }
+
+    /// Restore pity counters saved with `pity_counters`.
+    pub fn set_pity_counters(&mut self, counters: BTreeMap<String, u32>) {
+        self.pity = counters;
+    }
+
+    /// Forget pity progress. Tables stay loaded.
+    pub fn reset_pity(&mut self) {
+        self.pity.clear();
+    }
+}
+
+fn validate(tables: &HashMap<String, LootTable>) -> Result<(), String> {
+    let mut names: Vec<&String> = tables.keys().collect();
+    names.sort();
+    for &name in &names {
+        let table = &tables[name];
+        for entry in table.guaranteed.iter().chain(&table.entries) {
+            if entry.item.is_some() && entry.table.is_some() {
+                return Err(format!("loot table '{}': entry has both an item and a table", name));
+            }
+            if let Some(nested) = &entry.table
+                && !tables.contains_key(nested)
+            {
+                return Err(format!("loot table '{}': unknown table '{}'", name, nested));
+            }
+            if !(entry.weight >= 0.0 && entry.weight.is_finite()) {
+                return Err(format!("loot table '{}': invalid weight {}", name, entry.weight));
+            }
+            if let LootCount::Range([min, max]) = entry.count
+                && min > max
+            {
+                return Err(format!("loot table '{}': count range [{}, {}] is reversed", name, min, max));
+            }
+        }
+    }
+    // Depth-first search for a table reachable from itself.
+    let mut done: HashSet<&str> = HashSet::new();
+    for &name in &names {
+        let mut path = Vec::new();
+        if let Some(cycle) = find_cycle(tables, name, &mut path, &mut done) {
+            return Err(format!("loot table '{}' nests itself: {}", cycle[0], cycle.join(" -> ")));
+        }
+    }
+    Ok(())
+}
+
+/// The first cycle through `name`'s nested tables, as the path from the
+/// table that repeats back to itself. `done` holds tables known cycle-free.
+fn find_cycle<'a>(
+    tables: &'a HashMap<String, LootTable>,
+    name: &'a str,
+    path: &mut Vec<&'a str>,
+    done: &mut HashSet<&'a str>,
+) -> Option<Vec<&'a str>> {
+    if done.contains(name) {
+        return None;
+    }
+    if let Some(start) = path.iter().position(|&n| n == name) {
+        let mut cycle = path[start..].to_vec();
+        cycle.push(name);
+        return Some(cycle);
+    }
+    path.push(name);
+    let table = &tables[name];
+    for nested in table.guaranteed.iter().chain(&table.entries).filter_map(|e| e.table.as_deref()) {
+        if let Some(cycle) = find_cycle(tables, nested, path, done) {
+            return Some(cycle);
+        }
+    }
+    path.pop();
+    done.insert(name);
+    None
+}
+
+/// State of one `LootTables::roll`: tables are borrowed, pity counters
+/// updated in place.
+struct Roller<'a> {
+    tables: &'a HashMap<String, LootTable>,
+    pity: &'a mut BTreeMap<String, u32>,
+    rng: &'a mut Rng,
+    /// Entries resolved so far, against `MAX_PICKS`.
+    picks: u32,
+    drops: Vec<LootDrop>,
+}
+
+impl<'a> Roller<'a> {
+    fn roll(&mut self, name: &str, depth: u32) {
+        if depth >= MAX_DEPTH {
+            return;
+        }
+        let Some(table) = self.tables.get(name) else {
+            return;
+        };
+        for entry in &table.guaranteed {
+            self.resolve(entry, depth);
+        }
+        let weights: Vec<f64> = table.entries.iter().map(|e| e.weight).collect();
+        for _ in 0..table.rolls {
+            if self.picks >= MAX_PICKS {
+                return;
+            }
+            let forced = table.entries.iter().enumerate().position(|(i, e)| {
+                e.pity.is_some_and(|n| self.pity.get(&pity_key(name, i)).copied().unwrap_or(0) + 1 >= n)
+            });
+            let picked = forced.or_else(|| self.rng.weighted_index(&weights));
+            for (i, entry) in table.entries.iter().enumerate() {
+                if entry.pity.is_some() {
+                    let misses = self.pity.entry(pity_key(name, i)).or_insert(0);
+                    *misses = if picked == Some(i) { 0 } else { *misses + 1 };
+                }
+            }
+            if let Some(i) = picked {
+                self.resolve(&table.entries[i], depth);
+            }
+        }
+    }
+
+    fn resolve(&mut self, entry: &'a LootEntry, depth: u32) {
+        if self.picks >= MAX_PICKS {
+            return;
+        }
+        self.picks += 1;
+        if let Some(item) = &entry.item {
+            let count = match entry.count {
+                LootCount::Fixed(n) => n,
+                LootCount::Range([min, max]) => self.rng.random_int_range(min as i64, max as i64) as u32,
+            };
+            if count > 0 {
+                self.drops.push(LootDrop { item: item.clone(), count });
+            }
+        } else if let Some(nested) = &entry.table {
+            self.roll(nested, depth + 1);
+        }
+    }
+}
+
+fn pity_key(table: &str, index: usize) -> String {
+    format!("{}#{}", table, index)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TABLES: &str = r#"[
+        {"name": "goblin", "rolls": 2,
+         "guaranteed": [{"item": "gold", "count": [3, 8]}],
+         "entries": [
+            {"weight": 60},
+            {"item": "dagger", "weight": 30},
+            {"table": "gems", "weight": 10},
+            {"item": "crown", "weight": 0, "pity": 5}]},
+        {"name": "gems", "entries": [{"item": "ruby"}, {"item": "opal", "count": 2}]}
+    ]"#;
+
+    #[test]
+    fn test_load_and_validate() {
+        let mut loot = LootTables::new();
+        assert_eq!(loot.load_json(TABLES), Ok(2));
+        assert_eq!(loot.get("gems").unwrap().rolls, 1);
+        assert_eq!(loot.get("goblin").unwrap().guaranteed[0].count, LootCount::Range([3, 8]));
+
+        let broken = r#"[{"name": "bad", "entries": [{"table": "missing"}]}]"#;
+        assert_eq!(loot.load_json(broken), Err("loot table 'bad': unknown table 'missing'".to_string()));
+        // A failed load leaves the loaded tables untouched.
+        assert!(loot.get("bad").is_none());
+        let replaced = r#"[{"name": "gems", "entries": [{"table": "missing"}]}]"#;
+        assert!(loot.load_json(replaced).is_err());
+        assert_eq!(loot.get("gems").unwrap().entries[0].item.as_deref(), Some("ruby"));
+        assert_eq!(loot.len(), 2);
+        assert!(LootTables::new().load_json(r#"[{"name": "x", "entries": [{"weight": -1}]}]"#).is_err());
+    }
+
+    #[test]
+    fn test_reject_cycles_and_bound_work() {
+        let mut loot = LootTables::new();
+        let looped = r#"[{"name": "hoard", "rolls": 4, "entries": [{"table": "hoard"}]}]"#;
+        assert_eq!(
+            loot.load_json(looped),
+            Err("loot table 'hoard' nests itself: hoard -> hoard".to_string())
+        );
+        let indirect = r#"[
+            {"name": "a", "entries": [{"item": "x"}, {"table": "b"}]},
+            {"name": "b", "guaranteed": [{"table": "c"}]},
+            {"name": "c", "entries": [{"table": "a"}]}]"#;
+        assert_eq!(
+            loot.load_json(indirect),
+            Err("loot table 'a' nests itself: a -> b -> c -> a".to_string())
+        );
+        assert!(loot.is_empty());
+
+        // Acyclic fan-out: 10 levels of 4 rolls would resolve ~4^10 entries.
+        let mut chain: Vec<String> = (0..10)
+            .map(|i| format!(r#"{{"name": "t{}", "rolls": 4, "entries": [{{"table": "t{}"}}]}}"#, i, i + 1))
+            .collect();
+        chain.push(r#"{"name": "t10", "rolls": 4, "entries": [{"item": "coin"}]}"#.to_string());
+        loot.load_json(&format!("[{}]", chain.join(","))).unwrap();
+        let drops = loot.roll("t0", &mut Rng::new(1));
+        assert!(!drops.is_empty() && drops.len() < MAX_PICKS as usize);
+    }
+
+    #[test]
+    fn test_roll_guaranteed_nested_and_pity() {
+        let mut loot = LootTables::new();
+        loot.load_json(TABLES).unwrap();
+        let mut rng = Rng::new(4);
+        let mut crowns = 0;
+        for roll in 1..=10 {
+            let drops = loot.roll("goblin", &mut rng);
+            let gold = &drops[0];
+            assert_eq!(gold.item, "gold");
+            assert!((3..=8).contains(&gold.count));
+            assert!(drops[1..].iter().all(|d| ["dagger", "ruby", "opal", "crown"].contains(&d.item.as_str())));
+            crowns += drops.iter().filter(|d| d.item == "crown").count();
+            // Zero weight: the crown only ever drops through pity, on every
+            // fifth roll of the table (two rolls per call).
+            assert_eq!(crowns, roll * 2 / 5);
+        }
+        assert!(loot.roll("unknown", &mut rng).is_empty());
+    }
+
+    #[test]
+    fn test_pity_counters_round_trip() {
+        let mut loot = LootTables::new();
+        loot.load_json(TABLES).unwrap();
+        let mut rng = Rng::new(1);
+        loot.roll("goblin", &mut rng);
+        let saved = loot.pity_counters().clone();
+        assert_eq!(saved.get("goblin#3"), Some(&2));
+
+        let mut a = Rng::new(2);
+        let mut b = a.clone();
+        let first = loot.roll("goblin", &mut a);
+        loot.set_pity_counters(saved);
+        assert_eq!(loot.roll("goblin", &mut b), first);
+    }
+}
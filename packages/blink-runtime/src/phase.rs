@@ -0,0 +1,110 @@
+//! Phases: the order of events that fire at the same time.
+//!
+//! Without phases, events scheduled for the same time fire in scheduling
+//! order, so whether a `DotTick` resolves before an `Attack` depends on which
+//! rule happened to schedule first. Each event type can instead be mapped to a
+//! `Phase`; at equal times the timeline fires every event of an earlier phase
+//! first, and only falls back to scheduling order within a phase.
+//!
+//! The order of the phases is the order of the `Phase` variants and is the
+//! same for every game. Event types are mapped once per game, in a
+//! `PhaseTable` loaded from JSON such as
+//! `{"DotTick": "start_of_tick", "Attack": "actions", "Cleanup": "cleanup"}`.
+//! Unmapped event types run in `Phase::Actions`.
+
+use std::collections::{BTreeMap, HashMap};
+
+use serde::{Deserialize, Serialize};
+
+use crate::interning::{InternedString, LiveStrings, MarkStrings, StringInterner};
+
+/// A lane within a timestamp. Variants are declared in firing order.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Phase {
+    /// Upkeep before anyone acts: damage over time, regeneration, cooldowns.
+    StartOfTick,
+    /// Actions taken by entities. The default phase.
+    #[default]
+    Actions,
+    /// Consequences of actions: damage, buffs, triggered abilities.
+    Effects,
+    /// Death checks, despawns and bookkeeping.
+    Cleanup,
+}
+
+impl Phase {
+    /// All phases in firing order.
+    pub const ALL: [Phase; 4] = [Phase::StartOfTick, Phase::Actions, Phase::Effects, Phase::Cleanup];
+}
+
+/// Phase of every mapped event type.
+#[derive(Default)]
+pub struct PhaseTable {
+    phases: HashMap<InternedString, Phase>,
+}
+
+impl PhaseTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Map an event type to a phase, replacing any earlier mapping.
+    pub fn set(&mut self, event_type: InternedString, phase: Phase) {
+        self.phases.insert(event_type, phase);
+    }
+
+    /// Phase of an event type; `Phase::Actions` if unmapped.
+    pub fn get(&self, event_type: InternedString) -> Phase {
+        self.phases.get(&event_type).copied().unwrap_or_default()
+    }
+
+    /// Map every event type of a JSON object of event name to phase name.
+    /// Returns the number of event types mapped.
+    pub fn register_json(&mut self, json: &str, interner: &mut StringInterner) -> Result<usize, String> {
+        let phases: BTreeMap<String, Phase> = serde_json::from_str(json).map_err(|e| e.to_string())?;
+        let count = phases.len();
+        for (name, phase) in phases {
+            self.set(interner.intern(&name), phase);
+        }
+        Ok(count)
+    }
+
+    /// Export the mappings as a JSON object, in the format `register_json` reads.
+    pub fn to_json(&self, interner: &StringInterner) -> String {
+        let phases: BTreeMap<&str, Phase> = self.phases.iter().map(|(k, v)| (interner.resolve(*k), *v)).collect();
+        serde_json::to_string(&phases).unwrap_or_else(|_| "{}".to_string())
+    }
+
+    pub fn len(&self) -> usize {
+        self.phases.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.phases.is_empty()
+    }
+}
+
+impl MarkStrings for PhaseTable {
+    fn mark_strings(&self, live: &mut LiveStrings) {
+        live.extend(self.phases.keys().copied());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_register_json() {
+        let mut interner = StringInterner::new();
+        let mut table = PhaseTable::new();
+        let json = r#"{"DotTick": "start_of_tick", "Death": "cleanup"}"#;
+        assert_eq!(table.register_json(json, &mut interner), Ok(2));
+        assert_eq!(table.get(interner.intern("DotTick")), Phase::StartOfTick);
+        assert_eq!(table.get(interner.intern("Attack")), Phase::Actions);
+        assert_eq!(table.to_json(&interner), r#"{"Death":"cleanup","DotTick":"start_of_tick"}"#);
+        assert!(table.register_json(r#"{"Attack": "whenever"}"#, &mut interner).is_err());
+        assert!(Phase::ALL.windows(2).all(|w| w[0] < w[1]));
+    }
+}
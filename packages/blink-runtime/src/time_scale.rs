@@ -0,0 +1,137 @@
+//! Time scales: per-entity and per-group time dilation.
+//!
+//! Haste and Slow used to be modelled by recomputing delays in every rule
+//! that schedules an action. Instead, an entity can be given a time scale
+//! and the timeline divides the delay of every event it schedules with the
+//! entity as `source`: at scale 2.0 a 1.0 delay becomes 0.5, at scale 0.5 it
+//! becomes 2.0. Entities can also belong to a named group ("enemies",
+//! "party"), whose scale multiplies the entity's own.
+//!
+//! Only `schedule_delay` is scaled. Immediate events and events scheduled at
+//! an absolute time are not, and neither are events without a source.
+
+use std::collections::HashMap;
+
+use crate::interning::{InternedString, LiveStrings, MarkStrings};
+use crate::value::EntityId;
+
+/// Time scale factors by entity and by group. The default scale is 1.0.
+#[derive(Default)]
+pub struct TimeScales {
+    entities: HashMap<EntityId, f64>,
+    groups: HashMap<InternedString, f64>,
+    membership: HashMap<EntityId, InternedString>,
+}
+
+impl TimeScales {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether a scale factor is usable: positive and finite.
+    pub fn is_valid(scale: f64) -> bool {
+        scale > 0.0 && scale.is_finite()
+    }
+
+    /// Effective scale of an entity: its own scale times its group's.
+    pub fn scale_of(&self, entity: EntityId) -> f64 {
+        let own = self.entities.get(&entity).copied().unwrap_or(1.0);
+        let group = self
+            .membership
+            .get(&entity)
+            .and_then(|g| self.groups.get(g))
+            .copied()
+            .unwrap_or(1.0);
+        own * group
+    }
+
+    /// The entity's own scale, without its group's.
+    pub fn entity_scale(&self, entity: EntityId) -> f64 {
+        self.entities.get(&entity).copied().unwrap_or(1.0)
+    }
+
+    pub fn group_scale(&self, group: InternedString) -> f64 {
+        self.groups.get(&group).copied().unwrap_or(1.0)
+    }
+
+    pub fn group_of(&self, entity: EntityId) -> Option<InternedString> {
+        self.membership.get(&entity).copied()
+    }
+
+    /// Set an entity's own scale. A scale of 1.0 removes the entry.
+    pub(crate) fn set_entity(&mut self, entity: EntityId, scale: f64) {
+        if scale == 1.0 {
+            self.entities.remove(&entity);
+        } else {
+            self.entities.insert(entity, scale);
+        }
+    }
+
+    /// Set a group's scale. A scale of 1.0 removes the entry.
+    pub(crate) fn set_group(&mut self, group: InternedString, scale: f64) {
+        if scale == 1.0 {
+            self.groups.remove(&group);
+        } else {
+            self.groups.insert(group, scale);
+        }
+    }
+
+    /// Put an entity in a group, or take it out of its group with `None`.
+    pub(crate) fn set_membership(&mut self, entity: EntityId, group: Option<InternedString>) {
+        match group {
+            Some(group) => self.membership.insert(entity, group),
+            None => self.membership.remove(&entity),
+        };
+    }
+
+    /// Members of a group.
+    pub fn members(&self, group: InternedString) -> impl Iterator<Item = EntityId> + '_ {
+        self.membership.iter().filter(move |(_, g)| **g == group).map(|(e, _)| *e)
+    }
+
+    /// Forget an entity's scale and group, e.g. when it is despawned.
+    pub(crate) fn remove_entity(&mut self, entity: EntityId) {
+        self.entities.remove(&entity);
+        self.membership.remove(&entity);
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.entities.clear();
+        self.groups.clear();
+        self.membership.clear();
+    }
+}
+
+impl MarkStrings for TimeScales {
+    fn mark_strings(&self, live: &mut LiveStrings) {
+        live.extend(self.groups.keys().copied());
+        live.extend(self.membership.values().copied());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_entity_and_group_scales_multiply() {
+        let mut scales = TimeScales::new();
+        let enemies = InternedString(1);
+        assert_eq!(scales.scale_of(7), 1.0);
+
+        scales.set_entity(7, 2.0);
+        scales.set_membership(7, Some(enemies));
+        scales.set_membership(8, Some(enemies));
+        scales.set_group(enemies, 0.5);
+        assert_eq!(scales.scale_of(7), 1.0);
+        assert_eq!(scales.scale_of(8), 0.5);
+        let mut members: Vec<EntityId> = scales.members(enemies).collect();
+        members.sort();
+        assert_eq!(members, vec![7, 8]);
+
+        scales.remove_entity(7);
+        assert_eq!(scales.scale_of(7), 1.0);
+        assert!(!TimeScales::is_valid(0.0));
+        assert!(!TimeScales::is_valid(f64::INFINITY));
+    }
+}
@@ -1,23 +1,77 @@
-/// Timeline: binary heap priority queue for event scheduling.
-/// Events are ordered by (time, sequence) for deterministic ordering.
+//! Timeline: binary heap priority queue for event scheduling.
+//! Events are ordered by (time, phase, sequence) for deterministic ordering:
+//! at equal times, events fire phase by phase (see `phase`), and in
+//! scheduling order within a phase.
+//!
+//! The heap is indexed: every pending event's position is tracked by ID, so
+//! cancelling removes the event immediately and rescheduling moves it in
+//! place, both in O(log n).
+//!
+//! Delays are divided by the time scale of the event's source entity (see
+//! `time_scale`), so hasted entities act sooner and slowed ones later.
+
+use std::collections::HashMap;
 
 use crate::event::{Event, EventId};
+use crate::event_schema::EventSchemaRegistry;
+use crate::interning::{InternedString, LiveStrings, MarkStrings, StringInterner};
+use crate::phase::{Phase, PhaseTable};
+use crate::time_scale::TimeScales;
+use crate::value::{EntityId, EventSnapshot, NO_ENTITY};
 
 /// A scheduled event in the timeline.
 #[derive(Clone, Debug)]
 pub struct ScheduledEvent {
     pub time: f64,
+    pub phase: Phase,
     pub sequence: u64,
     pub event: Event,
 }
 
+impl ScheduledEvent {
+    /// Read-only view for debugging UIs and state export.
+    pub fn snapshot(&self, interner: &StringInterner) -> EventSnapshot {
+        EventSnapshot {
+            id: self.event.event_id,
+            event_type: interner.resolve(self.event.event_type).to_string(),
+            time: self.time,
+            phase: self.phase,
+            source: self.event.source,
+            target: self.event.target,
+            fields: self
+                .event
+                .fields
+                .iter()
+                .map(|(k, v)| (interner.resolve(*k).to_string(), v.to_json_value(interner)))
+                .collect(),
+        }
+    }
+
+    /// Ordering key: earlier time first, then earlier phase, then
+    /// scheduling order. Times use IEEE total ordering, so -0.0 fires before
+    /// 0.0 and NaN after infinity.
+    fn fires_before(&self, other: &ScheduledEvent) -> std::cmp::Ordering {
+        self.time
+            .total_cmp(&other.time)
+            .then(self.phase.cmp(&other.phase))
+            .then(self.sequence.cmp(&other.sequence))
+    }
+}
+
 /// Binary heap timeline for deterministic event scheduling.
 pub struct Timeline {
     events: Vec<ScheduledEvent>,
     current_time: f64,
     next_sequence: u64,
     next_event_id: EventId,
-    cancelled: std::collections::HashSet<EventId>,
+    /// Heap index of every pending event.
+    positions: HashMap<EventId, usize>,
+    /// Schemas every scheduled event is checked against.
+    pub schemas: EventSchemaRegistry,
+    /// Phase of each event type, applied when an event is scheduled.
+    pub phases: PhaseTable,
+    /// Time dilation by source entity and group.
+    pub(crate) time_scales: TimeScales,
 }
 
 impl Timeline {
@@ -27,7 +81,10 @@ impl Timeline {
             current_time: 0.0,
             next_sequence: 0,
             next_event_id: 1,
-            cancelled: std::collections::HashSet::new(),
+            positions: HashMap::new(),
+            schemas: EventSchemaRegistry::new(),
+            phases: PhaseTable::new(),
+            time_scales: TimeScales::new(),
         }
     }
 
@@ -41,14 +98,22 @@ impl Timeline {
         self.current_time = time;
     }
 
-    /// Schedule an event at current_time + delay.
+    /// Schedule an event at current_time + delay. The delay is divided by the
+    /// time scale of the event's source.
     pub fn schedule_delay(&mut self, delay: f64, mut event: Event) -> EventId {
+        self.schemas.check(&event);
+        let delay = if event.source == NO_ENTITY {
+            delay
+        } else {
+            delay / self.time_scales.scale_of(event.source)
+        };
         let event_id = self.next_event_id;
         self.next_event_id += 1;
         event.event_id = event_id;
 
         let scheduled = ScheduledEvent {
             time: self.current_time + delay,
+            phase: self.phases.get(event.event_type),
             sequence: self.next_sequence,
             event,
         };
@@ -59,12 +124,14 @@ impl Timeline {
 
     /// Schedule an event at the current time (immediate).
     pub fn schedule_immediate(&mut self, mut event: Event) -> EventId {
+        self.schemas.check(&event);
         let event_id = self.next_event_id;
         self.next_event_id += 1;
         event.event_id = event_id;
 
         let scheduled = ScheduledEvent {
             time: self.current_time,
+            phase: self.phases.get(event.event_type),
             sequence: self.next_sequence,
             event,
         };
@@ -75,12 +142,14 @@ impl Timeline {
 
     /// Schedule an event at an absolute time.
     pub fn schedule_at(&mut self, time: f64, mut event: Event) -> EventId {
+        self.schemas.check(&event);
         let event_id = self.next_event_id;
         self.next_event_id += 1;
         event.event_id = event_id;
 
         let scheduled = ScheduledEvent {
             time,
+            phase: self.phases.get(event.event_type),
             sequence: self.next_sequence,
             event,
         };
@@ -90,21 +159,13 @@ impl Timeline {
     }
 
     /// Pop the next event from the timeline. Advances current_time.
-    /// Skips cancelled events.
     pub fn pop(&mut self) -> Option<Event> {
-        loop {
-            if self.events.is_empty() {
-                return None;
-            }
-            let scheduled = self.pop_min();
-            self.current_time = scheduled.time;
-
-            if self.cancelled.remove(&scheduled.event.event_id) {
-                continue; // Skip cancelled events
-            }
-
-            return Some(scheduled.event);
+        if self.events.is_empty() {
+            return None;
         }
+        let scheduled = self.remove_at(0);
+        self.current_time = scheduled.time;
+        Some(scheduled.event)
     }
 
     /// Peek at the next event without removing it.
@@ -117,12 +178,191 @@ impl Timeline {
         !self.events.is_empty()
     }
 
-    /// Cancel an event by its ID.
-    pub fn cancel(&mut self, event_id: EventId) {
-        self.cancelled.insert(event_id);
+    /// Cancel a pending event by its ID. Returns false if the event already
+    /// fired, was already cancelled, or never existed.
+    pub fn cancel(&mut self, event_id: EventId) -> bool {
+        match self.positions.get(&event_id) {
+            Some(&index) => {
+                self.remove_at(index);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Move a pending event to an absolute time. The event keeps its ID but
+    /// is ordered after events of its phase already scheduled for `new_time`,
+    /// as if it had just been scheduled. Returns false if the event is not pending.
+    pub fn reschedule(&mut self, event_id: EventId, new_time: f64) -> bool {
+        let Some(&index) = self.positions.get(&event_id) else {
+            return false;
+        };
+        self.events[index].time = new_time;
+        self.events[index].sequence = self.next_sequence;
+        self.next_sequence += 1;
+        let index = self.sift_up(index);
+        self.sift_down(index);
+        true
+    }
+
+    /// Time scales of entities and groups.
+    pub fn time_scales(&self) -> &TimeScales {
+        &self.time_scales
+    }
+
+    /// Set an entity's time scale (2.0 acts twice as fast, 0.5 half as fast).
+    /// With `rescale`, the remaining delay of every pending event the entity
+    /// sourced is stretched or shrunk as if it had been scheduled at the new
+    /// scale. Returns false, changing nothing, unless the scale is positive
+    /// and finite.
+    pub fn set_time_scale(&mut self, entity: EntityId, scale: f64, rescale: bool) -> bool {
+        if !TimeScales::is_valid(scale) {
+            return false;
+        }
+        let old = self.time_scales.scale_of(entity);
+        self.time_scales.set_entity(entity, scale);
+        if rescale {
+            let ratios = HashMap::from([(entity, old / self.time_scales.scale_of(entity))]);
+            self.rescale_pending(&ratios);
+        }
+        true
+    }
+
+    /// Set the time scale of a group, applied on top of each member's own
+    /// scale. `rescale` works as in `set_time_scale`, for every member.
+    pub fn set_group_time_scale(&mut self, group: InternedString, scale: f64, rescale: bool) -> bool {
+        if !TimeScales::is_valid(scale) {
+            return false;
+        }
+        let members: Vec<EntityId> = self.time_scales.members(group).collect();
+        let old: Vec<f64> = members.iter().map(|&e| self.time_scales.scale_of(e)).collect();
+        self.time_scales.set_group(group, scale);
+        if rescale {
+            let ratios = members
+                .iter()
+                .zip(old)
+                .map(|(&e, old)| (e, old / self.time_scales.scale_of(e)))
+                .collect();
+            self.rescale_pending(&ratios);
+        }
+        true
+    }
+
+    /// Put an entity in a time scale group, or take it out with `None`.
+    /// Pending events are not rescaled.
+    pub fn set_time_group(&mut self, entity: EntityId, group: Option<InternedString>) {
+        self.time_scales.set_membership(entity, group);
+    }
+
+    /// Multiply the remaining delay of pending events by their source's ratio.
+    fn rescale_pending(&mut self, ratios: &HashMap<EntityId, f64>) {
+        let now = self.current_time;
+        let mut changed = false;
+        for scheduled in &mut self.events {
+            if let Some(ratio) = ratios.get(&scheduled.event.source)
+                && *ratio != 1.0
+                && scheduled.time > now
+            {
+                scheduled.time = now + (scheduled.time - now) * ratio;
+                changed = true;
+            }
+        }
+        if changed {
+            for index in (0..self.events.len() / 2).rev() {
+                self.sift_down(index);
+            }
+        }
+    }
+
+    /// Check whether an event is still pending.
+    pub fn is_pending(&self, event_id: EventId) -> bool {
+        self.positions.contains_key(&event_id)
+    }
+
+    /// Scheduled time of a pending event.
+    pub fn time_of(&self, event_id: EventId) -> Option<f64> {
+        self.positions.get(&event_id).map(|&i| self.events[i].time)
+    }
+
+    /// Pending events, in heap order (not firing order).
+    pub fn pending(&self) -> impl Iterator<Item = &ScheduledEvent> {
+        self.events.iter()
+    }
+
+    /// IDs of pending events matching a predicate, in firing order.
+    pub fn find_where<F: FnMut(&ScheduledEvent) -> bool>(&self, mut predicate: F) -> Vec<EventId> {
+        let mut found: Vec<&ScheduledEvent> = self.events.iter().filter(|e| predicate(e)).collect();
+        found.sort_by(|a, b| a.fires_before(b));
+        found.into_iter().map(|e| e.event.event_id).collect()
+    }
+
+    /// A page of pending events in firing order: skip `offset` events, then
+    /// return at most `limit`.
+    pub fn upcoming(&self, offset: usize, limit: usize) -> Vec<&ScheduledEvent> {
+        let mut ordered: Vec<&ScheduledEvent> = self.events.iter().collect();
+        let end = offset.saturating_add(limit).min(ordered.len());
+        if offset >= end {
+            return Vec::new();
+        }
+        // Only the first `end` events need to be in order.
+        if end < ordered.len() {
+            ordered.select_nth_unstable_by(end - 1, |a, b| a.fires_before(b));
+            ordered.truncate(end);
+        }
+        ordered.sort_by(|a, b| a.fires_before(b));
+        ordered.drain(offset..).collect()
+    }
+
+    /// Pending events of a type, in firing order.
+    pub fn find_by_type(&self, event_type: InternedString) -> Vec<EventId> {
+        self.find_where(|e| e.event.event_type == event_type)
+    }
+
+    /// Pending events sourced from an entity, in firing order.
+    pub fn find_by_source(&self, source: EntityId) -> Vec<EventId> {
+        self.find_where(|e| e.event.source == source)
+    }
+
+    /// Pending events targeting an entity, in firing order.
+    pub fn find_by_target(&self, target: EntityId) -> Vec<EventId> {
+        self.find_where(|e| e.event.target == target)
+    }
+
+    /// Pending events referring to an entity anywhere (see `Event::references`).
+    pub fn find_referencing(&self, entity: EntityId) -> Vec<EventId> {
+        self.find_where(|e| e.event.references(entity))
+    }
+
+    /// Cancel every pending event matching a predicate. Returns how many were cancelled.
+    pub fn cancel_where<F: FnMut(&ScheduledEvent) -> bool>(&mut self, predicate: F) -> usize {
+        let ids = self.find_where(predicate);
+        for id in &ids {
+            self.cancel(*id);
+        }
+        ids.len()
+    }
+
+    /// Cancel all pending events of a type.
+    pub fn cancel_by_type(&mut self, event_type: InternedString) -> usize {
+        self.cancel_where(|e| e.event.event_type == event_type)
+    }
+
+    /// Cancel all pending events sourced from an entity.
+    pub fn cancel_by_source(&mut self, source: EntityId) -> usize {
+        self.cancel_where(|e| e.event.source == source)
     }
 
-    /// Get the number of pending events (including cancelled ones).
+    /// Cancel all pending events targeting an entity.
+    pub fn cancel_by_target(&mut self, target: EntityId) -> usize {
+        self.cancel_where(|e| e.event.target == target)
+    }
+
+    /// Cancel all pending events referring to an entity anywhere.
+    pub fn cancel_referencing(&mut self, entity: EntityId) -> usize {
+        self.cancel_where(|e| e.event.references(entity))
+    }
+
+    /// Get the number of pending events.
     pub fn len(&self) -> usize {
         self.events.len()
     }
@@ -138,36 +378,51 @@ impl Timeline {
         self.current_time = 0.0;
         self.next_sequence = 0;
         self.next_event_id = 1;
-        self.cancelled.clear();
+        self.positions.clear();
+        self.schemas.clear_violations();
+        self.time_scales.clear();
     }
 
     // ── Binary heap operations ──
 
     fn push(&mut self, event: ScheduledEvent) {
+        let index = self.events.len();
+        self.positions.insert(event.event.event_id, index);
         self.events.push(event);
-        self.sift_up(self.events.len() - 1);
+        self.sift_up(index);
     }
 
-    fn pop_min(&mut self) -> ScheduledEvent {
+    /// Remove the event at a heap index, restoring the heap property.
+    fn remove_at(&mut self, index: usize) -> ScheduledEvent {
         let last = self.events.len() - 1;
-        self.events.swap(0, last);
-        let min = self.events.pop().unwrap();
-        if !self.events.is_empty() {
-            self.sift_down(0);
+        self.swap(index, last);
+        let removed = self.events.pop().unwrap();
+        self.positions.remove(&removed.event.event_id);
+        if index < self.events.len() {
+            let index = self.sift_up(index);
+            self.sift_down(index);
         }
-        min
+        removed
     }
 
-    fn sift_up(&mut self, mut idx: usize) {
+    fn swap(&mut self, a: usize, b: usize) {
+        self.events.swap(a, b);
+        self.positions.insert(self.events[a].event.event_id, a);
+        self.positions.insert(self.events[b].event.event_id, b);
+    }
+
+    /// Move an entry towards the root. Returns its final index.
+    fn sift_up(&mut self, mut idx: usize) -> usize {
         while idx > 0 {
             let parent = (idx - 1) / 2;
             if self.compare(idx, parent) {
-                self.events.swap(idx, parent);
+                self.swap(idx, parent);
                 idx = parent;
             } else {
                 break;
             }
         }
+        idx
     }
 
     fn sift_down(&mut self, mut idx: usize) {
@@ -185,7 +440,7 @@ impl Timeline {
             }
 
             if smallest != idx {
-                self.events.swap(idx, smallest);
+                self.swap(idx, smallest);
                 idx = smallest;
             } else {
                 break;
@@ -195,13 +450,18 @@ impl Timeline {
 
     /// Returns true if event at idx_a should come before event at idx_b.
     fn compare(&self, idx_a: usize, idx_b: usize) -> bool {
-        let a = &self.events[idx_a];
-        let b = &self.events[idx_b];
-        if a.time != b.time {
-            a.time < b.time
-        } else {
-            a.sequence < b.sequence
+        self.events[idx_a].fires_before(&self.events[idx_b]).is_lt()
+    }
+}
+
+impl MarkStrings for Timeline {
+    fn mark_strings(&self, live: &mut LiveStrings) {
+        for scheduled in &self.events {
+            scheduled.event.mark_strings(live);
         }
+        self.schemas.mark_strings(live);
+        self.phases.mark_strings(live);
+        self.time_scales.mark_strings(live);
     }
 }
 
@@ -263,6 +523,93 @@ mod tests {
         assert_eq!(e3.event_type, interner.intern("Third"));
     }
 
+    #[test]
+    fn test_pop_order_matches_upcoming_for_odd_times() {
+        let mut interner = StringInterner::new();
+        let mut timeline = Timeline::new();
+        for time in [f64::NAN, 0.0, f64::INFINITY, -0.0, 1.0, f64::NAN, -0.0] {
+            timeline.schedule_at(time, make_event(&mut interner, "E"));
+        }
+        let expected: Vec<EventId> = timeline.upcoming(0, usize::MAX).iter().map(|e| e.event.event_id).collect();
+        assert_eq!(expected, vec![4, 7, 2, 5, 3, 1, 6]);
+        let mut popped = Vec::new();
+        while let Some(event) = timeline.pop() {
+            popped.push(event.event_id);
+        }
+        assert_eq!(popped, expected);
+    }
+
+    #[test]
+    fn test_phases_order_same_time_events() {
+        let mut interner = StringInterner::new();
+        let mut timeline = Timeline::new();
+        timeline
+            .phases
+            .register_json(r#"{"DotTick": "start_of_tick", "Damage": "effects", "Death": "cleanup"}"#, &mut interner)
+            .unwrap();
+
+        for name in ["Death", "Damage", "Attack", "DotTick", "Attack"] {
+            timeline.schedule_delay(1.0, make_event(&mut interner, name));
+        }
+        timeline.schedule_delay(0.5, make_event(&mut interner, "Death"));
+
+        let order: Vec<String> = std::iter::from_fn(|| timeline.pop())
+            .map(|e| interner.resolve(e.event_type).to_string())
+            .collect();
+        assert_eq!(order, ["Death", "DotTick", "Attack", "Attack", "Damage", "Death"]);
+    }
+
+    #[test]
+    fn test_time_scale_applies_to_sourced_delays() {
+        let mut interner = StringInterner::new();
+        let mut timeline = Timeline::new();
+        assert!(timeline.set_time_scale(1, 2.0, false));
+        assert!(!timeline.set_time_scale(1, 0.0, false));
+
+        let hasted = timeline.schedule_delay(1.0, make_event(&mut interner, "Attack").with_source(1));
+        let normal = timeline.schedule_delay(1.0, make_event(&mut interner, "Attack").with_source(2));
+        let unsourced = timeline.schedule_delay(1.0, make_event(&mut interner, "Tick"));
+        let immediate = timeline.schedule_immediate(make_event(&mut interner, "Now").with_source(1));
+        assert_eq!(timeline.time_of(hasted), Some(0.5));
+        assert_eq!(timeline.time_of(normal), Some(1.0));
+        assert_eq!(timeline.time_of(unsourced), Some(1.0));
+        assert_eq!(timeline.time_of(immediate), Some(0.0));
+
+        let slowed = interner.intern("slowed");
+        timeline.set_time_group(2, Some(slowed));
+        assert!(timeline.set_group_time_scale(slowed, 0.25, false));
+        let slow = timeline.schedule_delay(1.0, make_event(&mut interner, "Attack").with_source(2));
+        assert_eq!(timeline.time_of(slow), Some(4.0));
+    }
+
+    #[test]
+    fn test_time_scale_rescales_pending() {
+        let mut interner = StringInterner::new();
+        let mut timeline = Timeline::new();
+        let a = timeline.schedule_delay(4.0, make_event(&mut interner, "A").with_source(1));
+        let b = timeline.schedule_delay(3.0, make_event(&mut interner, "B").with_source(2));
+        timeline.schedule_delay(2.0, make_event(&mut interner, "Tick"));
+        timeline.pop();
+        assert_eq!(timeline.get_time(), 2.0);
+
+        // Remaining delay of A (2.0) halves; B is untouched.
+        timeline.set_time_scale(1, 2.0, true);
+        assert_eq!(timeline.time_of(a), Some(3.0));
+        assert_eq!(timeline.time_of(b), Some(3.0));
+        // Same time: scheduling order still decides.
+        assert_eq!(timeline.pop().unwrap().event_id, a);
+
+        let party = interner.intern("party");
+        timeline.set_time_group(2, Some(party));
+        let c = timeline.schedule_delay(1.0, make_event(&mut interner, "C").with_source(1));
+        timeline.set_group_time_scale(party, 0.5, true);
+        assert_eq!(timeline.time_of(b), Some(3.0));
+        assert_eq!(timeline.time_of(c), Some(3.5));
+        timeline.set_time_scale(2, 0.5, true);
+        assert_eq!(timeline.time_of(b), Some(3.0));
+        assert_eq!(timeline.pop().unwrap().event_id, b);
+    }
+
     #[test]
     fn test_cancel_event() {
         let mut interner = StringInterner::new();
@@ -271,11 +618,101 @@ mod tests {
         let id1 = timeline.schedule_delay(1.0, make_event(&mut interner, "A"));
         timeline.schedule_delay(2.0, make_event(&mut interner, "B"));
 
-        timeline.cancel(id1);
+        assert!(timeline.cancel(id1));
+        assert!(!timeline.cancel(id1));
+        assert_eq!(timeline.len(), 1);
 
         let e = timeline.pop().unwrap();
         assert_eq!(e.event_type, interner.intern("B"));
         assert_eq!(timeline.get_time(), 2.0);
+        assert!(!timeline.cancel(e.event_id));
+        assert!(timeline.is_empty());
+    }
+
+    #[test]
+    fn test_cancel_keeps_heap_order() {
+        let mut interner = StringInterner::new();
+        let mut timeline = Timeline::new();
+        let ids: Vec<EventId> = (0..20)
+            .map(|i| timeline.schedule_delay(((i * 7) % 20) as f64, make_event(&mut interner, "E")))
+            .collect();
+        for id in ids.iter().step_by(3) {
+            assert!(timeline.cancel(*id));
+        }
+        assert_eq!(timeline.len(), 13);
+        let mut last = f64::NEG_INFINITY;
+        let mut popped = 0;
+        while let Some(e) = timeline.pop() {
+            assert!(timeline.get_time() >= last);
+            assert!(!ids.iter().step_by(3).any(|id| *id == e.event_id));
+            last = timeline.get_time();
+            popped += 1;
+        }
+        assert_eq!(popped, 13);
+    }
+
+    #[test]
+    fn test_find_and_cancel_by_entity() {
+        let mut interner = StringInterner::new();
+        let mut timeline = Timeline::new();
+        let attack = interner.intern("Attack");
+        let heal = interner.intern("Heal");
+
+        let a2 = timeline.schedule_delay(2.0, Event::new(attack).with_source(1).with_target(9));
+        let a1 = timeline.schedule_delay(1.0, Event::new(attack).with_source(1).with_target(8));
+        let h = timeline.schedule_delay(1.5, Event::new(heal).with_source(2).with_target(1));
+        let other = timeline.schedule_delay(3.0, Event::new(heal).with_source(2).with_target(2));
+
+        assert_eq!(timeline.find_by_source(1), vec![a1, a2]);
+        assert_eq!(timeline.find_by_type(heal), vec![h, other]);
+        assert_eq!(timeline.find_by_target(9), vec![a2]);
+        assert_eq!(timeline.find_referencing(1), vec![a1, h, a2]);
+        assert_eq!(timeline.find_where(|e| e.time > 1.0), vec![h, a2, other]);
+
+        assert_eq!(timeline.cancel_referencing(1), 3);
+        assert_eq!(timeline.len(), 1);
+        assert_eq!(timeline.cancel_by_source(1), 0);
+        assert_eq!(timeline.pending().map(|e| e.event.event_id).collect::<Vec<_>>(), vec![other]);
+    }
+
+    #[test]
+    fn test_upcoming_pages() {
+        let mut interner = StringInterner::new();
+        let mut timeline = Timeline::new();
+        let ids: Vec<EventId> = [5.0, 1.0, 3.0, 1.0, 4.0, 2.0]
+            .iter()
+            .map(|t| timeline.schedule_delay(*t, make_event(&mut interner, "E")))
+            .collect();
+        let page = |offset, limit| -> Vec<EventId> {
+            timeline.upcoming(offset, limit).iter().map(|e| e.event.event_id).collect()
+        };
+        assert_eq!(page(0, 3), vec![ids[1], ids[3], ids[5]]);
+        assert_eq!(page(3, 3), vec![ids[2], ids[4], ids[0]]);
+        assert_eq!(page(4, 10), vec![ids[4], ids[0]]);
+        assert!(page(6, 3).is_empty());
+        assert_eq!(page(0, usize::MAX).len(), 6);
+
+        let snapshot = timeline.upcoming(0, 1)[0].snapshot(&interner);
+        assert_eq!(snapshot.event_type, "E");
+        assert_eq!(snapshot.time, 1.0);
+    }
+
+    #[test]
+    fn test_reschedule() {
+        let mut interner = StringInterner::new();
+        let mut timeline = Timeline::new();
+
+        let a = timeline.schedule_delay(1.0, make_event(&mut interner, "A"));
+        let b = timeline.schedule_delay(2.0, make_event(&mut interner, "B"));
+        timeline.schedule_delay(3.0, make_event(&mut interner, "C"));
+
+        assert!(timeline.reschedule(a, 3.0));
+        assert_eq!(timeline.time_of(a), Some(3.0));
+        assert!(timeline.reschedule(b, 0.5));
+
+        let order: Vec<EventId> = std::iter::from_fn(|| timeline.pop()).map(|e| e.event_id).collect();
+        assert_eq!(order, vec![b, 3, a]);
+        assert!(!timeline.reschedule(a, 10.0));
     }
 
     #[test]
@@ -1,9 +1,12 @@
-/// Value enum for dynamic event fields and runtime values.
-/// Component fields use native Rust types (generated structs), but event fields
-/// and some runtime contexts need a dynamic value type.
+//! Value enum for dynamic event fields and runtime values.
+//! Component fields use native Rust types (generated structs), but event fields
+//! and some runtime contexts need a dynamic value type.
 
-use crate::interning::InternedString;
-use serde::Serialize;
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+
+use crate::interning::{InternedString, LiveStrings, MarkStrings, StringInterner};
+use serde::{Deserialize, Serialize};
 
 /// Entity ID type - a simple u32 wrapper.
 pub type EntityId = u32;
@@ -12,7 +15,8 @@ pub type EntityId = u32;
 pub const NO_ENTITY: EntityId = 0;
 
 /// Dynamic value type for event fields and runtime contexts.
-#[derive(Clone, Debug, PartialEq)]
+/// Equality, ordering and arithmetic follow BRL semantics (see `value_ops`).
+#[derive(Clone, Debug, Default)]
 pub enum Value {
     Integer(i64),
     Number(f64),
@@ -20,22 +24,21 @@ pub enum Value {
     Boolean(bool),
     Entity(EntityId),
     List(Vec<Value>),
+    /// Record keyed by interned field name, e.g. damage per damage type.
+    /// Keys iterate in interning order, which is deterministic for a run.
+    Map(BTreeMap<InternedString, Value>),
+    #[default]
     None,
 }
 
-impl Default for Value {
-    fn default() -> Self {
-        Value::None
-    }
-}
-
 impl Value {
     /// Convert to i64, coercing if possible.
     pub fn as_integer(&self) -> i64 {
         match self {
             Value::Integer(v) => *v,
             Value::Number(v) => *v as i64,
-            Value::Boolean(v) => if *v { 1 } else { 0 },
+            Value::Boolean(true) => 1,
+            Value::Boolean(false) => 0,
             Value::Entity(v) => *v as i64,
             _ => 0,
         }
@@ -46,7 +49,8 @@ impl Value {
         match self {
             Value::Number(v) => *v,
             Value::Integer(v) => *v as f64,
-            Value::Boolean(v) => if *v { 1.0 } else { 0.0 },
+            Value::Boolean(true) => 1.0,
+            Value::Boolean(false) => 0.0,
             Value::Entity(v) => *v as f64,
             _ => 0.0,
         }
@@ -62,6 +66,7 @@ impl Value {
             Value::None => false,
             Value::String(s) => *s != InternedString::NONE,
             Value::List(l) => !l.is_empty(),
+            Value::Map(m) => !m.is_empty(),
         }
     }
 
@@ -82,6 +87,93 @@ impl Value {
         }
     }
 
+    /// Borrow the items of a list.
+    pub fn as_list(&self) -> Option<&[Value]> {
+        match self {
+            Value::List(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    /// Borrow the entries of a map.
+    pub fn as_map(&self) -> Option<&BTreeMap<InternedString, Value>> {
+        match self {
+            Value::Map(entries) => Some(entries),
+            _ => None,
+        }
+    }
+
+    /// Look up a key of a map. Returns `None` for missing keys and non-maps.
+    pub fn get(&self, key: InternedString) -> Option<&Value> {
+        self.as_map()?.get(&key)
+    }
+
+    /// Follow a path of keys through nested maps,
+    /// e.g. `[damage, fire]` on `{damage: {fire: 3}}` yields `3`.
+    pub fn get_path(&self, path: &[InternedString]) -> Option<&Value> {
+        path.iter().try_fold(self, |value, key| value.get(*key))
+    }
+
+    /// Deterministic total order over values, used for sorting and as the
+    /// tie-break for structural comparisons. Values of different kinds order
+    /// by kind (None < Boolean < Integer < Number < String < Entity < List < Map),
+    /// except that integers and numbers compare by value first, so `2 < 2.5 < 3`;
+    /// an integer sorts before a number of equal value. Numbers use IEEE total
+    /// ordering, strings compare by interned ID, lists and maps compare element
+    /// by element.
+    pub fn total_cmp(&self, other: &Value) -> Ordering {
+        match (self, other) {
+            (Value::None, Value::None) => Ordering::Equal,
+            (Value::Boolean(a), Value::Boolean(b)) => a.cmp(b),
+            (Value::Integer(a), Value::Integer(b)) => a.cmp(b),
+            (Value::Number(a), Value::Number(b)) => a.total_cmp(b),
+            (Value::Integer(a), Value::Number(b)) => cmp_integer_number(*a, *b).then(Ordering::Less),
+            (Value::Number(a), Value::Integer(b)) => cmp_integer_number(*b, *a).reverse().then(Ordering::Greater),
+            (Value::String(a), Value::String(b)) => a.cmp(b),
+            (Value::Entity(a), Value::Entity(b)) => a.cmp(b),
+            (Value::List(a), Value::List(b)) => a
+                .iter()
+                .zip(b)
+                .map(|(x, y)| x.total_cmp(y))
+                .find(|o| o.is_ne())
+                .unwrap_or_else(|| a.len().cmp(&b.len())),
+            (Value::Map(a), Value::Map(b)) => a
+                .iter()
+                .zip(b)
+                .map(|((ka, va), (kb, vb))| ka.cmp(kb).then_with(|| va.total_cmp(vb)))
+                .find(|o| o.is_ne())
+                .unwrap_or_else(|| a.len().cmp(&b.len())),
+            _ => self.kind_rank().cmp(&other.kind_rank()),
+        }
+    }
+
+    /// Name of the value's kind, used in error messages.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::Integer(_) => "integer",
+            Value::Number(_) => "number",
+            Value::String(_) => "string",
+            Value::Boolean(_) => "boolean",
+            Value::Entity(_) => "entity",
+            Value::List(_) => "list",
+            Value::Map(_) => "map",
+            Value::None => "none",
+        }
+    }
+
+    fn kind_rank(&self) -> u8 {
+        match self {
+            Value::None => 0,
+            Value::Boolean(_) => 1,
+            Value::Integer(_) => 2,
+            Value::Number(_) => 3,
+            Value::String(_) => 4,
+            Value::Entity(_) => 5,
+            Value::List(_) => 6,
+            Value::Map(_) => 7,
+        }
+    }
+
     /// Check if value is None.
     pub fn is_none(&self) -> bool {
         matches!(self, Value::None)
@@ -100,9 +192,131 @@ impl Value {
                     items.iter().map(|v| v.to_json_value(interner)).collect();
                 serde_json::Value::Array(arr)
             }
+            Value::Map(entries) => serde_json::Value::Object(
+                entries
+                    .iter()
+                    .map(|(k, v)| (interner.resolve(*k).to_string(), v.to_json_value(interner)))
+                    .collect(),
+            ),
             Value::None => serde_json::Value::Null,
         }
     }
+
+    /// Parse a JSON document into a value, interning strings through `interner`.
+    pub fn from_json(json: &str, interner: &mut StringInterner) -> Result<Value, String> {
+        let parsed: serde_json::Value = serde_json::from_str(json).map_err(|e| e.to_string())?;
+        Ok(Value::from_json_value(&parsed, interner))
+    }
+
+    /// Inverse of `to_json_value`, interning strings and object keys.
+    /// Integral numbers become `Integer`, other numbers `Number`.
+    pub fn from_json_value(json: &serde_json::Value, interner: &mut StringInterner) -> Value {
+        match json {
+            serde_json::Value::Null => Value::None,
+            serde_json::Value::Bool(b) => Value::Boolean(*b),
+            serde_json::Value::Number(n) => match n.as_i64() {
+                Some(i) => Value::Integer(i),
+                None => Value::Number(n.as_f64().unwrap_or(0.0)),
+            },
+            serde_json::Value::String(s) => Value::String(interner.intern(s)),
+            serde_json::Value::Array(items) => {
+                Value::List(items.iter().map(|v| Value::from_json_value(v, interner)).collect())
+            }
+            serde_json::Value::Object(entries) => Value::Map(
+                entries
+                    .iter()
+                    .map(|(k, v)| (interner.intern(k), Value::from_json_value(v, interner)))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+/// Exact order of an integer against a number, consistent with
+/// `f64::total_cmp`: NaN sorts above (or, if negative, below) every integer.
+pub(crate) fn cmp_integer_number(i: i64, n: f64) -> Ordering {
+    // 2^63, the first float above i64::MAX
+    const LIMIT: f64 = 9_223_372_036_854_775_808.0;
+    if n.is_nan() {
+        return if n.is_sign_negative() { Ordering::Greater } else { Ordering::Less };
+    }
+    if n >= LIMIT {
+        return Ordering::Less;
+    }
+    if n < -LIMIT {
+        return Ordering::Greater;
+    }
+    let whole = n.trunc();
+    i.cmp(&(whole as i64)).then(0f64.total_cmp(&(n - whole)))
+}
+
+impl MarkStrings for Value {
+    fn mark_strings(&self, live: &mut LiveStrings) {
+        match self {
+            Value::String(s) => s.mark_strings(live),
+            Value::List(items) => items.mark_strings(live),
+            Value::Map(map) => {
+                for (key, value) in map {
+                    key.mark_strings(live);
+                    value.mark_strings(live);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Declared type of a dynamically-typed field (schema-defined components).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ValueType {
+    Integer,
+    #[serde(alias = "decimal")]
+    Number,
+    String,
+    Boolean,
+    #[serde(alias = "id")]
+    Entity,
+    List,
+    Map,
+    /// No declared type; any value is accepted as-is.
+    Any,
+}
+
+impl ValueType {
+    /// Zero value of the type, matching `Default` on generated component structs.
+    pub fn default_value(self) -> Value {
+        match self {
+            ValueType::Integer => Value::Integer(0),
+            ValueType::Number => Value::Number(0.0),
+            ValueType::String => Value::String(InternedString::NONE),
+            ValueType::Boolean => Value::Boolean(false),
+            ValueType::Entity => Value::Entity(NO_ENTITY),
+            ValueType::List => Value::List(Vec::new()),
+            ValueType::Map => Value::Map(BTreeMap::new()),
+            ValueType::Any => Value::None,
+        }
+    }
+
+    /// Convert a value to this type, like the casts in generated code.
+    pub fn coerce(self, value: Value) -> Value {
+        match self {
+            ValueType::Integer => Value::Integer(value.as_integer()),
+            ValueType::Number => Value::Number(value.as_number()),
+            ValueType::String => Value::String(value.as_string_id()),
+            ValueType::Boolean => Value::Boolean(value.as_boolean()),
+            ValueType::Entity => Value::Entity(value.as_entity()),
+            ValueType::List => match value {
+                Value::List(_) => value,
+                _ => Value::List(Vec::new()),
+            },
+            ValueType::Map => match value {
+                Value::Map(_) => value,
+                _ => Value::Map(BTreeMap::new()),
+            },
+            ValueType::Any => value,
+        }
+    }
 }
 
 /// Serializable state snapshot for a single entity, used in JSON export.
@@ -113,12 +327,37 @@ pub struct EntitySnapshot {
     pub components: std::collections::HashMap<String, std::collections::HashMap<String, serde_json::Value>>,
 }
 
+/// Serializable view of one pending event, used in JSON export.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct EventSnapshot {
+    pub id: crate::event::EventId,
+    #[serde(rename = "type")]
+    pub event_type: String,
+    pub time: f64,
+    pub phase: crate::phase::Phase,
+    pub source: EntityId,
+    pub target: EntityId,
+    pub fields: std::collections::BTreeMap<String, serde_json::Value>,
+}
+
+/// A page of upcoming events in firing order.
+#[derive(Serialize, Debug)]
+pub struct UpcomingEvents {
+    /// Number of pending events in total.
+    pub total: usize,
+    /// Index of the first event of this page.
+    pub offset: usize,
+    pub events: Vec<EventSnapshot>,
+}
+
 /// Full game state snapshot for JSON export.
 #[derive(Serialize, Debug)]
 pub struct GameStateSnapshot {
     pub time: f64,
     pub entities: Vec<EntitySnapshot>,
     pub pending_events: usize,
+    /// The next few events to fire, for "next action" displays.
+    pub upcoming_events: Vec<EventSnapshot>,
 }
 
 #[cfg(test)]
@@ -126,6 +365,7 @@ mod tests {
     use super::*;
 
     #[test]
+    #[allow(clippy::approx_constant)]
     fn test_value_conversions() {
         assert_eq!(Value::Integer(42).as_integer(), 42);
         assert_eq!(Value::Integer(42).as_number(), 42.0);
@@ -143,4 +383,99 @@ mod tests {
         let v = Value::default();
         assert!(v.is_none());
     }
+
+    #[test]
+    fn test_value_type_coerce() {
+        assert_eq!(ValueType::Integer.coerce(Value::Number(7.9)), Value::Integer(7));
+        assert_eq!(ValueType::Number.coerce(Value::Integer(2)), Value::Number(2.0));
+        assert_eq!(ValueType::Entity.coerce(Value::None), Value::Entity(NO_ENTITY));
+        assert_eq!(ValueType::List.coerce(Value::Integer(1)), Value::List(vec![]));
+        assert_eq!(ValueType::Any.coerce(Value::Integer(1)), Value::Integer(1));
+        let ty: ValueType = serde_json::from_str("\"decimal\"").unwrap();
+        assert_eq!(ty, ValueType::Number);
+    }
+
+    #[test]
+    fn test_map_access_and_json_round_trip() {
+        let mut interner = StringInterner::new();
+        let json = serde_json::json!({
+            "damage": { "fire": 3, "ice": 1.5 },
+            "loot": [{ "item": "Potion", "qty": 2 }],
+            "crit": true,
+            "target": null
+        });
+        let value = Value::from_json_value(&json, &mut interner);
+        let damage = interner.intern("damage");
+        let fire = interner.intern("fire");
+        assert_eq!(value.get_path(&[damage, fire]), Some(&Value::Integer(3)));
+        assert_eq!(value.get_path(&[fire]), None);
+        assert_eq!(value.get(interner.intern("loot")).and_then(|l| l.as_list()).map(|l| l.len()), Some(1));
+        assert_eq!(value.as_map().map(|m| m.len()), Some(4));
+        assert_eq!(value.to_json_value(&interner), json);
+    }
+
+    #[test]
+    fn test_from_json_str() {
+        let mut interner = StringInterner::new();
+        let value = Value::from_json("[1, 2.5, \"x\"]", &mut interner).unwrap();
+        let x = interner.intern("x");
+        assert_eq!(value, Value::List(vec![Value::Integer(1), Value::Number(2.5), Value::String(x)]));
+        assert!(Value::from_json("[1,", &mut interner).is_err());
+    }
+
+    #[test]
+    fn test_total_cmp() {
+        let a = Value::List(vec![Value::Integer(1), Value::Integer(2)]);
+        let b = Value::List(vec![Value::Integer(1), Value::Integer(3)]);
+        assert_eq!(a.total_cmp(&b), Ordering::Less);
+        assert_eq!(a.total_cmp(&a.clone()), Ordering::Equal);
+        assert_eq!(Value::None.total_cmp(&Value::Integer(0)), Ordering::Less);
+        assert_eq!(Value::Number(f64::NAN).total_cmp(&Value::Number(f64::NAN)), Ordering::Equal);
+
+        let mut m1 = BTreeMap::new();
+        m1.insert(InternedString(1), Value::Integer(5));
+        let mut m2 = m1.clone();
+        assert_eq!(Value::Map(m1.clone()), Value::Map(m2.clone()));
+        m2.insert(InternedString(2), Value::Integer(0));
+        assert_eq!(Value::Map(m1).total_cmp(&Value::Map(m2)), Ordering::Less);
+        assert_eq!(ValueType::Map.coerce(Value::Integer(1)), Value::Map(BTreeMap::new()));
+    }
+
+    #[test]
+    fn test_total_cmp_mixed_numbers() {
+        let mut values = vec![
+            Value::Number(2.5),
+            Value::Integer(3),
+            Value::Number(f64::NAN),
+            Value::Number(2.0),
+            Value::Integer(-1),
+            Value::Number(-1.5),
+            Value::Integer(2),
+            Value::Number(f64::NEG_INFINITY),
+            Value::Integer(i64::MAX),
+            Value::Number(1e19),
+        ];
+        values.sort_by(Value::total_cmp);
+        assert_eq!(
+            values[..9],
+            [
+                Value::Number(f64::NEG_INFINITY),
+                Value::Number(-1.5),
+                Value::Integer(-1),
+                Value::Integer(2),
+                Value::Number(2.0),
+                Value::Number(2.5),
+                Value::Integer(3),
+                Value::Integer(i64::MAX),
+                Value::Number(1e19),
+            ]
+        );
+        assert!(matches!(values[9], Value::Number(n) if n.is_nan()));
+
+        // Exact above 2^53, where `as f64` would round 2^53 + 1 down.
+        let big = 1i64 << 53;
+        assert_eq!(Value::Integer(big + 1).total_cmp(&Value::Number(big as f64)), Ordering::Greater);
+        assert_eq!(Value::Number(-0.0).total_cmp(&Value::Integer(0)), Ordering::Greater);
+        assert_eq!(Value::Integer(5).total_cmp(&Value::String(InternedString(0))), Ordering::Less);
+    }
 }
@@ -0,0 +1,314 @@
+//! Arithmetic and comparison on `Value` with BRL semantics.
+//!
+//! Generated code coerces operands through `as_integer` / `as_number`, which
+//! silently turns strings and `None` into 0. The operators here check types
+//! instead and report mistakes as a `ValueError`:
+//!
+//! - integer ∘ integer stays an integer, with overflow checked;
+//! - integer ∘ number promotes to a number (IEEE semantics, so float division
+//!   by zero yields infinity like generated code);
+//! - list + list appends;
+//! - string + string / number concatenates. Concatenation has to intern its
+//!   result, so it goes through `Value::add_with`, which takes the interner;
+//!   the `Add` operator reports `ValueError::NeedsInterner` for strings.
+//!
+//! `PartialEq` and `PartialOrd` compare integers with numbers by exact value,
+//! so `Integer(1) == Number(1.0)` but `Integer(2^53 + 1) != Number(2^53)`.
+//! NaN is unequal and unordered, as in IEEE. Strings compare equal by ID but
+//! have no order without the interner; `Value::compare` resolves them.
+
+use std::cmp::Ordering;
+use std::ops::{Add, Div, Mul, Rem, Sub};
+
+use crate::interning::StringInterner;
+use crate::value::{cmp_integer_number, Value};
+
+/// Errors from operators on `Value`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ValueError {
+    /// The operator is not defined for these operand types.
+    TypeMismatch {
+        op: &'static str,
+        left: &'static str,
+        right: &'static str,
+    },
+    /// Integer result does not fit in an `i64`.
+    Overflow { op: &'static str },
+    /// Integer division or remainder by zero.
+    DivisionByZero,
+    /// String concatenation was attempted without an interner.
+    NeedsInterner,
+}
+
+impl std::fmt::Display for ValueError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ValueError::TypeMismatch { op, left, right } => {
+                write!(f, "cannot apply '{}' to {} and {}", op, left, right)
+            }
+            ValueError::Overflow { op } => write!(f, "integer overflow in '{}'", op),
+            ValueError::DivisionByZero => write!(f, "integer division by zero"),
+            ValueError::NeedsInterner => write!(f, "string concatenation needs an interner"),
+        }
+    }
+}
+
+impl std::error::Error for ValueError {}
+
+/// Apply an arithmetic operator to two numeric operands.
+fn numeric(
+    op: &'static str,
+    l: Value,
+    r: Value,
+    int_op: fn(i64, i64) -> Option<i64>,
+    float_op: fn(f64, f64) -> f64,
+) -> Result<Value, ValueError> {
+    match (&l, &r) {
+        (Value::Integer(a), Value::Integer(b)) => int_op(*a, *b)
+            .map(Value::Integer)
+            .ok_or(ValueError::Overflow { op }),
+        (Value::Integer(_) | Value::Number(_), Value::Integer(_) | Value::Number(_)) => {
+            Ok(Value::Number(float_op(l.as_number(), r.as_number())))
+        }
+        _ => Err(mismatch(op, &l, &r)),
+    }
+}
+
+/// Integer division and remainder: zero divisors are their own error,
+/// `i64::MIN / -1` overflows.
+fn integer_division(
+    op: &'static str,
+    l: Value,
+    r: Value,
+    int_op: fn(i64, i64) -> Option<i64>,
+    float_op: fn(f64, f64) -> f64,
+) -> Result<Value, ValueError> {
+    if let (Value::Integer(_), Value::Integer(0)) = (&l, &r) {
+        return Err(ValueError::DivisionByZero);
+    }
+    numeric(op, l, r, int_op, float_op)
+}
+
+fn mismatch(op: &'static str, l: &Value, r: &Value) -> ValueError {
+    ValueError::TypeMismatch {
+        op,
+        left: l.type_name(),
+        right: r.type_name(),
+    }
+}
+
+impl Add for Value {
+    type Output = Result<Value, ValueError>;
+
+    fn add(self, rhs: Value) -> Self::Output {
+        match (self, rhs) {
+            (Value::List(mut a), Value::List(b)) => {
+                a.extend(b);
+                Ok(Value::List(a))
+            }
+            (Value::String(_), _) | (_, Value::String(_)) => Err(ValueError::NeedsInterner),
+            (l, r) => numeric("+", l, r, i64::checked_add, |a, b| a + b),
+        }
+    }
+}
+
+impl Sub for Value {
+    type Output = Result<Value, ValueError>;
+
+    fn sub(self, rhs: Value) -> Self::Output {
+        numeric("-", self, rhs, i64::checked_sub, |a, b| a - b)
+    }
+}
+
+impl Mul for Value {
+    type Output = Result<Value, ValueError>;
+
+    fn mul(self, rhs: Value) -> Self::Output {
+        numeric("*", self, rhs, i64::checked_mul, |a, b| a * b)
+    }
+}
+
+impl Div for Value {
+    type Output = Result<Value, ValueError>;
+
+    fn div(self, rhs: Value) -> Self::Output {
+        integer_division("/", self, rhs, i64::checked_div, |a, b| a / b)
+    }
+}
+
+impl Rem for Value {
+    type Output = Result<Value, ValueError>;
+
+    fn rem(self, rhs: Value) -> Self::Output {
+        integer_division("%", self, rhs, i64::checked_rem, |a, b| a % b)
+    }
+}
+
+impl Value {
+    /// BRL `+`, including string concatenation. A string on either side
+    /// concatenates with a string, integer or number on the other; only the
+    /// final result is interned.
+    pub fn add_with(self, rhs: Value, interner: &mut StringInterner) -> Result<Value, ValueError> {
+        if !matches!(self, Value::String(_)) && !matches!(rhs, Value::String(_)) {
+            return self + rhs;
+        }
+        let (Some(a), Some(b)) = (concat_part(&self, interner), concat_part(&rhs, interner)) else {
+            return Err(mismatch("+", &self, &rhs));
+        };
+        Ok(Value::String(interner.intern(&(a + &b))))
+    }
+
+    /// Order two values, resolving strings through the interner so they
+    /// compare by text. Returns an error for values that have no order
+    /// (mixed kinds, maps, NaN).
+    pub fn compare(&self, other: &Value, interner: &StringInterner) -> Result<Ordering, ValueError> {
+        match (self, other) {
+            (Value::String(a), Value::String(b)) => Ok(interner.resolve(*a).cmp(interner.resolve(*b))),
+            (Value::List(a), Value::List(b)) => {
+                for (x, y) in a.iter().zip(b) {
+                    match x.compare(y, interner)? {
+                        Ordering::Equal => {}
+                        unequal => return Ok(unequal),
+                    }
+                }
+                Ok(a.len().cmp(&b.len()))
+            }
+            _ => self.partial_cmp(other).ok_or_else(|| mismatch("compare", self, other)),
+        }
+    }
+}
+
+/// Text of a concatenation operand, formatted like `brl_to_string_*`.
+fn concat_part(value: &Value, interner: &StringInterner) -> Option<String> {
+    match value {
+        Value::String(s) => Some(interner.resolve(*s).to_string()),
+        Value::Integer(v) => Some(v.to_string()),
+        Value::Number(v) if v.fract() == 0.0 && v.abs() < i64::MAX as f64 => Some((*v as i64).to_string()),
+        Value::Number(v) => Some(v.to_string()),
+        _ => None,
+    }
+}
+
+impl PartialEq for Value {
+    fn eq(&self, other: &Value) -> bool {
+        match (self, other) {
+            (Value::Integer(a), Value::Integer(b)) => a == b,
+            (Value::Number(a), Value::Number(b)) => a == b,
+            (Value::Integer(_) | Value::Number(_), Value::Integer(_) | Value::Number(_)) => {
+                self.partial_cmp(other) == Some(Ordering::Equal)
+            }
+            (Value::String(a), Value::String(b)) => a == b,
+            (Value::Boolean(a), Value::Boolean(b)) => a == b,
+            (Value::Entity(a), Value::Entity(b)) => a == b,
+            (Value::List(a), Value::List(b)) => a == b,
+            (Value::Map(a), Value::Map(b)) => a == b,
+            (Value::None, Value::None) => true,
+            _ => false,
+        }
+    }
+}
+
+impl PartialOrd for Value {
+    fn partial_cmp(&self, other: &Value) -> Option<Ordering> {
+        match (self, other) {
+            (Value::Integer(a), Value::Integer(b)) => Some(a.cmp(b)),
+            (Value::Number(a), Value::Number(b)) => a.partial_cmp(b),
+            (Value::Integer(a), Value::Number(b)) => (!b.is_nan()).then(|| cmp_integer_number(*a, *b)),
+            (Value::Number(a), Value::Integer(b)) => (!a.is_nan()).then(|| cmp_integer_number(*b, *a).reverse()),
+            (Value::Boolean(a), Value::Boolean(b)) => Some(a.cmp(b)),
+            (Value::Entity(a), Value::Entity(b)) => Some(a.cmp(b)),
+            (Value::List(a), Value::List(b)) => a.partial_cmp(b),
+            // Strings and maps are only ordered when equal; see `Value::compare`.
+            _ if self == other => Some(Ordering::Equal),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_numeric_promotion_and_overflow() {
+        assert_eq!(Value::Integer(2) + Value::Integer(3), Ok(Value::Integer(5)));
+        assert_eq!(Value::Integer(2) * Value::Number(1.5), Ok(Value::Number(3.0)));
+        assert_eq!(Value::Integer(7) / Value::Integer(2), Ok(Value::Integer(3)));
+        assert_eq!(Value::Integer(7) % Value::Number(2.0), Ok(Value::Number(1.0)));
+        assert_eq!(Value::Integer(i64::MAX) + Value::Integer(1), Err(ValueError::Overflow { op: "+" }));
+        assert_eq!(Value::Integer(i64::MIN) / Value::Integer(-1), Err(ValueError::Overflow { op: "/" }));
+        assert_eq!(Value::Integer(1) / Value::Integer(0), Err(ValueError::DivisionByZero));
+        assert_eq!(Value::Number(1.0) / Value::Integer(0), Ok(Value::Number(f64::INFINITY)));
+    }
+
+    #[test]
+    fn test_type_errors() {
+        assert_eq!(
+            Value::None - Value::Integer(1),
+            Err(ValueError::TypeMismatch { op: "-", left: "none", right: "integer" })
+        );
+        assert!((Value::Boolean(true) + Value::Integer(1)).is_err());
+        assert_eq!(
+            Value::List(vec![]) * Value::Integer(2),
+            Err(ValueError::TypeMismatch { op: "*", left: "list", right: "integer" })
+        );
+    }
+
+    #[test]
+    fn test_list_append_and_string_concat() {
+        let appended = Value::List(vec![Value::Integer(1)]) + Value::List(vec![Value::Integer(2)]);
+        assert_eq!(appended, Ok(Value::List(vec![Value::Integer(1), Value::Integer(2)])));
+
+        let mut interner = StringInterner::new();
+        let hp = Value::String(interner.intern("HP: "));
+        assert_eq!(hp.clone() + Value::Integer(1), Err(ValueError::NeedsInterner));
+        let text = hp.clone().add_with(Value::Number(12.0), &mut interner).unwrap();
+        assert_eq!(interner.resolve(text.as_string_id()), "HP: 12");
+        assert!(hp.add_with(Value::Entity(1), &mut interner).is_err());
+        assert_eq!(
+            Value::Integer(1).add_with(Value::Integer(2), &mut interner),
+            Ok(Value::Integer(3))
+        );
+    }
+
+    #[test]
+    fn test_equality_and_ordering() {
+        assert_eq!(Value::Integer(1), Value::Number(1.0));
+        assert!(Value::Integer(1) < Value::Number(1.5));
+        assert!(Value::Entity(2) > Value::Entity(1));
+        assert_ne!(Value::Integer(0), Value::None);
+        assert_eq!(Value::None.partial_cmp(&Value::Integer(0)), None);
+        assert!(Value::List(vec![Value::Integer(1)]) < Value::List(vec![Value::Integer(1), Value::Integer(0)]));
+
+        let mut interner = StringInterner::new();
+        let b = Value::String(interner.intern("b"));
+        let a = Value::String(interner.intern("a"));
+        assert_eq!(b.partial_cmp(&a), None);
+        assert_eq!(b.partial_cmp(&b.clone()), Some(Ordering::Equal));
+        assert_eq!(b.compare(&a, &interner), Ok(Ordering::Greater));
+        assert!(Value::Integer(1).compare(&a, &interner).is_err());
+    }
+
+    #[test]
+    fn test_mixed_comparison_is_exact() {
+        // 2^53 + 1 rounds to 2^53 as a float; equality must stay transitive.
+        let big = 1i64 << 53;
+        let float = Value::Number(big as f64);
+        assert_eq!(Value::Integer(big), float);
+        assert_ne!(Value::Integer(big + 1), float);
+        assert_ne!(float, Value::Integer(big + 1));
+        assert!(Value::Integer(big + 1) > float);
+        assert!(float < Value::Integer(big + 1));
+        assert_eq!(
+            Value::Integer(big + 1).partial_cmp(&float),
+            Some(Value::Integer(big + 1).total_cmp(&float))
+        );
+        assert_ne!(Value::Integer(0), Value::Number(f64::NAN));
+        assert_eq!(Value::Number(f64::NAN).partial_cmp(&Value::Integer(0)), None);
+        assert_eq!(Value::Integer(i64::MAX).partial_cmp(&Value::Number(i64::MAX as f64)), Some(Ordering::Less));
+
+        let list = [Value::Integer(big + 1), float.clone(), Value::Integer(big)];
+        assert_eq!(crate::builtins::brl_list_unique(&list), vec![Value::Integer(big + 1), float.clone()]);
+        assert_eq!(crate::builtins::brl_list_index_of(&list, &float), 1);
+    }
+}
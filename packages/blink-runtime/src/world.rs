@@ -1,13 +1,15 @@
-/// ECS World: typed component storage with entity management.
-///
-/// Unlike the JS engine's Map-of-Maps approach, the WASM engine uses a trait-based
-/// component storage system. Generated game code registers concrete component types
-/// at compile time, giving direct struct field access instead of string lookups.
-///
-/// The World provides a type-erased storage interface via `ComponentStorageMap` that
-/// generated code implements for each game's specific set of components.
+//! ECS World: typed component storage with entity management.
+//!
+//! Unlike the JS engine's Map-of-Maps approach, the WASM engine uses a trait-based
+//! component storage system. Generated game code registers concrete component types
+//! at compile time, giving direct struct field access instead of string lookups.
+//!
+//! The World provides a type-erased storage interface via `ComponentStorageMap` that
+//! generated code implements for each game's specific set of components.
 
 use std::collections::{BTreeMap, HashMap};
+use crate::dynamic::{ComponentSchema, DynamicStorage};
+use crate::interning::{LiveStrings, MarkStrings, StringInterner};
 use crate::value::EntityId;
 
 /// Trait for component storage - implemented by generated code for each component type.
@@ -17,6 +19,9 @@ pub trait ComponentStorage: std::any::Any {
     fn has(&self, entity: EntityId) -> bool;
     fn entity_ids(&self) -> Vec<EntityId>;
     fn clone_component(&mut self, source: EntityId, dest: EntityId);
+    /// Mark the interned strings held by stored components. Storages that
+    /// hold none keep the default.
+    fn mark_strings(&self, _live: &mut LiveStrings) {}
     fn as_any(&self) -> &dyn std::any::Any;
     fn as_any_mut(&mut self) -> &mut dyn std::any::Any;
 }
@@ -28,12 +33,15 @@ pub trait ComponentStorage: std::any::Any {
 /// the same seed, regardless of insertion history or process runs.
 pub struct TypedStorage<C: Clone + 'static> {
     pub data: BTreeMap<EntityId, C>,
+    /// Marks the strings of one component; set by `register_component_strings`.
+    marker: Option<fn(&C, &mut LiveStrings)>,
 }
 
 impl<C: Clone + 'static> TypedStorage<C> {
     pub fn new() -> Self {
         TypedStorage {
             data: BTreeMap::new(),
+            marker: None,
         }
     }
 
@@ -75,6 +83,14 @@ impl<C: Clone + 'static> ComponentStorage for TypedStorage<C> {
         }
     }
 
+    fn mark_strings(&self, live: &mut LiveStrings) {
+        if let Some(mark) = self.marker {
+            for component in self.data.values() {
+                mark(component, live);
+            }
+        }
+    }
+
     fn as_any(&self) -> &dyn std::any::Any {
         self
     }
@@ -90,6 +106,8 @@ pub struct World {
     next_entity_id: EntityId,
     alive: std::collections::BTreeSet<EntityId>,
     storages: HashMap<std::any::TypeId, Box<dyn ComponentStorage>>,
+    /// Schema-defined component storages, keyed by component name.
+    dynamic_storages: BTreeMap<String, DynamicStorage>,
     /// Maps entity IDs to their variable names (from BRL entity definitions).
     entity_variables: HashMap<EntityId, String>,
 }
@@ -100,6 +118,7 @@ impl World {
             next_entity_id: 1, // 0 is reserved for NO_ENTITY
             alive: std::collections::BTreeSet::new(),
             storages: HashMap::new(),
+            dynamic_storages: BTreeMap::new(),
             entity_variables: HashMap::new(),
         }
     }
@@ -107,9 +126,19 @@ impl World {
     /// Register a component storage for a type. Called by generated code at init.
     pub fn register_component<C: Clone + 'static>(&mut self) {
         let type_id = std::any::TypeId::of::<C>();
-        if !self.storages.contains_key(&type_id) {
-            self.storages
-                .insert(type_id, Box::new(TypedStorage::<C>::new()));
+        self.storages
+            .entry(type_id)
+            .or_insert_with(|| Box::new(TypedStorage::<C>::new()));
+    }
+
+    /// Register a component type that holds interned strings, so string
+    /// collection keeps them. Called by generated code for every component
+    /// with string fields.
+    pub fn register_component_strings<C: Clone + MarkStrings + 'static>(&mut self) {
+        self.register_component::<C>();
+        let storage = self.storages.get_mut(&std::any::TypeId::of::<C>());
+        if let Some(typed) = storage.and_then(|s| s.as_any_mut().downcast_mut::<TypedStorage<C>>()) {
+            typed.marker = Some(C::mark_strings);
         }
     }
 
@@ -143,11 +172,16 @@ impl World {
     }
 
     /// Despawn an entity, removing all its components.
+    /// Pending events are untouched; `Engine::despawn` can also cancel the
+    /// events that reference the entity.
     pub fn despawn(&mut self, id: EntityId) {
         self.alive.remove(&id);
         for storage in self.storages.values_mut() {
             storage.remove(id);
         }
+        for storage in self.dynamic_storages.values_mut() {
+            storage.remove(id);
+        }
         self.entity_variables.remove(&id);
     }
 
@@ -287,12 +321,95 @@ impl World {
         }
     }
 
+    /// Register a schema-defined component storage.
+    /// Returns false if a dynamic component with the same name already exists.
+    pub fn register_dynamic_component(&mut self, schema: ComponentSchema) -> bool {
+        if self.dynamic_storages.contains_key(&schema.name) {
+            return false;
+        }
+        self.dynamic_storages
+            .insert(schema.name.clone(), DynamicStorage::new(schema));
+        true
+    }
+
+    /// Get a schema-defined component storage by name.
+    pub fn dynamic_storage(&self, name: &str) -> Option<&DynamicStorage> {
+        self.dynamic_storages.get(name)
+    }
+
+    /// Get a mutable schema-defined component storage by name.
+    pub fn dynamic_storage_mut(&mut self, name: &str) -> Option<&mut DynamicStorage> {
+        self.dynamic_storages.get_mut(name)
+    }
+
+    /// Iterate over all schema-defined storages, in name order.
+    pub fn dynamic_storages(&self) -> impl Iterator<Item = (&str, &DynamicStorage)> {
+        self.dynamic_storages.iter().map(|(k, v)| (k.as_str(), v))
+    }
+
+    /// Check if an entity has a schema-defined component.
+    pub fn has_dynamic(&self, id: EntityId, name: &str) -> bool {
+        self.dynamic_storages
+            .get(name)
+            .map(|s| s.has(id))
+            .unwrap_or(false)
+    }
+
+    /// Query all entity IDs that have a schema-defined component.
+    pub fn query_dynamic(&self, name: &str) -> Vec<EntityId> {
+        self.dynamic_storages
+            .get(name)
+            .map(|s| s.entity_ids())
+            .unwrap_or_default()
+    }
+
+    /// Attach a schema-defined component from a flat JSON object (JSON bridge).
+    /// Returns false if the component name is unknown or the JSON is not an object.
+    pub fn add_dynamic_component_from_json(
+        &mut self,
+        id: EntityId,
+        name: &str,
+        json: &serde_json::Value,
+        interner: &mut StringInterner,
+    ) -> bool {
+        match self.dynamic_storages.get_mut(name) {
+            Some(storage) => storage.insert_from_json(id, json, interner),
+            None => false,
+        }
+    }
+
+    /// Serialize a schema-defined component as a JSON string (JSON bridge).
+    /// Returns `None` if the component is unknown or the entity lacks it.
+    pub fn dynamic_component_json(&self, id: EntityId, name: &str, interner: &StringInterner) -> Option<String> {
+        let fields = self.dynamic_storages.get(name)?.to_json(id, interner)?;
+        serde_json::to_string(&fields).ok()
+    }
+
+    /// Clone a schema-defined component from source entity to dest entity.
+    pub fn clone_component_by_name(&mut self, name: &str, source: EntityId, dest: EntityId) {
+        if let Some(storage) = self.dynamic_storages.get_mut(name) {
+            storage.clone_component(source, dest);
+        }
+    }
+
     /// Reset the world to empty state.
     pub fn reset(&mut self) {
         self.alive.clear();
         self.entity_variables.clear();
         self.next_entity_id = 1;
         self.storages.clear();
+        self.dynamic_storages.clear();
+    }
+}
+
+impl MarkStrings for World {
+    fn mark_strings(&self, live: &mut LiveStrings) {
+        for storage in self.storages.values() {
+            storage.mark_strings(live);
+        }
+        for storage in self.dynamic_storages.values() {
+            storage.mark_strings(live);
+        }
     }
 }
 
@@ -394,6 +511,43 @@ mod tests {
         assert_eq!(world.get_variable_name(entity), Some("hero"));
     }
 
+    #[test]
+    fn test_dynamic_alongside_typed() {
+        use crate::value::{Value, ValueType};
+
+        let mut world = World::new();
+        world.register_component::<Health>();
+        assert!(world.register_dynamic_component(
+            ComponentSchema::new("Poisoned").with_field("dps", ValueType::Number)
+        ));
+        assert!(!world.register_dynamic_component(ComponentSchema::new("Poisoned")));
+
+        let e1 = world.spawn();
+        let e2 = world.spawn();
+        world.insert(e1, Health { current: 10, max: 10 });
+        world.insert(e2, Health { current: 10, max: 10 });
+        world
+            .dynamic_storage_mut("Poisoned")
+            .unwrap()
+            .insert_fields(e2, vec![("dps", Value::Integer(3))]);
+
+        let poisoned_with_health: Vec<EntityId> = world
+            .query_component::<Health>()
+            .into_iter()
+            .filter(|&id| world.has_dynamic(id, "Poisoned"))
+            .collect();
+        assert_eq!(poisoned_with_health, vec![e2]);
+
+        let mut interner = StringInterner::new();
+        let json = serde_json::json!({ "dps": 4.5 });
+        assert!(world.add_dynamic_component_from_json(e1, "Poisoned", &json, &mut interner));
+        assert!(!world.add_dynamic_component_from_json(e1, "Unknown", &json, &mut interner));
+        assert_eq!(world.dynamic_component_json(e1, "Poisoned", &interner).unwrap(), r#"{"dps":4.5}"#);
+
+        world.despawn(e2);
+        assert_eq!(world.query_dynamic("Poisoned"), vec![e1]);
+    }
+
     #[test]
     fn test_try_get() {
         let mut world = World::new();
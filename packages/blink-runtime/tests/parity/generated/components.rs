@@ -0,0 +1,61 @@
+// Generated by blink-compiler-ts - do not edit
+#![allow(dead_code, unused_imports)]
+
+use blink_runtime::interning::{InternedString, LiveStrings, MarkStrings};
+use blink_runtime::EntityId;
+
+#[derive(Clone, Debug, Default)]
+pub struct Character {
+    pub name: InternedString,
+    pub title: InternedString,
+}
+
+impl MarkStrings for Character {
+    fn mark_strings(&self, live: &mut LiveStrings) {
+        self.name.mark_strings(live);
+        self.title.mark_strings(live);
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Health {
+    pub current: i64,
+    pub max: i64,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Combat {
+    pub damage: i64,
+    pub speed: f64,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Target {
+    pub entity: EntityId,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Team {
+    pub side: InternedString,
+}
+
+impl MarkStrings for Team {
+    fn mark_strings(&self, live: &mut LiveStrings) {
+        self.side.mark_strings(live);
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Log {
+    pub line: InternedString,
+    pub hits: i64,
+    pub rounds: i64,
+    pub dice: i64,
+}
+
+impl MarkStrings for Log {
+    fn mark_strings(&self, live: &mut LiveStrings) {
+        self.line.mark_strings(live);
+    }
+}
+
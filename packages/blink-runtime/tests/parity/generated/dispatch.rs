@@ -0,0 +1,21 @@
+// Generated by blink-compiler-ts - do not edit
+#![allow(dead_code, unused_imports)]
+
+use blink_runtime::{Engine, Event, InternedString};
+use crate::rules;
+use crate::string_ids;
+
+/// Dispatch an event to all matching rules.
+pub fn dispatch_event(event: &Event, engine: &mut Engine) {
+    let event_type = event.event_type;
+
+    if event_type == string_ids::STR_GAME_START {
+        rules::rule_anonymous_0(event, engine);
+    } else if event_type == string_ids::STR_ATTACK {
+        rules::rule_anonymous_1(event, engine);
+    } else if event_type == string_ids::STR_DEFEATED {
+        rules::rule_anonymous_2(event, engine);
+    } else if event_type == string_ids::STR_VICTORY {
+        rules::rule_anonymous_3(event, engine);
+    }
+}
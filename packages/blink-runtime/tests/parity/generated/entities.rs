@@ -0,0 +1,146 @@
+// Generated by blink-compiler-ts - do not edit
+#![allow(dead_code, unused_imports, unused_variables)]
+
+use blink_runtime::{Engine, World, InternedString, Value, EntityId};
+use crate::components::*;
+use crate::string_ids;
+
+/// Named entity IDs (populated during create_initial_entities)
+pub struct NamedEntities {
+    pub hero: EntityId,
+    pub squire: EntityId,
+    pub goblin: EntityId,
+    pub wolf: EntityId,
+    pub log: EntityId,
+}
+
+impl Default for NamedEntities {
+    fn default() -> Self {
+        NamedEntities {
+            hero: 0,
+            squire: 0,
+            goblin: 0,
+            wolf: 0,
+            log: 0,
+        }
+    }
+}
+
+/// Create all initial entities defined in BRL source.
+pub fn create_initial_entities(engine: &mut Engine) -> NamedEntities {
+    let mut named = NamedEntities::default();
+    let hero = engine.world.spawn_named("hero");
+    engine.world.insert(hero, Character {
+        name: string_ids::STR_ARIA,
+        title: string_ids::STR_,
+        ..Default::default()
+    });
+    engine.world.insert(hero, Health {
+        current: 60,
+        max: 60,
+        ..Default::default()
+    });
+    engine.world.insert(hero, Combat {
+        damage: 9,
+        speed: 1.0,
+        ..Default::default()
+    });
+    engine.world.insert(hero, Target {
+        entity: blink_runtime::NO_ENTITY,
+        ..Default::default()
+    });
+    engine.world.insert(hero, Team {
+        side: string_ids::STR_HEROES,
+        ..Default::default()
+    });
+    named.hero = hero;
+
+    let squire = engine.world.spawn_named("squire");
+    engine.world.insert(squire, Character {
+        name: string_ids::STR_BRAM,
+        title: string_ids::STR_,
+        ..Default::default()
+    });
+    engine.world.insert(squire, Health {
+        current: 35,
+        max: 35,
+        ..Default::default()
+    });
+    engine.world.insert(squire, Combat {
+        damage: 5,
+        speed: 0.7,
+        ..Default::default()
+    });
+    engine.world.insert(squire, Target {
+        entity: blink_runtime::NO_ENTITY,
+        ..Default::default()
+    });
+    engine.world.insert(squire, Team {
+        side: string_ids::STR_HEROES,
+        ..Default::default()
+    });
+    named.squire = squire;
+
+    let goblin = engine.world.spawn_named("goblin");
+    engine.world.insert(goblin, Character {
+        name: string_ids::STR_GOBLIN,
+        title: string_ids::STR_,
+        ..Default::default()
+    });
+    engine.world.insert(goblin, Health {
+        current: 40,
+        max: 40,
+        ..Default::default()
+    });
+    engine.world.insert(goblin, Combat {
+        damage: 7,
+        speed: 0.9,
+        ..Default::default()
+    });
+    engine.world.insert(goblin, Target {
+        entity: blink_runtime::NO_ENTITY,
+        ..Default::default()
+    });
+    engine.world.insert(goblin, Team {
+        side: string_ids::STR_MONSTERS,
+        ..Default::default()
+    });
+    named.goblin = goblin;
+
+    let wolf = engine.world.spawn_named("wolf");
+    engine.world.insert(wolf, Character {
+        name: string_ids::STR_WOLF,
+        title: string_ids::STR_,
+        ..Default::default()
+    });
+    engine.world.insert(wolf, Health {
+        current: 25,
+        max: 25,
+        ..Default::default()
+    });
+    engine.world.insert(wolf, Combat {
+        damage: 6,
+        speed: 0.6,
+        ..Default::default()
+    });
+    engine.world.insert(wolf, Target {
+        entity: blink_runtime::NO_ENTITY,
+        ..Default::default()
+    });
+    engine.world.insert(wolf, Team {
+        side: string_ids::STR_MONSTERS,
+        ..Default::default()
+    });
+    named.wolf = wolf;
+
+    let log = engine.world.spawn_named("log");
+    engine.world.insert(log, Log {
+        line: string_ids::STR_,
+        hits: 0,
+        rounds: 0,
+        ..Default::default()
+    });
+    named.log = log;
+
+    named
+}
@@ -0,0 +1,13 @@
+// Generated by blink-compiler-ts - do not edit
+#![allow(dead_code, unused_imports, unused_variables)]
+
+use blink_runtime::{Engine, World, InternedString, Value, EntityId, NO_ENTITY};
+use blink_runtime::builtins::*;
+use crate::components::*;
+use crate::string_ids;
+
+#[inline]
+pub fn roll_damage(base: i64, engine: &mut Engine) -> i64 {
+    base + brl_random_int((0) as i64, (4) as i64, &mut engine.rng)
+}
+
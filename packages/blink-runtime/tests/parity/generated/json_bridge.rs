@@ -0,0 +1,164 @@
+// Generated by blink-compiler-ts - do not edit
+// JSON bridge: component serialisation/deserialisation for WASM↔JS interop
+#![allow(dead_code, unused_imports)]
+
+use blink_runtime::{Engine, EntityId};
+use crate::components::*;
+
+/// Create a component from a JSON string and attach it to an entity.
+/// Names without a generated type fall back to schema-defined (dynamic) components.
+/// Returns true on success, false if the component name is unknown or the JSON is invalid.
+pub fn add_component_from_json(
+    engine: &mut Engine,
+    entity_id: EntityId,
+    component_name: &str,
+    fields_json: &str,
+) -> bool {
+    let v: serde_json::Value = match serde_json::from_str(fields_json) {
+        Ok(v) => v,
+        Err(_) => return false,
+    };
+    match component_name {
+        "Character" => {
+            let comp = Character {
+                name: engine.interner.intern(v["name"].as_str().unwrap_or("")),
+                title: engine.interner.intern(v["title"].as_str().unwrap_or("")),
+            };
+            engine.world.insert(entity_id, comp);
+            true
+        }
+        "Health" => {
+            let comp = Health {
+                current: { let _v = &v["current"]; _v.as_i64().or_else(|| _v.as_f64().map(|f| f as i64)).unwrap_or(0) },
+                max: { let _v = &v["max"]; _v.as_i64().or_else(|| _v.as_f64().map(|f| f as i64)).unwrap_or(0) },
+            };
+            engine.world.insert(entity_id, comp);
+            true
+        }
+        "Combat" => {
+            let comp = Combat {
+                damage: { let _v = &v["damage"]; _v.as_i64().or_else(|| _v.as_f64().map(|f| f as i64)).unwrap_or(0) },
+                speed: v["speed"].as_f64().unwrap_or(0.0),
+            };
+            engine.world.insert(entity_id, comp);
+            true
+        }
+        "Target" => {
+            let comp = Target {
+                entity: v["entity"].as_u64().unwrap_or(0) as EntityId,
+            };
+            engine.world.insert(entity_id, comp);
+            true
+        }
+        "Team" => {
+            let comp = Team {
+                side: engine.interner.intern(v["side"].as_str().unwrap_or("")),
+            };
+            engine.world.insert(entity_id, comp);
+            true
+        }
+        "Log" => {
+            let comp = Log {
+                line: engine.interner.intern(v["line"].as_str().unwrap_or("")),
+                hits: { let _v = &v["hits"]; _v.as_i64().or_else(|| _v.as_f64().map(|f| f as i64)).unwrap_or(0) },
+                rounds: { let _v = &v["rounds"]; _v.as_i64().or_else(|| _v.as_f64().map(|f| f as i64)).unwrap_or(0) },
+                dice: { let _v = &v["dice"]; _v.as_i64().or_else(|| _v.as_f64().map(|f| f as i64)).unwrap_or(0) },
+            };
+            engine.world.insert(entity_id, comp);
+            true
+        }
+        _ => engine.world.add_dynamic_component_from_json(entity_id, component_name, &v, &mut engine.interner),
+    }
+}
+
+/// Serialise a component to a JSON string.
+/// Returns `"{}"` if the entity does not have the component.
+pub fn get_component_json(
+    engine: &Engine,
+    entity_id: EntityId,
+    component_name: &str,
+) -> String {
+    match component_name {
+        "Character" => {
+            if let Some(c) = engine.world.try_get::<Character>(entity_id) {
+                serde_json::json!({
+                    "name": engine.interner.resolve(c.name),
+                    "title": engine.interner.resolve(c.title),
+                }).to_string()
+            } else {
+                "{}".to_string()
+            }
+        }
+        "Health" => {
+            if let Some(c) = engine.world.try_get::<Health>(entity_id) {
+                serde_json::json!({
+                    "current": c.current,
+                    "max": c.max,
+                }).to_string()
+            } else {
+                "{}".to_string()
+            }
+        }
+        "Combat" => {
+            if let Some(c) = engine.world.try_get::<Combat>(entity_id) {
+                serde_json::json!({
+                    "damage": c.damage,
+                    "speed": c.speed,
+                }).to_string()
+            } else {
+                "{}".to_string()
+            }
+        }
+        "Target" => {
+            if let Some(c) = engine.world.try_get::<Target>(entity_id) {
+                serde_json::json!({
+                    "entity": c.entity,
+                }).to_string()
+            } else {
+                "{}".to_string()
+            }
+        }
+        "Team" => {
+            if let Some(c) = engine.world.try_get::<Team>(entity_id) {
+                serde_json::json!({
+                    "side": engine.interner.resolve(c.side),
+                }).to_string()
+            } else {
+                "{}".to_string()
+            }
+        }
+        "Log" => {
+            if let Some(c) = engine.world.try_get::<Log>(entity_id) {
+                serde_json::json!({
+                    "line": engine.interner.resolve(c.line),
+                    "hits": c.hits,
+                    "rounds": c.rounds,
+                    "dice": c.dice,
+                }).to_string()
+            } else {
+                "{}".to_string()
+            }
+        }
+        _ => engine
+            .world
+            .dynamic_component_json(entity_id, component_name, &engine.interner)
+            .unwrap_or_else(|| "{}".to_string()),
+    }
+}
+
+/// Return all entity IDs that have the named component.
+/// Returns an empty Vec if the component name is unknown.
+pub fn get_entities_having_json(
+    engine: &Engine,
+    component_name: &str,
+) -> Vec<u32> {
+    match component_name {
+        "Character" => engine.world.query_component::<Character>(),
+        "Health" => engine.world.query_component::<Health>(),
+        "Combat" => engine.world.query_component::<Combat>(),
+        "Target" => engine.world.query_component::<Target>(),
+        "Team" => engine.world.query_component::<Team>(),
+        "Log" => engine.world.query_component::<Log>(),
+        _ => engine.world.query_dynamic(component_name),
+    }
+}
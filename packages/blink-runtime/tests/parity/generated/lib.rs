@@ -0,0 +1,133 @@
+// Generated by blink-compiler-ts - do not edit
+#![allow(dead_code, unused_imports)]
+
+pub mod components;
+pub mod entities;
+pub mod rules;
+pub mod functions;
+pub mod dispatch;
+pub mod json_bridge;
+
+/// Interned string IDs - initialized at engine startup.
+pub mod string_ids {
+    use blink_runtime::interning::InternedString;
+
+    pub static STR_: InternedString = InternedString(0);
+    pub static STR__OF_THE_: InternedString = InternedString(1);
+    pub static STR__WIN: InternedString = InternedString(2);
+    pub static STR__: InternedString = InternedString(3);
+    pub static STR_4D6KH3_2: InternedString = InternedString(4);
+    pub static STR_ATTACK: InternedString = InternedString(5);
+    pub static STR_DEFEATED: InternedString = InternedString(6);
+    pub static STR_GAME_START: InternedString = InternedString(7);
+    pub static STR_HEALTH: InternedString = InternedString(8);
+    pub static STR_VICTORY: InternedString = InternedString(9);
+    pub static STR_ARIA: InternedString = InternedString(10);
+    pub static STR_BRAM: InternedString = InternedString(11);
+    pub static STR_CURRENT: InternedString = InternedString(12);
+    pub static STR_GOBLIN: InternedString = InternedString(13);
+    pub static STR_HEROES: InternedString = InternedString(14);
+    pub static STR_MONSTERS: InternedString = InternedString(15);
+    pub static STR_SOURCE: InternedString = InternedString(16);
+    pub static STR_WOLF: InternedString = InternedString(17);
+    pub static STR____HITS____FOR___: InternedString = InternedString(18);
+}
+
+use blink_runtime::{Engine, Event, InternedString, Value};
+use crate::components::*;
+
+/// Register all component types with the engine.
+pub fn register_components(engine: &mut Engine) {
+    engine.world.register_component_strings::<Character>();
+    engine.world.register_component::<Health>();
+    engine.world.register_component::<Combat>();
+    engine.world.register_component::<Target>();
+    engine.world.register_component_strings::<Team>();
+    engine.world.register_component_strings::<Log>();
+}
+
+/// Event schemas from `event` declarations, in the format
+/// `EventSchemaRegistry::register_json` reads and the host type-checks against.
+pub const EVENT_SCHEMAS_JSON: &str = r#"[]"#;
+
+/// Register event schemas so scheduled events are validated.
+pub fn register_event_schemas(engine: &mut Engine) {
+    engine
+        .timeline
+        .schemas
+        .register_json(EVENT_SCHEMAS_JSON, &mut engine.interner)
+        .expect("generated event schemas are valid");
+}
+
+/// Same-time phases from `event Name phase <phase>` declarations, in the
+/// format `PhaseTable::register_json` reads.
+pub const EVENT_PHASES_JSON: &str = r#"{}"#;
+
+/// Map event types to their phases so same-time events fire in phase order.
+pub fn register_event_phases(engine: &mut Engine) {
+    engine
+        .timeline
+        .phases
+        .register_json(EVENT_PHASES_JSON, &mut engine.interner)
+        .expect("generated event phases are valid");
+}
+
+/// Initialize the string intern table with all known string literals.
+pub fn init_string_table(engine: &mut Engine) {
+    // NOTE: "" (empty string) is pre-reserved at ID 0 by the runtime.
+    // Re-interning it is harmless (returns 0) but we skip it for clarity.
+    engine.interner.intern(" of the ");
+    engine.interner.intern(" win");
+    engine.interner.intern(".");
+    engine.interner.intern("4d6kh3+2");
+    engine.interner.intern("Attack");
+    engine.interner.intern("Defeated");
+    engine.interner.intern("GameStart");
+    engine.interner.intern("Health");
+    engine.interner.intern("Victory");
+    engine.interner.intern("aria");
+    engine.interner.intern("bram");
+    engine.interner.intern("current");
+    engine.interner.intern("goblin");
+    engine.interner.intern("heroes");
+    engine.interner.intern("monsters");
+    engine.interner.intern("source");
+    engine.interner.intern("wolf");
+    engine.interner.intern("{} hits {} for {}");
+}
+
+/// Initialize the game: register components, intern strings, register event
+/// schemas and phases, create entities.
+pub fn init_game(engine: &mut Engine) -> entities::NamedEntities {
+    register_components(engine);
+    init_string_table(engine);
+    register_event_schemas(engine);
+    register_event_phases(engine);
+    // Everything interned so far is static; later strings can be collected.
+    engine.interner.mark_static();
+    entities::create_initial_entities(engine)
+}
+
+/// Process one event from the timeline.
+/// Returns true if an event was processed, false if timeline is empty
+/// or the engine is suspended on a pending choice.
+pub fn step(engine: &mut Engine) -> bool {
+    if let Some(event) = engine.next_event() {
+        dispatch::dispatch_event(&event, engine);
+        true
+    } else {
+        false
+    }
+}
+
+/// Process up to max_steps events. Returns the number of events processed.
+pub fn run_steps(engine: &mut Engine, max_steps: u32) -> u32 {
+    let mut count = 0;
+    for _ in 0..max_steps {
+        if !step(engine) {
+            break;
+        }
+        count += 1;
+    }
+    count
+}
@@ -0,0 +1,92 @@
+// Generated by blink-compiler-ts - do not edit
+#![allow(dead_code, unused_imports, unused_variables, unused_mut)]
+
+use blink_runtime::{Engine, World, Timeline, Event, InternedString, Value, EntityId, NO_ENTITY};
+use blink_runtime::builtins::*;
+use crate::components::*;
+use crate::functions::*;
+use crate::string_ids;
+
+pub fn rule_anonymous_0(event: &Event, engine: &mut Engine) {
+    let gs_source = event.source;
+    let entity_ids = engine.world.query_component::<Combat>();
+    for entity_id in entity_ids {
+        if engine.world.has::<Combat>(entity_id) {
+            engine.world.get_mut::<Character>(entity_id).title = brl_concat(brl_concat(brl_str_capitalize(engine.world.get::<Character>(entity_id).name, &mut engine.interner), string_ids::STR__OF_THE_, &mut engine.interner), engine.world.get::<Team>(entity_id).side, &mut engine.interner);
+            {
+                let mut sched_event = blink_runtime::Event::new(string_ids::STR_ATTACK);
+                sched_event.source = entity_id as EntityId;
+                engine.timeline.schedule_delay(engine.world.get::<Combat>(entity_id).speed as f64, sched_event);
+            }
+        }
+    }
+}
+
+pub fn rule_anonymous_1(event: &Event, engine: &mut Engine) {
+    let a_source = event.source;
+    let mut attacker = event.source;
+    if engine.world.get::<Health>(attacker).current <= 0 {
+        return;
+    }
+    if engine.world.get::<Target>(attacker).entity == blink_runtime::NO_ENTITY {
+        for e in brl_list_sort_by(&(engine.world.query_component::<Health>()), |e| engine.world.get::<Health>(*e).current.clone(), &engine.interner).iter().copied() {
+            if engine.world.get::<Target>(attacker).entity == blink_runtime::NO_ENTITY && engine.world.get::<Health>(e).current > 0 && engine.world.get::<Team>(e).side != engine.world.get::<Team>(attacker).side {
+                engine.world.get_mut::<Target>(attacker).entity = e;
+            }
+        }
+    }
+    let mut target = engine.world.get::<Target>(attacker).entity;
+    if target == blink_runtime::NO_ENTITY {
+        {
+            let mut sched_event = blink_runtime::Event::new(string_ids::STR_VICTORY);
+            sched_event.source = attacker as EntityId;
+            engine.timeline.schedule_immediate(sched_event);
+        }
+        return;
+    }
+    let mut dmg = roll_damage(engine.world.get::<Combat>(attacker).damage, engine);
+    engine.world.get_mut::<Health>(target).current = (brl_max((engine.world.get::<Health>(target).current - dmg) as f64, (0) as f64)) as i64;
+    let l_ids = engine.world.query_component::<Log>();
+    for l in l_ids {
+        engine.world.get_mut::<Log>(l).hits += 1;
+        engine.world.get_mut::<Log>(l).line = brl_format(string_ids::STR____HITS____FOR___, &Value::List(vec![Value::String(engine.world.get::<Character>(attacker).title), Value::String(brl_str_upper(engine.world.get::<Character>(target).name, &mut engine.interner)), Value::Integer(dmg)]), |w, e| w.try_get::<Character>(e).map(|c| c.name).filter(|s| *s != InternedString::NONE), engine);
+    }
+    if engine.world.get::<Health>(target).current == 0 {
+        {
+            let mut sched_event = blink_runtime::Event::new(string_ids::STR_DEFEATED);
+            sched_event.source = target as EntityId;
+            engine.timeline.schedule_immediate(sched_event);
+        }
+        engine.world.get_mut::<Target>(attacker).entity = blink_runtime::NO_ENTITY;
+    }
+    {
+        let mut sched_event = blink_runtime::Event::new(string_ids::STR_ATTACK);
+        sched_event.source = attacker as EntityId;
+        engine.timeline.schedule_delay(engine.world.get::<Combat>(attacker).speed as f64, sched_event);
+    }
+}
+
+pub fn rule_anonymous_2(event: &Event, engine: &mut Engine) {
+    let d_source = event.source;
+    let mut fallen = event.source;
+    let e_ids = engine.world.query_component::<Target>();
+    for e in e_ids {
+        if engine.world.get::<Target>(e).entity == fallen {
+            engine.world.get_mut::<Target>(e).entity = blink_runtime::NO_ENTITY;
+        }
+    }
+}
+
+pub fn rule_anonymous_3(event: &Event, engine: &mut Engine) {
+    let v_source = event.source;
+    let entity_ids = engine.world.query_component::<Log>();
+    for entity_id in entity_ids {
+        if engine.world.has::<Log>(entity_id) {
+            engine.world.get_mut::<Log>(entity_id).rounds = engine.world.get::<Log>(entity_id).hits;
+            engine.world.get_mut::<Log>(entity_id).line = brl_concat(brl_str_pad_end(engine.world.get::<Team>(event.source).side, (10) as i64, string_ids::STR__, &mut engine.interner), string_ids::STR__WIN, &mut engine.interner);
+            let mut breakdown = brl_roll_breakdown(string_ids::STR_4D6KH3_2, &mut engine.rng, &mut engine.interner);
+            engine.world.get_mut::<Log>(entity_id).dice = (breakdown).len() as i64;
+        }
+    }
+}
+
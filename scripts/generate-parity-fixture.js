@@ -0,0 +1,53 @@
+#!/usr/bin/env node
+/**
+ * generate-parity-fixture.js
+ *
+ * Compile packages/blink-runtime/tests/parity/parity.brl with the TypeScript
+ * compiler to both backends: IR JSON for the interpreter and generated Rust
+ * for the compiled path. `cargo test --test parity` runs the two side by side.
+ *
+ * Run after changing the compiler or the fixture:
+ *   npm run build:compiler:ts
+ *   node scripts/generate-parity-fixture.js
+ */
+
+'use strict';
+
+const fs   = require('fs');
+const path = require('path');
+
+const ROOT        = path.resolve(__dirname, '..');
+const FIXTURE_DIR = path.join(ROOT, 'packages', 'blink-runtime', 'tests', 'parity');
+const SOURCE      = path.join(FIXTURE_DIR, 'parity.brl');
+
+const { compile, compileToRust } = require(
+  path.join(ROOT, 'packages', 'blink-compiler-ts', 'dist', 'index.js')
+);
+
+const sources = [{
+  path: 'parity.brl',
+  content: fs.readFileSync(SOURCE, 'utf8'),
+  language: 'brl',
+}];
+
+function fail(errors) {
+  errors.forEach(e => console.error(`  ${e.file || '?'}:${e.line || '?'}: ${e.message}`));
+  process.exit(1);
+}
+
+const ir = compile(sources, { moduleName: 'parity' });
+if (ir.errors.length > 0) fail(ir.errors);
+// Drop the timestamp so regenerating an unchanged fixture is a no-op.
+delete ir.ir.metadata;
+fs.writeFileSync(path.join(FIXTURE_DIR, 'parity.ir.json'), JSON.stringify(ir.ir, null, 2) + '\n');
+
+const rust = compileToRust(sources, { moduleName: 'parity' });
+if (rust.errors.length > 0) fail(rust.errors);
+const generatedDir = path.join(FIXTURE_DIR, 'generated');
+fs.rmSync(generatedDir, { recursive: true, force: true });
+fs.mkdirSync(generatedDir, { recursive: true });
+for (const [filename, content] of rust.files) {
+  fs.writeFileSync(path.join(generatedDir, filename), content);
+}
+
+console.log(`Wrote parity.ir.json and ${rust.files.size} generated Rust files to ${path.relative(ROOT, FIXTURE_DIR)}`);